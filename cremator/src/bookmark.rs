@@ -0,0 +1,55 @@
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+use std::{fmt, str::FromStr};
+
+const SCHEME: &str = "hlife://";
+
+// "look at this exact spot", shared as hlife://<content hash>/<generation>/<x>,<y>/<zoom>
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bookmark {
+    pub content_hash: u64,
+    pub generation: u128,
+    pub position: (f32, f32),
+    pub zoom: f32,
+}
+
+impl fmt::Display for Bookmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{:016x}/{}/{},{}/{}",
+            SCHEME, self.content_hash, self.generation, self.position.0, self.position.1, self.zoom
+        )
+    }
+}
+
+impl FromStr for Bookmark {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let token = s
+            .strip_prefix(SCHEME)
+            .ok_or_else(|| eyre!("bookmarks start with {}", SCHEME))?;
+        let parts: Vec<&str> = token.split('/').collect();
+        let [hash, generation, position, zoom] = parts[..] else {
+            return Err(eyre!("malformed bookmark \"{}\"", s));
+        };
+        let (x, y) = position
+            .split_once(',')
+            .ok_or_else(|| eyre!("malformed position \"{}\"", position))?;
+
+        Ok(Self {
+            content_hash: u64::from_str_radix(hash, 16).wrap_err("invalid content hash")?,
+            generation: generation.parse().wrap_err("invalid generation")?,
+            position: (
+                x.parse().wrap_err("invalid position")?,
+                y.parse().wrap_err("invalid position")?,
+            ),
+            zoom: zoom.parse().wrap_err("invalid zoom")?,
+        })
+    }
+}
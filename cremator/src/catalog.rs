@@ -0,0 +1,78 @@
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+use std::{ffi::OsStr, path::Path};
+
+use la::{
+    patterns,
+    store::{Archive, Directory, PatternStore},
+    Pattern,
+};
+
+// the patterns cycled through with p and stamped with P, either the embedded ones or those of a
+// directory or zip archive
+pub struct Catalog {
+    // the embedded patterns if there is none
+    store: Option<Box<dyn PatternStore>>,
+    names: Vec<String>,
+    selected: usize,
+}
+
+impl Catalog {
+    pub fn embedded() -> Self {
+        Self {
+            store: None,
+            names: patterns::catalog()
+                .iter()
+                .map(|entry| entry.name.to_owned())
+                .collect(),
+            selected: 0,
+        }
+    }
+
+    // a .zip file is read as an archive, anything else as a directory
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut store: Box<dyn PatternStore> =
+            if path.extension().and_then(OsStr::to_str) == Some("zip") {
+                Box::new(Archive::open(path)?)
+            } else {
+                Box::new(Directory::new(path))
+            };
+        let names = store.names()?;
+        if names.is_empty() {
+            return Err(eyre!("no patterns found"));
+        }
+        Ok(Self {
+            store: Some(store),
+            names,
+            selected: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn selected_name(&self) -> &str {
+        &self.names[self.selected]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.names.len();
+    }
+
+    // patterns of a store are only read once they are stamped
+    pub fn selected_pattern(&mut self) -> Result<Pattern> {
+        let name = &self.names[self.selected];
+        match &mut self.store {
+            Some(store) => store
+                .load(name)
+                .wrap_err_with(|| format!("failed to load {}", name)),
+            None => Ok(patterns::get(name)
+                .expect("embedded pattern names are known")
+                .pattern()),
+        }
+    }
+}
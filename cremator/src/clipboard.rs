@@ -0,0 +1,44 @@
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+use std::borrow::Cow;
+
+use arboard::{Clipboard, ImageData};
+use glium::Display;
+
+use la::Universe;
+
+use crate::screenshot;
+
+// on X11 the copied content is served by the clipboard instance, so it has to stay alive
+pub fn copy_rle(clipboard: &mut Clipboard, universe: &Universe) -> Result<()> {
+    clipboard
+        .set_text(universe.to_rle())
+        .wrap_err("failed to copy rle to the clipboard")
+}
+
+pub fn copy_text(clipboard: &mut Clipboard, text: String) -> Result<()> {
+    clipboard
+        .set_text(text)
+        .wrap_err("failed to copy text to the clipboard")
+}
+
+pub fn paste_text(clipboard: &mut Clipboard) -> Result<String> {
+    clipboard
+        .get_text()
+        .wrap_err("failed to read text from the clipboard")
+}
+
+// copies what is currently shown in the window
+pub fn copy_viewport(clipboard: &mut Clipboard, display: &Display) -> Result<()> {
+    let screenshot = screenshot::capture(display)?;
+    clipboard
+        .set_image(ImageData {
+            width: screenshot.width,
+            height: screenshot.height,
+            bytes: Cow::Owned(screenshot.bytes),
+        })
+        .wrap_err("failed to copy the viewport to the clipboard")
+}
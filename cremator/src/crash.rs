@@ -0,0 +1,224 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    env,
+    fmt::{self, Write as _},
+    fs, io,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use imgui::{im_str, Ui, Window};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::launcher;
+
+// log messages kept for the report
+const LOGGED_ACTIONS: usize = 64;
+// names the report which hasn't been shown yet
+const PENDING_FILE: &str = "last-crash";
+
+// the latest messages of at least info level, which is what the controls log
+static ACTIONS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+// the latest status line, with the engine statistics
+static STATUS: Mutex<String> = Mutex::new(String::new());
+// the first panic is the interesting one, the others tend to be poisoned locks
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+// CREMATOR_DATA names the directory for crash reports instead of cremator in the data directory
+// of the user
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("CREMATOR_DATA") {
+        return Some(PathBuf::from(dir));
+    }
+    let dir = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(dir.join("cremator"))
+}
+
+// chains a hook after the default one, which writes a report of the first panic into `dir`
+pub fn install(dir: PathBuf) {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default(info);
+        if REPORTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        match write_report(&dir, &report(info)) {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(err) => eprintln!("failed to write a crash report: {}", err),
+        }
+    }));
+}
+
+// remembered for the report, the hud refreshes it twice a second
+pub fn set_status(status: &str) {
+    let mut latest = STATUS.lock().unwrap();
+    latest.clear();
+    latest.push_str(status);
+}
+
+// the report of the last crash if it hasn't been shown yet, it is only offered once
+pub fn take_pending(dir: &Path) -> Option<PathBuf> {
+    let pending = dir.join(PENDING_FILE);
+    let path = PathBuf::from(fs::read_to_string(&pending).ok()?.trim_end());
+    if let Err(err) = fs::remove_file(&pending) {
+        warn!("failed to remove {}: {}", pending.display(), err);
+    }
+    Some(path).filter(|path| path.exists())
+}
+
+fn report(info: &PanicHookInfo<'_>) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    let mut report = String::new();
+    // writing into a string doesn't fail
+    let _ = write_sections(&mut report, info, message);
+    report
+}
+
+fn write_sections(report: &mut String, info: &PanicHookInfo<'_>, message: &str) -> fmt::Result {
+    writeln!(
+        report,
+        "{} {} crashed",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )?;
+    write!(
+        report,
+        "thread '{}' panicked",
+        thread::current().name().unwrap_or("<unnamed>")
+    )?;
+    if let Some(location) = info.location() {
+        write!(report, " at {}", location)?;
+    }
+    writeln!(report, ":\n{}\n", message)?;
+    // the panicking thread may hold one of the locks
+    match STATUS.try_lock() {
+        Ok(status) if !status.is_empty() => writeln!(report, "status: {}\n", status)?,
+        Ok(_) => {}
+        Err(_) => writeln!(report, "status: unavailable\n")?,
+    }
+    writeln!(report, "recent actions:")?;
+    match ACTIONS.try_lock() {
+        Ok(actions) => {
+            for action in actions.iter() {
+                writeln!(report, "  {}", action)?;
+            }
+        }
+        Err(_) => writeln!(report, "  unavailable")?,
+    }
+    writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture())
+}
+
+// writes the report next to the earlier ones and marks it as not shown yet
+fn write_report(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let path = dir.join(format!("crash-{}.txt", secs));
+    fs::write(&path, report)?;
+    fs::write(dir.join(PENDING_FILE), path.to_string_lossy().as_bytes())?;
+    Ok(path)
+}
+
+// keeps the latest messages for the report
+pub struct ActionLog;
+
+impl<S: Subscriber> Layer<S> for ActionLog {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() > Level::INFO {
+            return;
+        }
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        let mut actions = ACTIONS.lock().unwrap();
+        if actions.len() == LOGGED_ACTIONS {
+            actions.pop_front();
+        }
+        actions.push_back(format!("{} {}", event.metadata().level(), message.0));
+    }
+}
+
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        }
+    }
+}
+
+// tells about the crash of the last run and offers to open its report
+pub struct CrashNotice {
+    report: PathBuf,
+}
+
+impl CrashNotice {
+    pub fn new(report: PathBuf) -> Self {
+        Self { report }
+    }
+
+    // lays out the window, returns whether it stays open
+    pub fn build(&self, ui: &Ui) -> bool {
+        let (mut open, mut dismissed) = (true, false);
+        Window::new(im_str!("Crash report"))
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.text("cremator crashed the last time it ran, sorry about that.");
+                ui.text("A report was written to");
+                ui.text_disabled(self.report.to_string_lossy());
+                ui.text("Attaching it to a bug report helps a lot.");
+                if ui.button(im_str!("Open report"), [0.0, 0.0]) {
+                    if let Err(err) = launcher::open(&self.report) {
+                        error!("failed to open {}: {}", self.report.display(), err);
+                    }
+                }
+                ui.same_line(0.0);
+                if ui.button(im_str!("Dismiss"), [0.0, 0.0]) {
+                    dismissed = true;
+                }
+            });
+        open && !dismissed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_offered_once() {
+        let dir = env::temp_dir().join(format!("cremator-crash-{}", std::process::id()));
+        let path = write_report(&dir, "report").unwrap();
+        assert_eq!(take_pending(&dir), Some(path.clone()));
+        assert_eq!(take_pending(&dir), None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "report");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -9,56 +9,163 @@ pub use tracing::{
     warn_span,
 };
 
-use std::{io::prelude::*, time::Instant};
+use std::{
+    io::prelude::*,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
+use arboard::Clipboard;
 use glium::{
     glutin::{
         self,
-        event::{Event, WindowEvent},
+        event::{ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
         window::WindowBuilder,
     },
-    Display,
+    Display, Frame,
 };
 
-use la::{Cell, Universe};
+use la::{format, Cell, KernelKind, PasteMode, Pattern, Position, Rect, SetOp, Snapshot, Universe};
+
+use crate::{
+    bookmark::Bookmark,
+    catalog::Catalog,
+    clipboard,
+    crash::{self, CrashNotice},
+    graphics::{gui::Gui, inspector::Inspector, renderer::Renderer},
+    history::History,
+    hud::Hud,
+    identifier::Identified,
+    loader::Loader,
+    scheduler::{FrameBudget, Scheduler},
+    screenshot::{self, Metadata},
+    settings::{Settings, SettingsPanel},
+    simulation::SimulationThread,
+};
 
-use crate::graphics::renderer::Renderer;
+// frame rate cap of the low power mode
+const LOW_POWER_FRAME_TIME: Duration = Duration::from_millis(1000 / 15);
+// generations covered by the ghost layer
+const ENVELOPE_GENERATIONS: u64 = 64;
+// edits that can be undone
+const UNDO_DEPTH: usize = 100;
+// generations between two states kept for rewinding
+const TIMELINE_INTERVAL: u128 = 1000;
+// cells around the cursor pinned to the inspector when nothing is selected
+const INSPECTOR_RADIUS: i64 = 16;
 
 pub struct Cremator {
     display: Display,
     event_loop: EventLoop<()>,
     renderer: Renderer,
+    scheduler: Arc<Mutex<Scheduler>>,
+    // owns the live universe
+    simulation: SimulationThread,
 
-    universe: Universe,
+    // latest state published by the simulation, which is what gets drawn
+    view: Arc<Universe>,
+    // state before entering the sandbox, restored when the sandbox is discarded
+    sandbox: Option<Snapshot>,
+    history: History,
+    clipboard: Option<Clipboard>,
+    hud: Hud,
+    // patterns which get stamped at the cursor
+    catalog: Catalog,
+    // cells copied from the selection
+    yanked: Option<Pattern>,
+    // name of the loaded pattern, used for exported images
+    name: Option<String>,
+    // pattern file which is still being read
+    loader: Option<Loader>,
+    gui: Gui,
+    settings: Settings,
+    // where the settings are kept, nowhere if there is no configuration directory
+    settings_path: Option<PathBuf>,
+    settings_panel: SettingsPanel,
+    // shown once after a crash
+    crash_notice: Option<CrashNotice>,
+    // the object last asked about
+    identified: Option<Identified>,
+    // changed in the panel but not written to the file yet
+    settings_unsaved: bool,
 
     tick_count: u64,
     _last_tick: Instant,
+
+    // caps the frame rate and only redraws when something changed
+    low_power: bool,
+    dirty: bool,
 }
 
 impl Cremator {
     pub fn new() -> Cremator {
+        let settings_path = Settings::path();
+        let settings = settings_path
+            .as_deref()
+            .map(|path| {
+                Settings::load(path).unwrap_or_else(|err| {
+                    warn!("{:?}", err);
+                    Settings::default()
+                })
+            })
+            .unwrap_or_default();
+
         // graphics context creation
         let event_loop = EventLoop::new();
-        let context = glutin::ContextBuilder::new().with_vsync(true);
+        let context = glutin::ContextBuilder::new().with_vsync(settings.vsync);
         let builder = WindowBuilder::new()
             .with_title(env!("CARGO_PKG_NAME"))
             .with_inner_size(glutin::dpi::LogicalSize::new(1600f64, 1200f64));
         let display =
             Display::new(builder, context, &event_loop).expect("Failed to create display");
-        let renderer = Renderer::init(&display);
+        let mut renderer = Renderer::init(&display);
+        let gui = Gui::new(&display);
 
         // universe creation
-        let mut universe = Universe::new();
-        universe.initialize();
+        let mut universe = Universe::builder()
+            .history_depth(UNDO_DEPTH)
+            .timeline_interval(TIMELINE_INTERVAL)
+            .kernel(kernel_override())
+            .build();
+        info!("evolving leaves with the {} kernel", universe.kernel());
+        let history = History::new(&mut universe);
+        let scheduler = Arc::new(Mutex::new(Scheduler::new(FrameBudget::default())));
+        let simulation = SimulationThread::spawn(universe, Arc::clone(&scheduler));
+        Self::apply_settings(None, &settings, &simulation, &mut renderer);
+        let view = simulation.take_latest().unwrap();
 
         Cremator {
             display,
             event_loop,
             renderer,
-            universe,
+            scheduler,
+            simulation,
+            view,
+            sandbox: None,
+            history,
+            clipboard: Clipboard::new()
+                .map_err(|err| warn!("clipboard unavailable: {}", err))
+                .ok(),
+            hud: Hud::new(),
+            catalog: pattern_catalog(),
+            yanked: None,
+            name: None,
+            loader: None,
+            gui,
+            settings,
+            settings_path,
+            settings_panel: SettingsPanel::new(),
+            crash_notice: crash::data_dir()
+                .and_then(|dir| crash::take_pending(&dir))
+                .map(CrashNotice::new),
+            identified: None,
+            settings_unsaved: false,
             tick_count: 0,
             _last_tick: Instant::now(),
+            low_power: std::env::var_os("CREMATOR_LOW_POWER").is_some(),
+            dirty: true,
         }
     }
 
@@ -67,21 +174,129 @@ impl Cremator {
             display,
             event_loop,
             mut renderer,
-            mut universe,
+            scheduler,
+            simulation,
+            mut view,
+            mut sandbox,
+            mut history,
+            mut clipboard,
+            mut hud,
+            mut catalog,
+            mut yanked,
+            mut name,
+            mut loader,
+            mut gui,
+            mut settings,
+            settings_path,
+            mut settings_panel,
+            mut crash_notice,
+            mut identified,
+            mut settings_unsaved,
             mut tick_count,
             mut _last_tick,
+            mut low_power,
+            mut dirty,
         } = self;
+        let mut modifiers = ModifiersState::empty();
         event_loop.run(move |event, _, control_flow| match event {
+            // the gui windows see the events first and keep those they use
+            event
+                if (settings_panel.is_open() || crash_notice.is_some() || identified.is_some())
+                    && gui.handle_event(&display, &event) =>
+            {
+                dirty = true
+            }
             // beginning
             Event::NewEvents(_) => {
                 _last_tick = Instant::now();
                 tick_count = tick_count.wrapping_add(1);
             }
             // updating
-            Event::MainEventsCleared => Self::update(&mut universe, &mut renderer, &tick_count),
+            Event::MainEventsCleared => {
+                renderer.update();
+                if let Some(result) = loader.as_mut().and_then(|l| l.poll(&simulation)) {
+                    let path = loader.take().unwrap().path().to_owned();
+                    match result {
+                        Ok(header) => {
+                            if let Some(rule) = header.rule {
+                                simulation.edit(|universe| universe.set_rule(rule));
+                            }
+                            name = header.name.or_else(|| {
+                                path.file_stem()
+                                    .map(|stem| stem.to_string_lossy().into_owned())
+                            });
+                            info!("loaded {}", path.display());
+                        }
+                        Err(err) => error!("{:?}", err),
+                    }
+                }
+                dirty |= renderer.set_pending(simulation.pending_areas());
+                let refreshed = hud.refresh();
+                if let Some(latest) = simulation.take_latest() {
+                    renderer.push_trail(&view);
+                    view = latest;
+                    renderer.update_inspector(&view);
+                    dirty = true;
+                    Self::update_title(&display, &view, &hud, &simulation, loader.as_ref());
+                } else if refreshed {
+                    Self::update_title(&display, &view, &hud, &simulation, loader.as_ref());
+                }
+                if *control_flow != ControlFlow::Exit {
+                    *control_flow = if low_power {
+                        ControlFlow::WaitUntil(_last_tick + LOW_POWER_FRAME_TIME)
+                    } else {
+                        ControlFlow::Poll
+                    };
+                }
+            }
             // rendering
-            Event::RedrawRequested(_) => Self::render(&mut renderer, &universe, &display),
-            Event::RedrawEventsCleared => display.gl_window().window().request_redraw(),
+            Event::RedrawRequested(_) => {
+                let start = Instant::now();
+                if settings_panel.is_open() {
+                    Self::sync_settings(&mut settings, &view, &simulation);
+                }
+                let synced = settings.clone();
+                Self::render(&mut renderer, &view, &display, |frame| {
+                    if settings_panel.is_open() || crash_notice.is_some() || identified.is_some() {
+                        gui.render(&display, frame, |ui| {
+                            if settings_panel.is_open() {
+                                settings_panel.build(ui, &mut settings);
+                            }
+                            if crash_notice.as_ref().is_some_and(|notice| !notice.build(ui)) {
+                                crash_notice = None;
+                            }
+                            if identified.as_ref().is_some_and(|object| !object.build(ui)) {
+                                identified = None;
+                            }
+                        });
+                    }
+                });
+                if settings != synced {
+                    Self::apply_settings(Some(&synced), &settings, &simulation, &mut renderer);
+                    settings_unsaved = true;
+                }
+                // written once the window is closed rather than on every move of a slider
+                if settings_unsaved && !settings_panel.is_open() {
+                    Self::save_settings(&settings, settings_path.as_deref());
+                    settings_unsaved = false;
+                }
+                let mut scheduler = scheduler.lock().unwrap();
+                scheduler.record_render(start.elapsed());
+                scheduler.end_frame();
+                hud.frame();
+                dirty = false;
+            }
+            Event::RedrawEventsCleared => {
+                if dirty || !low_power {
+                    display.gl_window().window().request_redraw()
+                }
+            }
+            Event::LoopDestroyed => {
+                if settings_unsaved {
+                    Self::sync_settings(&mut settings, &view, &simulation);
+                    Self::save_settings(&settings, settings_path.as_deref());
+                }
+            }
             // window events
             Event::WindowEvent {
                 event: WindowEvent::CloseRequested,
@@ -91,61 +306,541 @@ impl Cremator {
                 event: WindowEvent::ReceivedCharacter('x'),
                 ..
             } => *control_flow = ControlFlow::Exit,
+            // simulation controls
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(' '),
+                ..
+            } => simulation.controls().toggle_running(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('.'),
+                ..
+            } => simulation.controls().single_step(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(']'),
+                ..
+            } => simulation.controls().grow_step(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('['),
+                ..
+            } => simulation.controls().shrink_step(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('A'),
+                ..
+            } => simulation.controls().toggle_auto(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('T'),
+                ..
+            } => simulation.controls().speed_up(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('t'),
+                ..
+            } => simulation.controls().slow_down(),
+            // frame budget
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('='),
+                ..
+            } => scheduler.lock().unwrap().grow_simulation_budget(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('-'),
+                ..
+            } => scheduler.lock().unwrap().shrink_simulation_budget(),
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('l'),
+                ..
+            } => {
+                low_power = !low_power;
+                info!("low power mode: {}", low_power);
+            }
+            // settings window
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('S'),
+                ..
+            } => {
+                settings_panel.toggle();
+                dirty = true;
+            }
+            // clipboard
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('c'),
+                ..
+            } => {
+                if let Some(clipboard) = &mut clipboard {
+                    if let Err(err) = clipboard::copy_rle(clipboard, &view) {
+                        error!("{:?}", err);
+                    }
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('C'),
+                ..
+            } => {
+                if let Some(clipboard) = &mut clipboard {
+                    if let Err(err) = clipboard::copy_viewport(clipboard, &display) {
+                        error!("{:?}", err);
+                    }
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('e'),
+                ..
+            } => match Self::export_viewport(&display, &view, &renderer, &name) {
+                Ok(path) => info!("exported {}", path.display()),
+                Err(err) => error!("{:?}", err),
+            },
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('i'),
+                ..
+            } => simulation.with_universe(Self::log_stats),
+            // sandbox
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('b'),
+                ..
+            } => {
+                if sandbox.is_none() {
+                    sandbox = Some(simulation.edit(|universe| universe.snapshot()));
+                    info!("entered sandbox");
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('k'),
+                ..
+            } => {
+                if let Some(snapshot) = sandbox.take() {
+                    simulation.edit(|universe| universe.release(snapshot));
+                    info!("kept sandbox");
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('u'),
+                ..
+            } => {
+                if let Some(snapshot) = sandbox.take() {
+                    simulation.edit(|universe| {
+                        universe.restore(&snapshot);
+                        universe.release(snapshot);
+                    });
+                    info!("discarded sandbox");
+                }
+            }
+            // history tree
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c @ ('n' | '{' | '}' | '<' | '>')),
+                ..
+            } => {
+                simulation.edit(|universe| match c {
+                    'n' => history.branch(universe),
+                    '{' => history.goto_parent(universe),
+                    '}' => history.goto_child(universe),
+                    '<' => history.goto_sibling(universe, -1),
+                    _ => history.goto_sibling(universe, 1),
+                });
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('h'),
+                ..
+            } => info!("history:\n{}", history.render(&view)),
+            // timeline, back to the previous snapshot or to the first one
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c @ ('r' | 'R')),
+                ..
+            } => simulation.edit(|universe| {
+                let generation = universe.generation();
+                let timeline = universe.timeline();
+                let target = match c {
+                    'r' => timeline.generations().filter(|&g| g < generation).last(),
+                    _ => timeline.generations().next(),
+                };
+                match target {
+                    Some(target) if universe.rewind_to(target) => {
+                        info!("rewound to generation {}", format::engineering(target))
+                    }
+                    _ => info!("nothing to rewind to"),
+                }
+            }),
+            // ghost layer
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('g'),
+                ..
+            } => {
+                let ghost = if renderer.has_ghost() {
+                    None
+                } else {
+                    info!("computing envelope of {} generations", ENVELOPE_GENERATIONS);
+                    Some(view.envelope(ENVELOPE_GENERATIONS))
+                };
+                renderer.set_ghost(ghost);
+                dirty = true;
+            }
+            // trail of the recently published states
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('H'),
+                ..
+            } => {
+                info!("trail: {}", renderer.toggle_trail());
+                dirty = true;
+            }
+            // inspector, pins the selection or the cells around the cursor
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('f'),
+                ..
+            } => {
+                let rect = renderer.selection().or_else(|| {
+                    renderer.cursor_cell(&display).map(|pos| {
+                        Rect::new(
+                            (pos.x - INSPECTOR_RADIUS, pos.y - INSPECTOR_RADIUS),
+                            (pos.x + INSPECTOR_RADIUS, pos.y + INSPECTOR_RADIUS),
+                        )
+                    })
+                });
+                if let Some(rect) = rect {
+                    info!(
+                        "inspecting {}x{} cells at {:?}",
+                        rect.width(),
+                        rect.height(),
+                        rect.min
+                    );
+                    renderer.set_inspector(Some(Inspector::new(rect, &view)));
+                    dirty = true;
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('F'),
+                ..
+            } => {
+                if renderer.inspector().is_some() {
+                    renderer.set_inspector(None);
+                    dirty = true;
+                }
+            }
+            // what is the object under the cursor
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('?'),
+                ..
+            } => {
+                if let Some(pos) = renderer.cursor_cell(&display) {
+                    identified = Self::identify(&view, pos);
+                    dirty = true;
+                }
+            }
+            // bookmarks
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('m'),
+                ..
+            } => {
+                if let Some(clipboard) = &mut clipboard {
+                    let bookmark = Self::bookmark(&view, &renderer).to_string();
+                    info!("bookmark: {}", bookmark);
+                    if let Err(err) = clipboard::copy_text(clipboard, bookmark) {
+                        error!("{:?}", err);
+                    }
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('o'),
+                ..
+            } => {
+                if let Some(clipboard) = &mut clipboard {
+                    let opened = clipboard::paste_text(clipboard)
+                        .and_then(|text| text.parse())
+                        .map(|bookmark| {
+                            simulation.edit(|universe| {
+                                Self::open_bookmark(universe, &mut renderer, bookmark)
+                            })
+                        });
+                    if let Err(err) = opened {
+                        error!("{:?}", err);
+                    }
+                }
+            }
+            // pattern catalog
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('p'),
+                ..
+            } => {
+                catalog.select_next();
+                info!("selected {}", catalog.selected_name());
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('P'),
+                ..
+            } => {
+                if let Some(pos) = renderer.cursor_cell(&display) {
+                    match catalog.selected_pattern() {
+                        Ok(pattern) => {
+                            let area = Self::pattern_area(&pattern, pos);
+                            simulation
+                                .queue_edit(area, move |universe| universe.stamp(&pattern, pos));
+                        }
+                        Err(err) => error!("{:?}", err),
+                    }
+                }
+            }
+            // selection
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter('y'),
+                ..
+            } => {
+                if let Some(rect) = renderer.selection() {
+                    let pattern = view.extract(rect);
+                    info!("copied {} cells", pattern.cells.len());
+                    yanked = Some(pattern);
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c @ ('v' | 'V' | 'z')),
+                ..
+            } => {
+                let mode = match c {
+                    'v' => PasteMode::Or,
+                    'V' => PasteMode::Overwrite,
+                    _ => PasteMode::Xor,
+                };
+                if let (Some(pattern), Some(pos)) = (&yanked, renderer.cursor_cell(&display)) {
+                    let (area, pattern) = (Self::pattern_area(pattern, pos), pattern.clone());
+                    simulation.queue_edit(area, move |universe| {
+                        universe.paste(&pattern, pos, mode)
+                    });
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c @ ('d' | 'D')),
+                ..
+            } => {
+                // clears the selection, or fills it
+                let op = if c == 'd' { SetOp::Difference } else { SetOp::Union };
+                if let Some(rect) = renderer.selection() {
+                    simulation.queue_edit(Some(rect), move |universe| {
+                        let mut region = Universe::new();
+                        region.map_region(rect, |_, _| Cell::Alive);
+                        universe.combine(&region, op);
+                    });
+                }
+            }
+            // undo and redo of edits
+            Event::WindowEvent {
+                event: WindowEvent::ModifiersChanged(state),
+                ..
+            } => modifiers = state,
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key @ (VirtualKeyCode::Z | VirtualKeyCode::Y)),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } if modifiers.ctrl() => {
+                let done = simulation.edit(|universe| match key {
+                    VirtualKeyCode::Z => universe.undo(),
+                    _ => universe.redo(),
+                });
+                if !done {
+                    info!("nothing to {}", if key == VirtualKeyCode::Z { "undo" } else { "redo" });
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                ..
+            } => dirty = true,
             // hand over any left over events
-            event => renderer.handle_event(event, &display), // TODO: handle any other event
+            event => {
+                dirty |= renderer.handle_event(event, &display); // TODO: handle any other event
+                // shift-clicking asks what the object is instead of toggling the cell
+                match renderer.take_clicked_cell() {
+                    Some(pos) if modifiers.shift() => {
+                        identified = Self::identify(&view, pos);
+                        dirty = true;
+                    }
+                    Some(pos) => {
+                        simulation.queue_edit(Some(Rect::new(pos, pos)), move |universe| {
+                            universe.set_cell(pos, !universe.get_cell(pos))
+                        });
+                    }
+                    None => {}
+                }
+            }
         })
     }
 
-    fn update(universe: &mut Universe, renderer: &mut Renderer, tick_count: &u64) {
-        if tick_count % 10 == 0 {
-            universe.evolve();
+    fn update_title(
+        display: &Display,
+        universe: &Universe,
+        hud: &Hud,
+        simulation: &SimulationThread,
+        loader: Option<&Loader>,
+    ) {
+        let stats = simulation.stats();
+        let activity = simulation.activity();
+        let mut text = hud.text(universe, &simulation.controls(), &stats, &activity);
+        if let Some(loader) = loader {
+            text = format!("loading {:.0}% | {}", loader.progress() * 100.0, text);
         }
-        renderer.update();
+        display
+            .gl_window()
+            .window()
+            .set_title(&format!("{} | {}", env!("CARGO_PKG_NAME"), text));
+        crash::set_status(&text);
+    }
+
+    // saves what is shown as a png in the working directory
+    fn export_viewport(
+        display: &Display,
+        universe: &Universe,
+        renderer: &Renderer,
+        name: &Option<String>,
+    ) -> Result<PathBuf> {
+        let camera = renderer.camera();
+        let metadata = Metadata {
+            name: name.clone(),
+            rule: universe.rule(),
+            generation: universe.generation(),
+            position: camera.position,
+            zoom: camera.zoom_level,
+        };
+        screenshot::capture(display)?.save(Path::new("."), &metadata)
+    }
+
+    // the cells a pattern placed at `pos` covers
+    fn pattern_area(pattern: &Pattern, pos: Position) -> Option<Rect> {
+        pattern.bounds().map(|bounds| {
+            Rect::new(
+                (bounds.min.x + pos.x, bounds.min.y + pos.y),
+                (bounds.max.x + pos.x, bounds.max.y + pos.y),
+            )
+        })
     }
 
-    pub fn render(renderer: &mut Renderer, universe: &Universe, display: &Display) {
-        renderer.render(universe, display);
+    fn identify(universe: &Universe, pos: Position) -> Option<Identified> {
+        let identified = Identified::at(universe, pos)?;
+        info!("{} at {:?}", identified.title(), pos);
+        Some(identified)
     }
 
-    pub fn read_rls(&mut self, pattern: &str) {
-        let (mut x, mut y) = (0i64, 0i64);
-        let mut argument: u32 = 0;
+    fn bookmark(universe: &Universe, renderer: &Renderer) -> Bookmark {
+        let camera = renderer.camera();
+        Bookmark {
+            content_hash: universe.content_hash(),
+            generation: universe.generation(),
+            position: camera.position,
+            zoom: camera.zoom_level,
+        }
+    }
+
+    // generations in the past can't be reached, only the view is restored then
+    fn open_bookmark(universe: &mut Universe, renderer: &mut Renderer, bookmark: Bookmark) {
+        if bookmark.generation >= universe.generation() {
+            universe.advance_to(bookmark.generation);
+        } else {
+            warn!(
+                "bookmark is at generation {}, which has already passed",
+                format::engineering(bookmark.generation)
+            );
+        }
+        if universe.content_hash() != bookmark.content_hash {
+            warn!("bookmark was taken from a different pattern");
+        }
+        let camera = renderer.camera_mut();
+        camera.position = bookmark.position;
+        camera.zoom_level = bookmark.zoom;
+    }
 
-        for line in pattern.lines() {
-            if line.starts_with('x') || line.starts_with('#') {
-                continue;
+    fn log_stats(universe: &Universe) {
+        let stats = universe.stats();
+        info!(
+            "generation {}, {} nodes, cache hit rate {:.1}%, {:?} spent evolving",
+            format::engineering(stats.generation),
+            format::si(stats.nodes as u128),
+            stats.cache_hit_rate() * 100.0,
+            stats.evolve_time
+        );
+    }
+
+    pub fn render(
+        renderer: &mut Renderer,
+        universe: &Universe,
+        display: &Display,
+        overlay: impl FnOnce(&mut Frame),
+    ) {
+        renderer.render(universe, display, overlay);
+    }
+
+    // the engine settings follow what the shortcuts and loaded patterns did in the meantime
+    fn sync_settings(settings: &mut Settings, universe: &Universe, simulation: &SimulationThread) {
+        let controls = simulation.controls();
+        settings.rule = universe.rule();
+        settings.step_log = controls.step_log();
+        settings.rate = controls.rate();
+        settings.auto_step = controls.is_auto();
+    }
+
+    // applies what differs from `previous`, or everything without it
+    fn apply_settings(
+        previous: Option<&Settings>,
+        settings: &Settings,
+        simulation: &SimulationThread,
+        renderer: &mut Renderer,
+    ) {
+        let all = previous.is_none();
+        let previous = previous.unwrap_or(settings);
+        if all || previous.rule != settings.rule {
+            simulation.edit(|universe| universe.set_rule(settings.rule));
+        }
+        if all || previous.gc_threshold() != settings.gc_threshold() {
+            simulation.edit(|universe| universe.set_gc_threshold(settings.gc_threshold()));
+        }
+        {
+            let mut controls = simulation.controls();
+            if all || previous.step_log != settings.step_log {
+                controls.set_step_log(settings.step_log);
+            }
+            if all || previous.rate != settings.rate {
+                controls.set_rate(settings.rate);
             }
-            let line = line.trim();
-            for c in line.chars() {
-                let parameter: u32 = if argument == 0 { 1 } else { argument };
+            // after the step, as choosing a step leaves the automatic mode like the shortcuts do
+            if all || previous.auto_step != settings.auto_step {
+                controls.set_auto(settings.auto_step);
+            }
+        }
+        renderer.set_theme(settings.theme);
+        renderer.set_grid(settings.grid);
+        renderer.set_input(settings.camera_speed, settings.wheel_zoom());
+    }
 
-                match c {
-                    'b' => {
-                        x += parameter as i64;
-                        argument = 0;
-                    }
-                    'o' => {
-                        for _ in 0..parameter {
-                            self.universe.set_cell((x, y), Cell::Alive);
-                            x += 1;
-                        }
-                        argument = 0
-                    }
-                    '$' => {
-                        y += parameter as i64;
-                        x = 0;
-                        argument = 0;
-                    }
-                    '!' => return,
-                    _ if c.is_digit(10) => {
-                        argument = 10 * argument + c.to_digit(10).unwrap();
-                    }
-                    _ => panic!("invalid char"),
-                }
+    fn save_settings(settings: &Settings, path: Option<&Path>) {
+        if let Some(path) = path {
+            match settings.save(path) {
+                Ok(()) => info!("saved settings to {}", path.display()),
+                Err(err) => error!("{:?}", err),
             }
         }
     }
 
+    pub fn read_rls(&mut self, pattern: &str) -> Result<()> {
+        let header = self
+            .simulation
+            .edit(|universe| universe.read_rle(pattern.as_bytes(), Position::ORIGIN, |_| {}))
+            .wrap_err("failed to load rle pattern")?;
+        if header.name.is_some() {
+            self.name = header.name;
+        }
+        Ok(())
+    }
+
+    // loads the file in the background while the simulation is paused, the progress is shown
+    // in the title
+    pub fn load_file(&mut self, path: &Path) -> Result<()> {
+        self.loader = Some(Loader::spawn(path)?);
+        let mut controls = self.simulation.controls();
+        if controls.is_running() {
+            controls.toggle_running();
+        }
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub fn read_rls_from_stdin(&mut self) -> Result<()> {
         let mut string = String::new();
@@ -161,8 +856,36 @@ impl Cremator {
             }
         }
 
-        self.read_rls(&string);
+        self.read_rls(&string)
+    }
+}
 
-        Ok(())
+// CREMATOR_PATTERNS names a directory or zip archive of patterns to stamp instead of the
+// embedded ones
+fn pattern_catalog() -> Catalog {
+    let path = match std::env::var_os("CREMATOR_PATTERNS") {
+        Some(path) => PathBuf::from(path),
+        None => return Catalog::embedded(),
+    };
+    match Catalog::open(&path) {
+        Ok(catalog) => {
+            info!("{} patterns in {}", catalog.len(), path.display());
+            catalog
+        }
+        Err(err) => {
+            warn!("failed to open {}: {:?}", path.display(), err);
+            Catalog::embedded()
+        }
+    }
+}
+
+// CREMATOR_KERNEL names the leaf kernel instead of the one detected for the cpu
+fn kernel_override() -> KernelKind {
+    match std::env::var("CREMATOR_KERNEL") {
+        Ok(kernel) => kernel.parse().unwrap_or_else(|err| {
+            warn!("{}", err);
+            KernelKind::Auto
+        }),
+        Err(_) => KernelKind::Auto,
     }
 }
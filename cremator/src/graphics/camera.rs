@@ -1,7 +1,11 @@
-use super::cell_renderer::{Vertex, CELL_PADDING, CELL_SIZE};
-use la::Position;
+use super::{
+    cell_renderer::{CELL_PADDING, CELL_SIZE},
+    projection::{Point, Projection, Viewport},
+};
+use la::{Position, Rect};
 
 pub const CAMERA_SPEED: f32 = 1.0;
+// zoom per notch of the mouse wheel
 pub const ZOOM_FACTOR: f32 = 1.1;
 
 pub struct Camera {
@@ -17,48 +21,63 @@ impl Camera {
         }
     }
 
-    pub fn x_range(&self) -> std::ops::Range<i64> {
-        let cell_size = (CELL_SIZE + CELL_PADDING) as f32 * self.zoom_level;
-        let x_count = 2.0 / cell_size;
-        let min = self.position.0 as i64 - (x_count / 2.0).ceil() as i64 - 1;
-        let max = self.position.0 as i64 + (x_count / 2.0).ceil() as i64 + 1;
-        min..max
+    fn cell_scale(&self) -> f32 {
+        (CELL_SIZE + CELL_PADDING) * self.zoom_level
     }
 
-    pub fn y_range(&self) -> std::ops::Range<i64> {
-        let cell_size = (CELL_SIZE + CELL_PADDING) as f32 * self.zoom_level;
-        let y_count = 2.0 / cell_size;
-        let min = self.position.1 as i64 - (y_count / 2.0).ceil() as i64 - 1;
-        let max = self.position.1 as i64 + (y_count / 2.0).ceil() as i64 + 1;
-        min..max
+    pub fn projection(&self, viewport: Viewport) -> Projection {
+        Projection {
+            center: self.position,
+            scale: self.cell_scale(),
+            viewport,
+        }
     }
 
-    pub fn project(&self, pos: impl Into<Position>) -> Vec<Vertex> {
-        let pos = pos.into();
+    // world coordinates of a point on the screen before snapping to a cell
+    pub fn screen_to_world(&self, screen: Point, viewport: Viewport) -> (f32, f32) {
+        self.projection(viewport).screen_to_world(screen)
+    }
 
-        // TODO: safe check conversion
-        let mut point: (f32, f32) = (pos.x as f32, pos.y as f32);
+    // the cell under a point on the screen, cells are centered on their integer position
+    pub fn unproject(&self, screen: Point, viewport: Viewport) -> Position {
+        let (x, y) = self.screen_to_world(screen, viewport);
+        Position::new(x.round() as i64, y.round() as i64)
+    }
 
-        // translate to camera pos
-        point.0 -= self.position.0;
-        point.1 -= self.position.1;
+    // zooms by `factor` while the world point under `anchor` stays where it is
+    pub fn zoom_at(&mut self, factor: f32, anchor: Point, viewport: Viewport) {
+        let before = self.screen_to_world(anchor, viewport);
+        self.zoom_level *= factor;
+        let after = self.screen_to_world(anchor, viewport);
+        self.position.0 += before.0 - after.0;
+        self.position.1 += before.1 - after.1;
+    }
 
-        // quadtree pos -> pixel pos
-        point = (
-            point.0 * (CELL_SIZE + CELL_PADDING),
-            point.1 * (CELL_SIZE + CELL_PADDING),
+    // all cells which are at least partially visible
+    pub fn visible_world_rect(&self, viewport: Viewport) -> Rect {
+        let (width, height) = (f64::from(viewport.width), f64::from(viewport.height));
+        let corner = self.unproject(Point { x: 0.0, y: 0.0 }, viewport);
+        let opposite = self.unproject(
+            Point {
+                x: width,
+                y: height,
+            },
+            viewport,
         );
+        let rect = Rect::new(corner, opposite);
+        Rect::new(
+            (rect.min.x - 1, rect.min.y - 1),
+            (rect.max.x + 1, rect.max.y + 1),
+        )
+    }
 
-        // scale to zoom
-        point = (point.0 * self.zoom_level, point.1 * self.zoom_level);
-
-        let half_size = CELL_SIZE * self.zoom_level / 2.0;
-
-        let nw = Vertex::new(point.0 - half_size, point.1 + half_size);
-        let sw = Vertex::new(point.0 - half_size, point.1 - half_size);
-        let se = Vertex::new(point.0 + half_size, point.1 - half_size);
-        let ne = Vertex::new(point.0 + half_size, point.1 + half_size);
+    // world to normalized device coordinates for the shaders
+    pub fn matrix(&self, viewport: Viewport) -> [[f32; 4]; 4] {
+        self.projection(viewport).matrix()
+    }
 
-        vec![nw, sw, ne, ne, sw, se]
+    // size of a cell in physical pixels
+    pub fn cell_pixels(&self, viewport: Viewport) -> f32 {
+        self.projection(viewport).cell_pixels()
     }
 }
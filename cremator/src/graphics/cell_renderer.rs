@@ -1,18 +1,46 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
 use glium::{
-    glutin::event::{Event, WindowEvent},
-    Display, Frame, Program, Surface,
+    glutin::event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    index::{NoIndices, PrimitiveType},
+    uniform, Display, DrawParameters, Frame, Program, Surface, VertexBuffer,
 };
 
-use la::{Cell, Universe};
+use la::{Cell, Position, Rect, Universe};
 
-use super::camera::{Camera, CAMERA_SPEED, ZOOM_FACTOR};
+use super::{
+    camera::{Camera, CAMERA_SPEED, ZOOM_FACTOR},
+    projection::{Point, Projection, Viewport},
+    theme::{Palette, Theme},
+};
 
 pub const CELL_SIZE: f32 = 0.02;
 pub const CELL_PADDING: f32 = 0.005;
+// the grid is left out when the cells are smaller than this many pixels
+const GRID_MIN_PIXELS: f32 = 6.0;
 
 pub struct CellRenderer {
     program: Program,
+    // unit square which is instanced once per cell or block
+    quad: VertexBuffer<Vertex>,
     camera: Camera,
+    // last known cursor position
+    cursor: Option<Point>,
+    clicked: Option<Position>,
+    // cell where the right mouse button was pressed, while dragging
+    drag_start: Option<Position>,
+    selection: Option<Rect>,
+    palette: Palette,
+    // lines between the cells when zoomed in
+    grid: bool,
+    // cells moved per key press
+    camera_speed: f32,
+    // zoom per notch of the mouse wheel, below one to zoom out when scrolling up
+    zoom_factor: f32,
 }
 
 impl CellRenderer {
@@ -20,109 +48,436 @@ impl CellRenderer {
         let vertex_shader = r#"
             #version 140
 
-            in vec2 position;
+            uniform mat4 matrix;
+            in vec2 corner;
+            in vec2 center;
+            in vec2 size;
+            in float shade;
+            out float v_shade;
 
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0);
+                v_shade = shade;
+                gl_Position = matrix * vec4(center + corner * size, 0.0, 1.0);
             }
-        
         "#;
 
         let fragment_shader = r#"
             #version 140
 
+            uniform vec4 cell_color;
+            in float v_shade;
             out vec4 color;
 
             void main() {
-                color = vec4(1.0, 0.0, 0.0, 1.0);
+                color = vec4(cell_color.rgb * v_shade, cell_color.a);
             }
         "#;
 
         let program = Program::from_source(display, vertex_shader, fragment_shader, None).unwrap();
+        let quad = VertexBuffer::new(
+            display,
+            &[
+                Vertex::new(-0.5, -0.5),
+                Vertex::new(0.5, -0.5),
+                Vertex::new(-0.5, 0.5),
+                Vertex::new(0.5, 0.5),
+            ],
+        )
+        .unwrap();
 
         let camera = Camera::new();
 
-        CellRenderer { program, camera }
+        CellRenderer {
+            program,
+            quad,
+            camera,
+            cursor: None,
+            clicked: None,
+            drag_start: None,
+            selection: None,
+            palette: Theme::default().palette(),
+            grid: false,
+            camera_speed: CAMERA_SPEED,
+            zoom_factor: ZOOM_FACTOR,
+        }
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn set_grid(&mut self, grid: bool) {
+        self.grid = grid;
+    }
+
+    pub fn set_input(&mut self, camera_speed: f32, zoom_factor: f32) {
+        self.camera_speed = camera_speed;
+        self.zoom_factor = zoom_factor;
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    pub fn take_clicked_cell(&mut self) -> Option<Position> {
+        self.clicked.take()
+    }
+
+    pub fn selection(&self) -> Option<Rect> {
+        self.selection
+    }
+
+    // the cell under the cursor
+    pub fn cursor_cell(&self, display: &Display) -> Option<Position> {
+        self.cursor
+            .map(|cursor| self.camera.unproject(cursor, viewport(display)))
     }
 
-    pub fn handle_event(&mut self, event: Event<()>, _display: &Display) {
+    pub fn handle_event(&mut self, event: Event<()>, display: &Display) -> bool {
         match event {
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                self.cursor = Some(Point {
+                    x: position.x,
+                    y: position.y,
+                });
+                match (self.drag_start, self.cursor_cell(display)) {
+                    (Some(start), Some(end)) => self.selection = Some(Rect::new(start, end)),
+                    _ => return false,
+                }
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(cursor) = self.cursor {
+                    self.clicked = Some(self.camera.unproject(cursor, viewport(display)));
+                }
+                return false;
+            }
+            // dragging with the right mouse button selects a rectangle, a click clears it
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Right,
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    self.drag_start = self.cursor_cell(display);
+                    self.selection = None;
+                }
+                ElementState::Released => self.drag_start = None,
+            },
             Event::WindowEvent {
                 event: WindowEvent::ReceivedCharacter('w'),
                 ..
-            } => self.camera.position.1 -= CAMERA_SPEED,
+            } => self.camera.position.1 -= self.camera_speed,
             Event::WindowEvent {
                 event: WindowEvent::ReceivedCharacter('s'),
                 ..
-            } => self.camera.position.1 += CAMERA_SPEED,
+            } => self.camera.position.1 += self.camera_speed,
             Event::WindowEvent {
                 event: WindowEvent::ReceivedCharacter('a'),
                 ..
-            } => self.camera.position.0 -= CAMERA_SPEED,
+            } => self.camera.position.0 -= self.camera_speed,
             Event::WindowEvent {
                 event: WindowEvent::ReceivedCharacter('d'),
                 ..
-            } => self.camera.position.0 += CAMERA_SPEED,
+            } => self.camera.position.0 += self.camera_speed,
             Event::WindowEvent {
-                event: WindowEvent::ReceivedCharacter('q'),
+                event: WindowEvent::MouseWheel { delta, .. },
                 ..
-            } => self.camera.zoom_level /= ZOOM_FACTOR,
-            Event::WindowEvent {
-                event: WindowEvent::ReceivedCharacter('e'),
-                ..
-            } => self.camera.zoom_level *= ZOOM_FACTOR,
-            _ => {}
+            } => {
+                let viewport = viewport(display);
+                let notches = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    // roughly one notch per 40 pixels of touchpad scrolling
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 40.0,
+                };
+                // without a known cursor zoom into the center
+                let anchor = self.cursor.unwrap_or(Point {
+                    x: f64::from(viewport.width) / 2.0,
+                    y: f64::from(viewport.height) / 2.0,
+                });
+                self.camera
+                    .zoom_at(self.zoom_factor.powf(notches), anchor, viewport);
+            }
+            _ => return false,
         }
+        true
     }
 
-    pub fn render(&mut self, universe: &Universe, display: &Display, target: &mut Frame) {
-        // calculate range in which we have to Universe::get_cell
-        let x_range = self.camera.x_range();
-        let y_range = self.camera.y_range();
+    pub fn render(
+        &mut self,
+        universe: &Universe,
+        color: [f32; 4],
+        display: &Display,
+        target: &mut Frame,
+    ) {
+        let (width, height) = target.get_dimensions();
+        let viewport = Viewport { width, height };
+        let visible = self.camera.visible_world_rect(viewport);
 
-        let mut vertices: Vec<Vertex> = Vec::new();
+        let instances = instances(universe, visible, self.camera.cell_pixels(viewport));
+        let matrix = self.camera.matrix(viewport);
+        self.draw(
+            &instances,
+            color,
+            matrix,
+            &Default::default(),
+            display,
+            target,
+        );
+    }
 
-        for y in y_range {
-            for x in x_range.clone() {
-                let alive = match universe.get_cell((x, y)) {
-                    Cell::Dead => false,
-                    Cell::Alive => true,
-                };
+    // draws `region` of `universe` magnified into `panel`, a part of the window in pixels from
+    // the bottom left, a region which isn't square is centered
+    pub fn render_inset(
+        &mut self,
+        universe: &Universe,
+        region: Rect,
+        panel: glium::Rect,
+        color: [f32; 4],
+        display: &Display,
+        target: &mut Frame,
+    ) {
+        let background = self.palette.inset_background;
+        target.clear(Some(&panel), Some(background), false, None, None);
+        let (width, height) = (region.width() as f32, region.height() as f32);
+        let projection = Projection {
+            center: (
+                region.min.x as f32 - 0.5 + width / 2.0,
+                region.min.y as f32 - 0.5 + height / 2.0,
+            ),
+            scale: 2.0 / width.max(height),
+            viewport: Viewport {
+                width: panel.width,
+                height: panel.height,
+            },
+        };
+        let instances = instances(universe, region, projection.cell_pixels());
+        let params = DrawParameters {
+            viewport: Some(panel),
+            ..Default::default()
+        };
+        self.draw(
+            &instances,
+            color,
+            projection.matrix(),
+            &params,
+            display,
+            target,
+        );
+    }
 
-                if alive {
-                    let mut new = self.camera.project((x, y));
-                    vertices.append(&mut new);
-                }
-            }
+    fn draw(
+        &self,
+        instances: &[Instance],
+        color: [f32; 4],
+        matrix: [[f32; 4]; 4],
+        params: &DrawParameters,
+        display: &Display,
+        target: &mut Frame,
+    ) {
+        trace!("drawing {} instances", instances.len());
+        if instances.is_empty() {
+            return;
         }
-
-        println!("vertex array length: {}", vertices.len());
-
-        let vertex_buffer = glium::VertexBuffer::new(display, &vertices).unwrap();
-
-        let index_buffer = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        let instance_buffer = VertexBuffer::new(display, instances).unwrap();
 
         target
             .draw(
-                &vertex_buffer,
-                &index_buffer,
+                (&self.quad, instance_buffer.per_instance().unwrap()),
+                NoIndices(PrimitiveType::TriangleStrip),
                 &self.program,
-                &glium::uniforms::EmptyUniforms,
-                &Default::default(),
+                &uniform! {
+                    matrix: matrix,
+                    cell_color: color,
+                },
+                params,
             )
             .unwrap();
     }
+
+    // the selected rectangle as a solid background
+    pub fn render_selection(&mut self, display: &Display, target: &mut Frame) {
+        if let Some(rect) = self.selection {
+            self.render_rects(&[rect], self.palette.selection, display, target);
+        }
+    }
+
+    // a line between every two rows and columns, if the grid is on and the cells are large
+    // enough to tell the lines apart
+    pub fn render_grid(&mut self, display: &Display, target: &mut Frame) {
+        let (width, height) = target.get_dimensions();
+        let viewport = Viewport { width, height };
+        let cell_pixels = self.camera.cell_pixels(viewport);
+        if !self.grid || cell_pixels < GRID_MIN_PIXELS {
+            return;
+        }
+        let visible = self.camera.visible_world_rect(viewport);
+        // one pixel wide
+        let thickness = 1.0 / cell_pixels;
+        let (center_x, center_y) = (
+            (visible.min.x + visible.max.x) as f32 / 2.0,
+            (visible.min.y + visible.max.y) as f32 / 2.0,
+        );
+        let (span_x, span_y) = (visible.width() as f32, visible.height() as f32);
+        let columns = (visible.min.x..=visible.max.x)
+            .map(|x| Instance::new([x as f32 - 0.5, center_y], [thickness, span_y], 1.0));
+        let rows = (visible.min.y..=visible.max.y)
+            .map(|y| Instance::new([center_x, y as f32 - 0.5], [span_x, thickness], 1.0));
+        let instances: Vec<Instance> = columns.chain(rows).collect();
+        let matrix = self.camera.matrix(viewport);
+        self.draw(
+            &instances,
+            self.palette.grid,
+            matrix,
+            &Default::default(),
+            display,
+            target,
+        );
+    }
+
+    // solid backgrounds behind the cells, e.g. for edits which haven't been applied yet
+    pub fn render_rects(
+        &mut self,
+        rects: &[Rect],
+        color: [f32; 4],
+        display: &Display,
+        target: &mut Frame,
+    ) {
+        let (width, height) = target.get_dimensions();
+        let matrix = self.camera.matrix(Viewport { width, height });
+        let instances: Vec<Instance> = rects
+            .iter()
+            .map(|&rect| Instance::rect(rect, 1.0))
+            .collect();
+        self.draw(
+            &instances,
+            color,
+            matrix,
+            &Default::default(),
+            display,
+            target,
+        );
+    }
+}
+
+// the cells of `universe` within `visible`, or blocks shaded by their density when cells are
+// smaller than a pixel
+fn instances(universe: &Universe, visible: Rect, cell_pixels: f32) -> Vec<Instance> {
+    if cell_pixels < 1.0 {
+        let level = (1.0 / cell_pixels).log2().ceil() as u8;
+        let side = 1i64 << level;
+        universe
+            .node_population_in(visible, level)
+            .map(|(min, population)| {
+                let density = population as f32 / (side * side) as f32;
+                Instance::block(min, side, density.sqrt())
+            })
+            .collect()
+    } else {
+        // dying cells of Generations rules fade out
+        let states = f32::from(universe.rule().states());
+        universe
+            .cells_in(visible)
+            .map(|(pos, cell)| {
+                let shade = match cell {
+                    Cell::Dying(state) => (states - f32::from(state)) / (states - 1.0),
+                    _ => 1.0,
+                };
+                Instance::cell(pos, shade)
+            })
+            .collect()
+    }
 }
 
+pub fn viewport(display: &Display) -> Viewport {
+    let size = display.gl_window().window().inner_size();
+    Viewport {
+        width: size.width,
+        height: size.height,
+    }
+}
+
+// corner of the unit square centered on the origin
 #[derive(Copy, Clone)]
 pub struct Vertex {
-    position: [f32; 2],
+    corner: [f32; 2],
 }
 
 impl Vertex {
     pub fn new(x: f32, y: f32) -> Self {
-        Vertex { position: [x, y] }
+        Vertex { corner: [x, y] }
+    }
+}
+
+glium::implement_vertex!(Vertex, corner);
+
+// a square in world coordinates, cells are centered on their integer position
+#[derive(Copy, Clone)]
+pub struct Instance {
+    center: [f32; 2],
+    size: [f32; 2],
+    // brightness of the cell color
+    shade: f32,
+}
+
+impl Instance {
+    fn new(center: [f32; 2], size: [f32; 2], shade: f32) -> Self {
+        Instance {
+            center,
+            size,
+            shade,
+        }
+    }
+
+    // a single cell, with padding to its neighbors
+    pub fn cell(pos: Position, shade: f32) -> Self {
+        let size = CELL_SIZE / (CELL_SIZE + CELL_PADDING);
+        Instance {
+            center: [pos.x as f32, pos.y as f32],
+            size: [size, size],
+            shade,
+        }
+    }
+
+    // a square of `side` cells starting at `min`, without padding between the cells
+    pub fn block(min: Position, side: i64, shade: f32) -> Self {
+        Self::rect(Rect::new(min, (min.x + side - 1, min.y + side - 1)), shade)
+    }
+
+    // covers all cells of `rect`, without padding between the cells
+    pub fn rect(rect: Rect, shade: f32) -> Self {
+        let (width, height) = (rect.width() as f32, rect.height() as f32);
+        Instance {
+            center: [
+                rect.min.x as f32 - 0.5 + width / 2.0,
+                rect.min.y as f32 - 0.5 + height / 2.0,
+            ],
+            size: [width, height],
+            shade,
+        }
     }
 }
 
-glium::implement_vertex!(Vertex, position);
+glium::implement_vertex!(Instance, center, size, shade);
@@ -0,0 +1,89 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::time::Instant;
+
+use glium::{
+    glutin::event::{ElementState, Event, WindowEvent},
+    Display, Frame,
+};
+use imgui::{Context, Ui};
+use imgui_winit_support::{HiDpiMode, WinitPlatform};
+
+// immediate mode windows drawn over the cells
+pub struct Gui {
+    context: Context,
+    platform: WinitPlatform,
+    renderer: imgui_glium_renderer::Renderer,
+    last_frame: Instant,
+}
+
+impl Gui {
+    pub fn new(display: &Display) -> Self {
+        let mut context = Context::create();
+        // the layout of the windows is not worth a file of its own
+        context.set_ini_filename(None);
+        let mut platform = WinitPlatform::init(&mut context);
+        platform.attach_window(
+            context.io_mut(),
+            display.gl_window().window(),
+            HiDpiMode::Default,
+        );
+        let renderer = imgui_glium_renderer::Renderer::init(&mut context, display)
+            .expect("Failed to initialize the gui renderer");
+        Self {
+            context,
+            platform,
+            renderer,
+            last_frame: Instant::now(),
+        }
+    }
+
+    // hands every event to the gui, returns whether the gui uses it, in which case the
+    // shortcuts and the camera should leave it alone
+    // released buttons always go through, so that drags which started elsewhere end
+    pub fn handle_event(&mut self, display: &Display, event: &Event<()>) -> bool {
+        let io = self.context.io_mut();
+        self.platform
+            .handle_event(io, display.gl_window().window(), event);
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::ReceivedCharacter(_) | WindowEvent::KeyboardInput { .. } => {
+                    io.want_capture_keyboard
+                }
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    ..
+                }
+                | WindowEvent::MouseWheel { .. } => io.want_capture_mouse,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    // lays out the windows with `build` and draws them into `frame`
+    pub fn render(&mut self, display: &Display, frame: &mut Frame, build: impl FnOnce(&Ui)) {
+        let gl_window = display.gl_window();
+        let window = gl_window.window();
+        let now = Instant::now();
+        self.context
+            .io_mut()
+            .update_delta_time(now - self.last_frame);
+        self.last_frame = now;
+        if let Err(err) = self.platform.prepare_frame(self.context.io_mut(), window) {
+            warn!("failed to prepare the gui: {}", err);
+            return;
+        }
+
+        let ui = self.context.frame();
+        build(&ui);
+        self.platform.prepare_render(&ui, window);
+        if let Err(err) = self.renderer.render(frame, ui.render()) {
+            error!("failed to draw the gui: {}", err);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use glium::glutin::event::{ElementState, Event, MouseButton, WindowEvent};
+
+use la::{Mirror, Rect, Universe};
+
+use super::projection::{Point, Viewport};
+
+// side of the panel in physical pixels
+pub const PANEL_SIZE: u32 = 320;
+// distance of the panel to the window border when it is opened
+const PANEL_MARGIN: f64 = 16.0;
+
+// a magnified live view of a pinned region in a panel on top of the window, so a reaction site
+// can be watched while the camera is somewhere else, the panel is moved by dragging it with the
+// left mouse button
+pub struct Inspector {
+    mirror: Mirror,
+    // top left corner of the panel
+    corner: Point,
+    cursor: Option<Point>,
+    // cursor position relative to the corner while the panel is dragged
+    grab: Option<Point>,
+}
+
+impl Inspector {
+    pub fn new(rect: Rect, source: &Universe) -> Self {
+        let mut mirror = Mirror::new(rect);
+        mirror.update(source);
+        Self {
+            mirror,
+            corner: Point {
+                x: PANEL_MARGIN,
+                y: PANEL_MARGIN,
+            },
+            cursor: None,
+            grab: None,
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.mirror.rect()
+    }
+
+    pub fn update(&mut self, source: &Universe) {
+        self.mirror.update(source);
+    }
+
+    pub fn universe(&self) -> &Universe {
+        self.mirror.universe()
+    }
+
+    // the pinned region in the coordinates of the mirrored universe
+    pub fn region(&self) -> Rect {
+        let rect = self.rect();
+        Rect::new((0, 0), (rect.max.x - rect.min.x, rect.max.y - rect.min.y))
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.grab.is_some()
+    }
+
+    // the panel in pixels from the bottom left of the window, as glium expects it, kept inside
+    // the window
+    pub fn panel(&self, window: Viewport) -> glium::Rect {
+        let size = PANEL_SIZE.min(window.width).min(window.height);
+        let left = (self.corner.x.max(0.0) as u32).min(window.width - size);
+        let top = (self.corner.y.max(0.0) as u32).min(window.height - size);
+        glium::Rect {
+            left,
+            bottom: window.height - size - top,
+            width: size,
+            height: size,
+        }
+    }
+
+    // top left corner of the panel as it is drawn
+    fn top_left(&self, window: Viewport) -> Point {
+        let panel = self.panel(window);
+        Point {
+            x: f64::from(panel.left),
+            y: f64::from(window.height - panel.bottom - panel.height),
+        }
+    }
+
+    fn contains(&self, point: Point, window: Viewport) -> bool {
+        let corner = self.top_left(window);
+        let size = f64::from(self.panel(window).width);
+        (corner.x..corner.x + size).contains(&point.x)
+            && (corner.y..corner.y + size).contains(&point.y)
+    }
+
+    // returns whether the event was used to drag the panel, cursor movements are never
+    // consumed, so the cursor stays known to everything else
+    pub fn handle_event(&mut self, event: &Event<()>, window: Viewport) -> bool {
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                let cursor = Point {
+                    x: position.x,
+                    y: position.y,
+                };
+                if let Some(grab) = self.grab {
+                    // the corner stays where the panel is drawn, so it can be grabbed again
+                    let panel = self.panel(window);
+                    self.corner = Point {
+                        x: (cursor.x - grab.x).clamp(0.0, f64::from(window.width - panel.width)),
+                        y: (cursor.y - grab.y).clamp(0.0, f64::from(window.height - panel.height)),
+                    };
+                }
+                self.cursor = Some(cursor);
+                false
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Left,
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => match self.cursor {
+                    Some(cursor) if self.contains(cursor, window) => {
+                        self.corner = self.top_left(window);
+                        self.grab = Some(Point {
+                            x: cursor.x - self.corner.x,
+                            y: cursor.y - self.corner.y,
+                        });
+                        true
+                    }
+                    _ => false,
+                },
+                ElementState::Released => self.grab.take().is_some(),
+            },
+            _ => false,
+        }
+    }
+}
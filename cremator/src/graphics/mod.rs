@@ -1,4 +1,8 @@
 //pub mod camera;
 pub mod camera;
 pub mod cell_renderer;
+pub mod gui;
+pub mod inspector;
+pub mod projection;
 pub mod renderer;
+pub mod theme;
@@ -0,0 +1,162 @@
+// conversions between world coordinates (cells), normalized device coordinates and screen pixels
+// every frontend should go through these, so clicks and drawing agree with each other
+
+// position on the window in physical pixels, origin at the top left
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+// size of the window in physical pixels
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Projection {
+    // world coordinates shown at the center of the viewport
+    pub center: (f32, f32),
+    // normalized device units per cell
+    pub scale: f32,
+    pub viewport: Viewport,
+}
+
+impl Projection {
+    // y points up in normalized device coordinates and down on the screen
+    pub fn screen_to_ndc(&self, screen: Point) -> (f32, f32) {
+        (
+            (2.0 * screen.x / f64::from(self.viewport.width) - 1.0) as f32,
+            (1.0 - 2.0 * screen.y / f64::from(self.viewport.height)) as f32,
+        )
+    }
+
+    pub fn ndc_to_screen(&self, ndc: (f32, f32)) -> Point {
+        Point {
+            x: (f64::from(ndc.0) + 1.0) / 2.0 * f64::from(self.viewport.width),
+            y: (1.0 - f64::from(ndc.1)) / 2.0 * f64::from(self.viewport.height),
+        }
+    }
+
+    pub fn world_to_ndc(&self, world: (f32, f32)) -> (f32, f32) {
+        (
+            (world.0 - self.center.0) * self.scale,
+            (world.1 - self.center.1) * self.scale,
+        )
+    }
+
+    pub fn ndc_to_world(&self, ndc: (f32, f32)) -> (f32, f32) {
+        (
+            ndc.0 / self.scale + self.center.0,
+            ndc.1 / self.scale + self.center.1,
+        )
+    }
+
+    pub fn screen_to_world(&self, screen: Point) -> (f32, f32) {
+        self.ndc_to_world(self.screen_to_ndc(screen))
+    }
+
+    pub fn world_to_screen(&self, world: (f32, f32)) -> Point {
+        self.ndc_to_screen(self.world_to_ndc(world))
+    }
+
+    // `world_to_ndc` as a column major matrix for the shaders
+    pub fn matrix(&self) -> [[f32; 4]; 4] {
+        let origin = self.world_to_ndc((0.0, 0.0));
+        [
+            [self.scale, 0.0, 0.0, 0.0],
+            [0.0, self.scale, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [origin.0, origin.1, 0.0, 1.0],
+        ]
+    }
+
+    // horizontal size of a cell in physical pixels
+    pub fn cell_pixels(&self) -> f32 {
+        let (a, b) = (
+            self.world_to_screen((0.0, 0.0)),
+            self.world_to_screen((1.0, 0.0)),
+        );
+        (b.x - a.x) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECTION: Projection = Projection {
+        center: (10.0, -4.0),
+        scale: 0.05,
+        viewport: Viewport {
+            width: 800,
+            height: 600,
+        },
+    };
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn viewport_center_shows_the_center() {
+        let screen = Point { x: 400.0, y: 300.0 };
+        assert_close(PROJECTION.screen_to_world(screen), PROJECTION.center);
+    }
+
+    #[test]
+    fn screen_corners_are_ndc_corners() {
+        assert_close(
+            PROJECTION.screen_to_ndc(Point { x: 0.0, y: 0.0 }),
+            (-1.0, 1.0),
+        );
+        assert_close(
+            PROJECTION.screen_to_ndc(Point { x: 800.0, y: 600.0 }),
+            (1.0, -1.0),
+        );
+    }
+
+    #[test]
+    fn world_and_screen_round_trip() {
+        for world in [(0.0, 0.0), (10.0, -4.0), (-7.5, 13.25)] {
+            let screen = PROJECTION.world_to_screen(world);
+            assert_close(PROJECTION.screen_to_world(screen), world);
+        }
+        let screen = Point { x: 123.0, y: 456.0 };
+        let back = PROJECTION.world_to_screen(PROJECTION.screen_to_world(screen));
+        assert_close((back.x as f32, back.y as f32), (123.0, 456.0));
+    }
+
+    #[test]
+    fn matrix_matches_world_to_ndc() {
+        let matrix = PROJECTION.matrix();
+        for world in [(0.0, 0.0), (10.0, -4.0), (-7.5, 13.25)] {
+            let ndc = (
+                matrix[0][0] * world.0 + matrix[1][0] * world.1 + matrix[3][0],
+                matrix[0][1] * world.0 + matrix[1][1] * world.1 + matrix[3][1],
+            );
+            assert_close(ndc, PROJECTION.world_to_ndc(world));
+        }
+    }
+
+    #[test]
+    fn cell_pixels_scale_with_the_viewport() {
+        // 0.05 of half the width
+        assert_close((PROJECTION.cell_pixels(), 0.0), (20.0, 0.0));
+        let wide = Projection {
+            viewport: Viewport {
+                width: 1600,
+                height: 600,
+            },
+            ..PROJECTION
+        };
+        assert_close((wide.cell_pixels(), 0.0), (40.0, 0.0));
+    }
+}
@@ -4,38 +4,202 @@ pub use tracing::{
     warn_span,
 };
 
-use glium::{glutin::event::Event, Display, Surface};
+use std::{collections::VecDeque, sync::Arc};
+
+use glium::{glutin::event::Event, Display, Frame, Surface};
 
 //use super::camera::{Camera, CAMERA_SPEED, ZOOM_FACTOR};
-use la::Universe;
+use la::{Position, Rect, Universe};
+
+use super::{
+    camera::Camera, cell_renderer, inspector::Inspector, projection::Viewport, theme::Theme,
+};
 
-use super::cell_renderer;
+use cell_renderer::{viewport, CellRenderer};
 
-use cell_renderer::CellRenderer;
+// published states drawn by the trail
+const TRAIL_LENGTH: usize = 8;
 
 pub struct Renderer {
     cell_renderer: CellRenderer,
+    // drawn below the live cells, e.g. the envelope of the pattern
+    ghost: Option<Universe>,
+    // states published before the current one, the latest first, drawn below the live cells
+    trail: Option<VecDeque<Arc<Universe>>>,
+    // drawn on top of everything else
+    inspector: Option<Inspector>,
+    // areas of queued edits
+    pending: Vec<Rect>,
 }
 
 impl Renderer {
     pub fn init(display: &Display) -> Self {
         let cell_renderer = CellRenderer::new(display);
 
-        Self { cell_renderer }
+        Self {
+            cell_renderer,
+            ghost: None,
+            trail: None,
+            inspector: None,
+            pending: Vec::new(),
+        }
+    }
+
+    // returns whether the view changed
+    pub fn handle_event(&mut self, event: Event<()>, display: &Display) -> bool {
+        if let Some(inspector) = &mut self.inspector {
+            if inspector.handle_event(&event, viewport(display)) {
+                return true;
+            }
+        }
+        let dragging = matches!(&self.inspector, Some(inspector) if inspector.is_dragging());
+        self.cell_renderer.handle_event(event, display) || dragging
     }
 
-    pub fn handle_event(&mut self, event: Event<()>, display: &Display) {
-        self.cell_renderer.handle_event(event, display)
+    // the cell which was clicked since the last call
+    pub fn take_clicked_cell(&mut self) -> Option<Position> {
+        self.cell_renderer.take_clicked_cell()
+    }
+
+    pub fn cursor_cell(&self, display: &Display) -> Option<Position> {
+        self.cell_renderer.cursor_cell(display)
+    }
+
+    pub fn selection(&self) -> Option<Rect> {
+        self.cell_renderer.selection()
     }
 
     pub fn update(&mut self) {}
 
-    pub fn render(&mut self, universe: &Universe, display: &Display) {
+    pub fn camera(&self) -> &Camera {
+        self.cell_renderer.camera()
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        self.cell_renderer.camera_mut()
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.cell_renderer.set_palette(theme.palette());
+    }
+
+    pub fn set_grid(&mut self, grid: bool) {
+        self.cell_renderer.set_grid(grid);
+    }
+
+    // cells moved per key press and zoom per notch of the mouse wheel
+    pub fn set_input(&mut self, camera_speed: f32, zoom_factor: f32) {
+        self.cell_renderer.set_input(camera_speed, zoom_factor);
+    }
+
+    pub fn set_ghost(&mut self, ghost: Option<Universe>) {
+        self.ghost = ghost;
+    }
+
+    pub fn has_ghost(&self) -> bool {
+        self.ghost.is_some()
+    }
+
+    // returns whether the trail is shown now, it starts empty
+    pub fn toggle_trail(&mut self) -> bool {
+        self.trail = match self.trail {
+            Some(_) => None,
+            None => Some(VecDeque::with_capacity(TRAIL_LENGTH)),
+        };
+        self.trail.is_some()
+    }
+
+    // remembers a state which is about to be replaced by a newer one
+    pub fn push_trail(&mut self, universe: &Arc<Universe>) {
+        if let Some(trail) = &mut self.trail {
+            trail.truncate(TRAIL_LENGTH - 1);
+            trail.push_front(Arc::clone(universe));
+        }
+    }
+
+    // opens the panel of a pinned region, or closes it
+    pub fn set_inspector(&mut self, inspector: Option<Inspector>) {
+        self.inspector = inspector;
+    }
+
+    pub fn inspector(&self) -> Option<&Inspector> {
+        self.inspector.as_ref()
+    }
+
+    // the pinned region follows the universe
+    pub fn update_inspector(&mut self, universe: &Universe) {
+        if let Some(inspector) = &mut self.inspector {
+            inspector.update(universe);
+        }
+    }
+
+    // returns whether the highlighted areas changed
+    pub fn set_pending(&mut self, pending: Vec<Rect>) -> bool {
+        if pending == self.pending {
+            return false;
+        }
+        self.pending = pending;
+        true
+    }
+
+    // `overlay` draws on top of everything, e.g. the windows of the gui
+    pub fn render(
+        &mut self,
+        universe: &Universe,
+        display: &Display,
+        overlay: impl FnOnce(&mut Frame),
+    ) {
+        let palette = *self.cell_renderer.palette();
         let mut frame = display.draw();
-        frame.clear_color(0.0, 0.0, 0.0, 1.0);
+        let (red, green, blue, alpha) = palette.background;
+        frame.clear_color(red, green, blue, alpha);
 
-        self.cell_renderer.render(universe, display, &mut frame);
+        self.cell_renderer.render_grid(display, &mut frame);
+        self.cell_renderer.render_selection(display, &mut frame);
+        self.cell_renderer
+            .render_rects(&self.pending, palette.pending, display, &mut frame);
+
+        if let Some(ghost) = &self.ghost {
+            self.cell_renderer
+                .render(ghost, palette.ghost, display, &mut frame);
+        }
+        if let Some(trail) = &self.trail {
+            // the oldest first, so that newer states are drawn over it
+            for (age, state) in trail.iter().enumerate().rev() {
+                let strength = (TRAIL_LENGTH - age) as f32 / (TRAIL_LENGTH + 1) as f32;
+                let color = fade(palette.trail, palette.background, strength);
+                self.cell_renderer.render(state, color, display, &mut frame);
+            }
+        }
+        self.cell_renderer
+            .render(universe, palette.live, display, &mut frame);
+
+        if let Some(inspector) = &self.inspector {
+            let (width, height) = frame.get_dimensions();
+            let panel = inspector.panel(Viewport { width, height });
+            self.cell_renderer.render_inset(
+                inspector.universe(),
+                inspector.region(),
+                panel,
+                palette.live,
+                display,
+                &mut frame,
+            );
+        }
+        overlay(&mut frame);
 
         frame.finish().unwrap();
     }
 }
+
+// `color` mixed into the background, fully at a strength of 1
+fn fade(color: [f32; 4], background: (f32, f32, f32, f32), strength: f32) -> [f32; 4] {
+    let (red, green, blue, _) = background;
+    let mix = |from: f32, to: f32| from + (to - from) * strength;
+    [
+        mix(red, color[0]),
+        mix(green, color[1]),
+        mix(blue, color[2]),
+        color[3],
+    ]
+}
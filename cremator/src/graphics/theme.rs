@@ -0,0 +1,79 @@
+use std::{fmt, str::FromStr};
+
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+// the colors of everything the renderer draws
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Palette {
+    pub background: (f32, f32, f32, f32),
+    pub live: [f32; 4],
+    // the envelope below the live cells
+    pub ghost: [f32; 4],
+    // cells alive in the recent past, faded towards the background the older they are
+    pub trail: [f32; 4],
+    pub selection: [f32; 4],
+    // areas of edits which haven't been applied yet
+    pub pending: [f32; 4],
+    pub grid: [f32; 4],
+    pub inset_background: (f32, f32, f32, f32),
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Dark, Theme::Light];
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Dark => Palette {
+                background: (0.0, 0.0, 0.0, 1.0),
+                live: [1.0, 0.0, 0.0, 1.0],
+                ghost: [0.25, 0.25, 0.4, 1.0],
+                trail: [0.9, 0.45, 0.1, 1.0],
+                selection: [0.15, 0.15, 0.15, 1.0],
+                pending: [0.35, 0.3, 0.0, 1.0],
+                grid: [0.12, 0.12, 0.12, 1.0],
+                inset_background: (0.08, 0.08, 0.08, 1.0),
+            },
+            Theme::Light => Palette {
+                background: (0.95, 0.95, 0.92, 1.0),
+                live: [0.1, 0.1, 0.1, 1.0],
+                ghost: [0.7, 0.75, 0.9, 1.0],
+                trail: [0.3, 0.45, 0.7, 1.0],
+                selection: [0.82, 0.85, 0.9, 1.0],
+                pending: [0.95, 0.85, 0.5, 1.0],
+                grid: [0.85, 0.85, 0.82, 1.0],
+                inset_background: (0.88, 0.88, 0.85, 1.0),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+        })
+    }
+}
+
+impl FromStr for Theme {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Theme::ALL
+            .iter()
+            .copied()
+            .find(|theme| theme.to_string() == s)
+            .ok_or_else(|| eyre!("unknown theme \"{}\"", s))
+    }
+}
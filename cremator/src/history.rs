@@ -0,0 +1,125 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::fmt::Write;
+
+use la::{format, Snapshot, Universe};
+
+// named branches of a universe, each remembering the state it was left in
+pub struct History {
+    branches: Vec<Branch>,
+    current: usize,
+}
+
+struct Branch {
+    name: String,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // up to date for every branch except the current one
+    head: Snapshot,
+}
+
+impl History {
+    pub fn new(universe: &mut Universe) -> Self {
+        Self {
+            branches: vec![Branch {
+                name: "main".to_owned(),
+                parent: None,
+                children: Vec::new(),
+                head: universe.snapshot(),
+            }],
+            current: 0,
+        }
+    }
+
+    // forks the current state into a new child branch and switches to it
+    pub fn branch(&mut self, universe: &mut Universe) {
+        self.save_head(universe);
+        let index = self.branches.len();
+        self.branches.push(Branch {
+            name: format!("branch {}", index),
+            parent: Some(self.current),
+            children: Vec::new(),
+            head: universe.snapshot(),
+        });
+        self.branches[self.current].children.push(index);
+        self.current = index;
+        info!("created {}", self.branches[index].name);
+    }
+
+    pub fn goto_parent(&mut self, universe: &mut Universe) {
+        if let Some(parent) = self.branches[self.current].parent {
+            self.checkout(universe, parent);
+        }
+    }
+
+    // the most recently created child
+    pub fn goto_child(&mut self, universe: &mut Universe) {
+        if let Some(&child) = self.branches[self.current].children.last() {
+            self.checkout(universe, child);
+        }
+    }
+
+    // `offset` steps through the siblings, wrapping around
+    pub fn goto_sibling(&mut self, universe: &mut Universe, offset: isize) {
+        let siblings = match self.branches[self.current].parent {
+            Some(parent) => &self.branches[parent].children,
+            None => return,
+        };
+        let position = siblings.iter().position(|&b| b == self.current).unwrap() as isize;
+        let len = siblings.len() as isize;
+        let sibling = siblings[(position + offset).rem_euclid(len) as usize];
+        self.checkout(universe, sibling);
+    }
+
+    fn checkout(&mut self, universe: &mut Universe, index: usize) {
+        if index == self.current {
+            return;
+        }
+        self.save_head(universe);
+        universe.restore(&self.branches[index].head);
+        self.current = index;
+        info!("switched to {}", self.branches[index].name);
+    }
+
+    fn save_head(&mut self, universe: &mut Universe) {
+        let head = universe.snapshot();
+        let old = std::mem::replace(&mut self.branches[self.current].head, head);
+        universe.release(old);
+    }
+
+    // one line per branch, indented by depth, the current branch is marked with '*'
+    pub fn render(&self, universe: &Universe) -> String {
+        let mut out = String::new();
+        let mut stack = vec![(0, 0)];
+        while let Some((index, depth)) = stack.pop() {
+            let branch = &self.branches[index];
+            let (marker, generation) = if index == self.current {
+                ('*', universe.generation())
+            } else {
+                (' ', branch.head.generation())
+            };
+            writeln!(
+                out,
+                "{} {:indent$}{} (generation {})",
+                marker,
+                "",
+                branch.name,
+                format::engineering(generation),
+                indent = 2 * depth
+            )
+            .unwrap();
+            stack.extend(
+                branch
+                    .children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, depth + 1)),
+            );
+        }
+        out
+    }
+}
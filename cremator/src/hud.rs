@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use la::{format, Activity, Stats, Universe};
+
+use crate::simulation::Simulation;
+
+// how often the frame rate is measured and the status refreshed
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+// status line with the state of the universe, the simulation controls and the frame rate,
+// shown in the window title
+pub struct Hud {
+    frames: u32,
+    since: Instant,
+    fps: f64,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self {
+            frames: 0,
+            since: Instant::now(),
+            fps: 0.0,
+        }
+    }
+
+    pub fn frame(&mut self) {
+        self.frames += 1;
+    }
+
+    // remeasures the frame rate once per interval, returns whether it did
+    pub fn refresh(&mut self) -> bool {
+        let elapsed = self.since.elapsed();
+        if elapsed < REFRESH_INTERVAL {
+            return false;
+        }
+        self.fps = f64::from(self.frames) / elapsed.as_secs_f64();
+        self.frames = 0;
+        self.since = Instant::now();
+        true
+    }
+
+    pub fn text(
+        &self,
+        universe: &Universe,
+        simulation: &Simulation,
+        stats: &Stats,
+        activity: &Activity,
+    ) -> String {
+        let pace = if simulation.is_running() {
+            format!("{:.1} steps/s", simulation.rate())
+        } else {
+            "paused".to_owned()
+        };
+        format!(
+            "generation {} | population {} (+{} -{}) | step {}{} | {} | {} nodes, {:.1}% cache hits | {:.0} fps",
+            format::engineering(universe.generation()),
+            format::engineering(universe.population()),
+            format::engineering(activity.births),
+            format::engineering(activity.deaths),
+            format::engineering(simulation.step()),
+            if simulation.is_auto() { " (auto)" } else { "" },
+            pace,
+            format::si(stats.nodes as u128),
+            stats.cache_hit_rate() * 100.0,
+            self.fps
+        )
+    }
+}
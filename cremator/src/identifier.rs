@@ -0,0 +1,144 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use imgui::{im_str, Ui, Window};
+
+use la::{
+    census::{self, Kind, Object},
+    Position, Rule, Universe,
+};
+
+use crate::launcher;
+
+// periods tried before an object counts as unknown
+const MAX_PERIOD: u64 = 60;
+
+// the object a cell belongs to, with what is known about it
+pub struct Identified {
+    object: Object,
+    // names are only known in conway's life
+    name: Option<&'static str>,
+    rule: Rule,
+}
+
+impl Identified {
+    // the periodic object at or next to `pos`, apgcodes need a rule with two states
+    pub fn at(universe: &Universe, pos: Position) -> Option<Self> {
+        let rule = universe.rule();
+        if rule.states() != 2 {
+            info!("objects can only be identified in rules with two states");
+            return None;
+        }
+        let object = census::object_at(universe, pos, MAX_PERIOD);
+        if object.is_none() {
+            info!("no object with a period up to {} at {:?}", MAX_PERIOD, pos);
+        }
+        let object = object?;
+        let name = Some(&object.apgcode)
+            .filter(|_| rule == Rule::CONWAY)
+            .and_then(|apgcode| census::common_name(apgcode));
+        Some(Self { object, name, rule })
+    }
+
+    pub fn title(&self) -> &str {
+        self.name.unwrap_or(&self.object.apgcode)
+    }
+
+    // e.g. c/4 diagonal for the glider, none for objects which don't move
+    pub fn speed(&self) -> Option<String> {
+        if self.object.kind != Kind::Ship {
+            return None;
+        }
+        let (dx, dy) = (
+            self.object.displacement.dx.unsigned_abs(),
+            self.object.displacement.dy.unsigned_abs(),
+        );
+        let period = self.object.period;
+        let direction = if dx == 0 || dy == 0 {
+            "orthogonal"
+        } else if dx == dy {
+            "diagonal"
+        } else {
+            return Some(format!(
+                "({}, {})c/{} oblique",
+                dx.max(dy),
+                dx.min(dy),
+                period
+            ));
+        };
+        let cells = dx.max(dy);
+        let divisor = gcd(cells, period);
+        let (cells, period) = (cells / divisor, period / divisor);
+        Some(match cells {
+            1 => format!("c/{} {}", period, direction),
+            _ => format!("{}c/{} {}", cells, period, direction),
+        })
+    }
+
+    // the article on the LifeWiki for known objects, the page on Catagolue otherwise
+    pub fn link(&self) -> String {
+        match self.name {
+            Some(name) => {
+                let mut title = name.replace(' ', "_");
+                title[..1].make_ascii_uppercase();
+                format!("https://conwaylife.com/wiki/{}", title)
+            }
+            None => format!(
+                "https://catagolue.hatsya.com/object/{}/{}",
+                self.object.apgcode,
+                self.rule.to_string().to_lowercase().replace('/', "")
+            ),
+        }
+    }
+
+    // lays out the window, returns whether it stays open
+    pub fn build(&self, ui: &Ui) -> bool {
+        let (mut open, mut dismissed) = (true, false);
+        Window::new(im_str!("What is this?"))
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.text(self.title());
+                if self.name.is_some() {
+                    ui.text_disabled(&self.object.apgcode);
+                }
+                let kind = match self.object.kind {
+                    Kind::StillLife => "still life",
+                    Kind::Oscillator => "oscillator",
+                    Kind::Ship => "spaceship",
+                    _ => "object",
+                };
+                ui.text(format!(
+                    "{} of {} cells, period {}",
+                    kind,
+                    self.object.cells.len(),
+                    self.object.period
+                ));
+                if let Some(speed) = self.speed() {
+                    ui.text(format!("speed {}", speed));
+                }
+                let link = self.link();
+                ui.text_disabled(&link);
+                if ui.button(im_str!("Open link"), [0.0, 0.0]) {
+                    if let Err(err) = launcher::open(&link) {
+                        error!("failed to open {}: {}", link, err);
+                    }
+                }
+                ui.same_line(0.0);
+                if ui.button(im_str!("Close"), [0.0, 0.0]) {
+                    dismissed = true;
+                }
+            });
+        open && !dismissed
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
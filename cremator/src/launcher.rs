@@ -0,0 +1,15 @@
+use std::{ffi::OsStr, io, process::Command};
+
+// opens a file or a url with the viewer the system picks for it, which is left running
+pub fn open(target: impl AsRef<OsStr>) -> io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(target).spawn().map(drop)
+}
@@ -0,0 +1,191 @@
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TryRecvError},
+    thread::{self, JoinHandle},
+};
+
+use la::{
+    io::{rle::Parser, ParseError},
+    Cell, MaskOp, Pattern, Position, Universe,
+};
+
+use crate::simulation::SimulationThread;
+
+// bytes read from the file at once
+const CHUNK_LEN: usize = 1 << 16;
+// messages which may wait for the main thread, bounds the memory used for parsed cells
+const QUEUE_LEN: usize = 16;
+
+// reads a pattern file on a background thread and hands the cells over in chunks as soon as
+// they are parsed, so the window stays responsive while giant files load
+pub struct Loader {
+    path: PathBuf,
+    len: u64,
+    read: u64,
+    receiver: Receiver<Message>,
+    handle: Option<JoinHandle<()>>,
+}
+
+enum Message {
+    // bytes read so far
+    Progress(u64),
+    // with their states, at their positions in the universe
+    Cells(Vec<(Position, Cell)>),
+    // macrocell trees can't be split, the whole universe is handed over at once
+    Universe(Box<Universe>),
+    // the name, the comments and the rule, without cells
+    Done(Pattern),
+    Failed(Report),
+}
+
+impl Loader {
+    pub fn spawn(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).wrap_err_with(|| format!("failed to open {}", path.display()))?;
+        let len = file.metadata()?.len();
+        let macrocell = path.extension().and_then(OsStr::to_str) == Some("mc");
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_LEN);
+        let handle = thread::Builder::new()
+            .name("loader".to_owned())
+            .spawn(move || {
+                let result = if macrocell {
+                    load_macrocell(file, &sender)
+                } else {
+                    load_rle(file, &sender)
+                };
+                // the receiver is gone if loading was abandoned
+                let _ = sender.send(match result {
+                    Ok(header) => Message::Done(header),
+                    Err(err) => Message::Failed(err),
+                });
+            })?;
+        info!("loading {}", path.display());
+        Ok(Self {
+            path: path.to_owned(),
+            len,
+            read: 0,
+            receiver,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // share of the file read so far, from 0 to 1
+    pub fn progress(&self) -> f64 {
+        if self.len == 0 {
+            return 1.0;
+        }
+        self.read as f64 / self.len as f64
+    }
+
+    // moves the parsed cells into the universe, returns the header once the whole file is loaded
+    pub fn poll(&mut self, simulation: &SimulationThread) -> Option<Result<Pattern>> {
+        loop {
+            let message = match self.receiver.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    return Some(Err(eyre!("loader thread stopped unexpectedly")))
+                }
+            };
+            match message {
+                Message::Progress(read) => self.read = read,
+                Message::Cells(cells) => simulation.edit(|universe| universe.set_cells(cells)),
+                Message::Universe(loaded) => {
+                    simulation.edit(|universe| universe.apply_mask(&loaded, MaskOp::FillInside))
+                }
+                Message::Done(header) => {
+                    self.join();
+                    return Some(Ok(header));
+                }
+                Message::Failed(err) => {
+                    self.join();
+                    return Some(Err(
+                        err.wrap_err(format!("failed to load {}", self.path.display()))
+                    ));
+                }
+            }
+        }
+    }
+
+    fn join(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("loader thread panicked");
+        }
+    }
+}
+
+fn load_rle(mut file: File, sender: &SyncSender<Message>) -> Result<Pattern> {
+    let mut parser = Parser::new();
+    let mut chunk = vec![0; CHUNK_LEN];
+    let mut cells = Vec::new();
+    let mut read = 0;
+    while !parser.is_done() {
+        let len = file.read(&mut chunk)?;
+        if len == 0 {
+            break;
+        }
+        read += len as u64;
+        parser.feed_batched(&chunk[..len], &mut cells, |cells| send_cells(sender, cells))?;
+        send(sender, Message::Progress(read))?;
+    }
+    send_cells(sender, &mut cells)?;
+    Ok(parser.finish()?.into())
+}
+
+fn load_macrocell(mut file: File, sender: &SyncSender<Message>) -> Result<Pattern> {
+    let mut input = Vec::new();
+    let mut chunk = vec![0; CHUNK_LEN];
+    loop {
+        let len = file.read(&mut chunk)?;
+        if len == 0 {
+            break;
+        }
+        input.extend_from_slice(&chunk[..len]);
+        send(sender, Message::Progress(input.len() as u64))?;
+    }
+    let input = String::from_utf8(input).wrap_err("macrocell file is not valid utf-8")?;
+    let universe = match Universe::from_macrocell(&input) {
+        Err(err @ ParseError::HashMismatch { .. }) => {
+            warn!("{}, loading it anyway", err);
+            Universe::from_macrocell_unverified(&input)?
+        }
+        result => result?,
+    };
+    let header = Pattern {
+        rule: Some(universe.rule()),
+        ..Pattern::default()
+    };
+    send(sender, Message::Universe(Box::new(universe)))?;
+    Ok(header)
+}
+
+fn send_cells(sender: &SyncSender<Message>, cells: &mut Vec<(Position, Cell)>) -> Result<()> {
+    if cells.is_empty() {
+        return Ok(());
+    }
+    send(sender, Message::Cells(std::mem::take(cells)))
+}
+
+fn send(sender: &SyncSender<Message>, message: Message) -> Result<()> {
+    sender
+        .send(message)
+        .map_err(|_| eyre!("loading was abandoned"))
+}
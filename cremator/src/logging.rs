@@ -9,5 +9,6 @@ pub fn setup_subscriber() {
         .with(filter_layer)
         .with(fmt_layer)
         .with(ErrorLayer::default())
+        .with(crate::crash::ActionLog)
         .init();
 }
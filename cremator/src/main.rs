@@ -13,9 +13,24 @@ pub use tracing::{
 
 extern crate lifeash as la;
 
+use std::path::Path;
+
+mod bookmark;
+mod catalog;
+mod clipboard;
+mod crash;
 mod cremator;
 mod graphics;
+mod history;
+mod hud;
+mod identifier;
+mod launcher;
+mod loader;
 mod logging;
+mod scheduler;
+mod screenshot;
+mod settings;
+mod simulation;
 
 use cremator::Cremator;
 
@@ -76,11 +91,18 @@ bo5b$3bo3b$2o2b3o!
 
 fn main() -> Result<()> {
     logging::setup_subscriber();
+    if let Some(dir) = crash::data_dir() {
+        crash::install(dir);
+    }
 
     info!("starting simulator");
     let mut cremator = Cremator::new();
 
-    cremator.read_rls(HALFMAX_PATTER);
+    // an rle or macrocell file given on the command line, the halfmax breeder otherwise
+    match std::env::args_os().nth(1) {
+        Some(path) => cremator.load_file(Path::new(&path))?,
+        None => cremator.read_rls(HALFMAX_PATTER)?,
+    }
 
     info!("start simulation loop");
     cremator.run();
@@ -0,0 +1,129 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::time::Duration;
+
+use la::format;
+
+pub const BUDGET_STEP: Duration = Duration::from_millis(1);
+// how many frames are summarized in one log line
+const REPORT_INTERVAL: u32 = 120;
+
+// maximal time per frame spent on each phase
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameBudget {
+    pub simulation: Duration,
+    pub render: Duration,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct PhaseStats {
+    total: Duration,
+    max: Duration,
+    over_budget: u32,
+}
+
+pub struct Scheduler {
+    budget: FrameBudget,
+    frames: u32,
+    generations: u128,
+    simulation: PhaseStats,
+    render: PhaseStats,
+}
+
+impl Default for FrameBudget {
+    // leaves room for a 60Hz frame
+    fn default() -> Self {
+        Self {
+            simulation: Duration::from_millis(8),
+            render: Duration::from_millis(6),
+        }
+    }
+}
+
+impl PhaseStats {
+    fn record(&mut self, elapsed: Duration, budget: Duration) {
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+        if elapsed > budget {
+            self.over_budget += 1;
+        }
+    }
+}
+
+impl Scheduler {
+    pub fn new(budget: FrameBudget) -> Self {
+        Self {
+            budget,
+            frames: 0,
+            generations: 0,
+            simulation: PhaseStats::default(),
+            render: PhaseStats::default(),
+        }
+    }
+
+    pub fn set_budget(&mut self, budget: FrameBudget) {
+        info!("frame budget: {:?}", budget);
+        self.budget = budget;
+    }
+
+    pub fn grow_simulation_budget(&mut self) {
+        let mut budget = self.budget;
+        budget.simulation += BUDGET_STEP;
+        self.set_budget(budget);
+    }
+
+    pub fn shrink_simulation_budget(&mut self) {
+        let mut budget = self.budget;
+        budget.simulation = budget.simulation.saturating_sub(BUDGET_STEP);
+        self.set_budget(budget);
+    }
+
+    pub fn budget(&self) -> FrameBudget {
+        self.budget
+    }
+
+    pub fn record_simulation(&mut self, elapsed: Duration, generations: u128) {
+        self.generations += generations;
+        self.simulation.record(elapsed, self.budget.simulation);
+    }
+
+    pub fn record_render(&mut self, elapsed: Duration) {
+        self.render.record(elapsed, self.budget.render);
+    }
+
+    pub fn end_frame(&mut self) {
+        self.frames += 1;
+        if self.frames < REPORT_INTERVAL {
+            return;
+        }
+
+        let frames = self.frames;
+        let report = |name: &str, stats: PhaseStats, budget: Duration| {
+            debug!(
+                "{}: avg {:?}, max {:?}, budget {:?}, over budget in {}/{} frames",
+                name,
+                stats.total / frames,
+                stats.max,
+                budget,
+                stats.over_budget,
+                frames
+            );
+        };
+        report("simulation", self.simulation, self.budget.simulation);
+        report("render", self.render, self.budget.render);
+        debug!(
+            "{} generations in {} frames",
+            format::thousands(self.generations),
+            frames
+        );
+
+        self.frames = 0;
+        self.generations = 0;
+        self.simulation = PhaseStats::default();
+        self.render = PhaseStats::default();
+    }
+}
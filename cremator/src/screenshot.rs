@@ -0,0 +1,124 @@
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use glium::{texture::RawImage2d, Display};
+
+use la::Rule;
+
+// rgba pixels, rows from top to bottom
+pub struct Screenshot {
+    pub width: usize,
+    pub height: usize,
+    pub bytes: Vec<u8>,
+}
+
+// what was shown, stored in the file name and in the text chunks of the png
+pub struct Metadata {
+    pub name: Option<String>,
+    pub rule: Rule,
+    pub generation: u128,
+    pub position: (f32, f32),
+    pub zoom: f32,
+}
+
+// what is currently shown in the window
+pub fn capture(display: &Display) -> Result<Screenshot> {
+    let image: RawImage2d<u8> = display
+        .read_front_buffer()
+        .map_err(|err| eyre!("failed to read the front buffer: {:?}", err))?;
+
+    // opengl stores the rows bottom to top
+    let (width, height) = (image.width as usize, image.height as usize);
+    let row_len = width * 4;
+    let bytes = image
+        .data
+        .chunks_exact(row_len)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    Ok(Screenshot {
+        width,
+        height,
+        bytes,
+    })
+}
+
+impl Screenshot {
+    // writes a png named after the metadata into `dir` and returns its path
+    pub fn save(&self, dir: &Path, metadata: &Metadata) -> Result<PathBuf> {
+        let path = dir.join(metadata.file_name());
+        let file =
+            File::create(&path).wrap_err_with(|| format!("failed to create {}", path.display()))?;
+
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        for (keyword, text) in metadata.text_chunks() {
+            encoder.add_text_chunk(keyword.to_owned(), text)?;
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.bytes)?;
+        writer.finish()?;
+        Ok(path)
+    }
+}
+
+impl Metadata {
+    // e.g. glider_B3-S23_gen1024_x12_y-3_z1.50.png
+    pub fn file_name(&self) -> String {
+        let name = self.name.as_deref().map(slug);
+        format!(
+            "{}_{}_gen{}_x{:.0}_y{:.0}_z{:.2}.png",
+            name.as_deref()
+                .filter(|name| !name.is_empty())
+                .unwrap_or("untitled"),
+            self.rule.to_string().replace('/', "-"),
+            self.generation,
+            self.position.0,
+            self.position.1,
+            self.zoom
+        )
+    }
+
+    fn text_chunks(&self) -> Vec<(&'static str, String)> {
+        let mut chunks = vec![("Software", env!("CARGO_PKG_NAME").to_owned())];
+        if let Some(name) = &self.name {
+            chunks.push(("Title", name.clone()));
+        }
+        chunks.extend([
+            ("Rule", self.rule.to_string()),
+            ("Generation", self.generation.to_string()),
+            (
+                "Camera",
+                format!(
+                    "x={} y={} zoom={}",
+                    self.position.0, self.position.1, self.zoom
+                ),
+            ),
+        ]);
+        chunks
+    }
+}
+
+// keeps letters and digits, everything else becomes a single '-'
+fn slug(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_owned()
+}
@@ -0,0 +1,432 @@
+#[allow(unused)]
+use color_eyre::{Help, Report, Result};
+#[allow(unused)]
+use eyre::{eyre, WrapErr};
+
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::{
+    borrow::Cow,
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use imgui::{im_str, CollapsingHeader, ComboBox, ImStr, ImString, Slider, SliderFlags, Ui, Window};
+
+use la::Rule;
+
+use crate::{
+    graphics::{
+        camera::{CAMERA_SPEED, ZOOM_FACTOR},
+        theme::Theme,
+    },
+    simulation::{DEFAULT_RATE, MAX_RATE, MAX_STEP_LOG},
+};
+
+const MEGABYTE: usize = 1 << 20;
+// node table size in megabytes above which garbage is collected with `GcPolicy::Limit`
+const DEFAULT_MEMORY_LIMIT: u32 = 1024;
+const MIN_MEMORY_LIMIT: u32 = 16;
+const MAX_MEMORY_LIMIT: u32 = 1 << 16;
+const MIN_RATE: f64 = 0.1;
+const MIN_CAMERA_SPEED: f32 = 0.1;
+const MAX_CAMERA_SPEED: f32 = 100.0;
+const MIN_ZOOM_FACTOR: f32 = 1.01;
+const MAX_ZOOM_FACTOR: f32 = 2.0;
+// longer rulestrings don't fit into the text field
+const MAX_RULE_LEN: usize = 64;
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+
+// when garbage is collected from the node table of the universe
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum GcPolicy {
+    // only by hand, the table keeps every result
+    #[default]
+    Never,
+    // whenever the table outgrows the memory limit
+    Limit,
+    // after every step, which keeps the table small but drops the results of the last step
+    EveryStep,
+}
+
+// the options of the engine, the rendering and the input, changed in the settings window while
+// running and kept in a file of `key = value` lines between runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    // engine
+    pub rule: Rule,
+    pub step_log: u8,
+    // steps per second
+    pub rate: f64,
+    pub auto_step: bool,
+    // megabytes
+    pub memory_limit: u32,
+    pub gc_policy: GcPolicy,
+    // rendering
+    pub theme: Theme,
+    pub grid: bool,
+    // only read when the window is created
+    pub vsync: bool,
+    // input
+    // cells moved per key press
+    pub camera_speed: f32,
+    // zoom per notch of the mouse wheel
+    pub zoom_factor: f32,
+    pub invert_zoom: bool,
+}
+
+// the settings window, which edits the settings in place
+pub struct SettingsPanel {
+    open: bool,
+    // the rule as typed, applied when enter is pressed and it parses
+    rule_text: ImString,
+    // the rule `rule_text` was last filled with
+    shown_rule: Option<Rule>,
+    rule_error: Option<String>,
+}
+
+impl GcPolicy {
+    pub const ALL: [GcPolicy; 3] = [GcPolicy::Never, GcPolicy::Limit, GcPolicy::EveryStep];
+
+    // the threshold of `Universe::set_gc_threshold` for a limit in megabytes
+    pub fn threshold(self, memory_limit: u32) -> Option<usize> {
+        match self {
+            GcPolicy::Never => None,
+            GcPolicy::Limit => Some(memory_limit as usize * MEGABYTE),
+            GcPolicy::EveryStep => Some(0),
+        }
+    }
+}
+
+impl fmt::Display for GcPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GcPolicy::Never => "never",
+            GcPolicy::Limit => "limit",
+            GcPolicy::EveryStep => "every-step",
+        })
+    }
+}
+
+impl FromStr for GcPolicy {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        GcPolicy::ALL
+            .iter()
+            .copied()
+            .find(|policy| policy.to_string() == s)
+            .ok_or_else(|| eyre!("unknown garbage collection policy \"{}\"", s))
+    }
+}
+
+impl Default for Settings {
+    // what cremator did before it had settings
+    fn default() -> Self {
+        Self {
+            rule: Rule::default(),
+            step_log: 0,
+            rate: DEFAULT_RATE,
+            auto_step: false,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            gc_policy: GcPolicy::default(),
+            theme: Theme::default(),
+            grid: false,
+            vsync: true,
+            camera_speed: CAMERA_SPEED,
+            zoom_factor: ZOOM_FACTOR,
+            invert_zoom: false,
+        }
+    }
+}
+
+impl Settings {
+    // CREMATOR_CONFIG names the settings file instead of cremator/settings in the configuration
+    // directory of the user
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("CREMATOR_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+        Some(dir.join("cremator").join("settings"))
+    }
+
+    // the defaults if there is no file yet
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => text
+                .parse()
+                .wrap_err_with(|| format!("invalid settings in {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).wrap_err_with(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+        }
+        fs::write(path, self.to_string())
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    }
+
+    // the mouse wheel zooms in when scrolling up unless it is inverted
+    pub fn wheel_zoom(&self) -> f32 {
+        if self.invert_zoom {
+            self.zoom_factor.recip()
+        } else {
+            self.zoom_factor
+        }
+    }
+
+    pub fn gc_threshold(&self) -> Option<usize> {
+        self.gc_policy.threshold(self.memory_limit)
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# written by cremator whenever the settings window changes it"
+        )?;
+        writeln!(f, "rule = {}", self.rule)?;
+        writeln!(f, "step_log = {}", self.step_log)?;
+        writeln!(f, "rate = {}", self.rate)?;
+        writeln!(f, "auto_step = {}", self.auto_step)?;
+        writeln!(f, "memory_limit = {}", self.memory_limit)?;
+        writeln!(f, "gc_policy = {}", self.gc_policy)?;
+        writeln!(f, "theme = {}", self.theme)?;
+        writeln!(f, "grid = {}", self.grid)?;
+        writeln!(f, "vsync = {}", self.vsync)?;
+        writeln!(f, "camera_speed = {}", self.camera_speed)?;
+        writeln!(f, "zoom_factor = {}", self.zoom_factor)?;
+        writeln!(f, "invert_zoom = {}", self.invert_zoom)
+    }
+}
+
+// missing keys keep their defaults, unknown ones are skipped as a newer version may have
+// written them
+impl FromStr for Settings {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut settings = Self::default();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| eyre!("line {} is not of the form key = value", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || format!("invalid {} \"{}\" on line {}", key, value, number + 1);
+            match key {
+                "rule" => settings.rule = value.parse().wrap_err_with(invalid)?,
+                "step_log" => {
+                    settings.step_log = value.parse().wrap_err_with(invalid)?;
+                    settings.step_log = settings.step_log.min(MAX_STEP_LOG);
+                }
+                "rate" => {
+                    let rate: f64 = value.parse().wrap_err_with(invalid)?;
+                    settings.rate = rate.clamp(MIN_RATE, MAX_RATE);
+                }
+                "auto_step" => settings.auto_step = value.parse().wrap_err_with(invalid)?,
+                "memory_limit" => {
+                    let limit: u32 = value.parse().wrap_err_with(invalid)?;
+                    settings.memory_limit = limit.clamp(MIN_MEMORY_LIMIT, MAX_MEMORY_LIMIT);
+                }
+                "gc_policy" => settings.gc_policy = value.parse()?,
+                "theme" => settings.theme = value.parse()?,
+                "grid" => settings.grid = value.parse().wrap_err_with(invalid)?,
+                "vsync" => settings.vsync = value.parse().wrap_err_with(invalid)?,
+                "camera_speed" => {
+                    let speed: f32 = value.parse().wrap_err_with(invalid)?;
+                    settings.camera_speed = speed.clamp(MIN_CAMERA_SPEED, MAX_CAMERA_SPEED);
+                }
+                "zoom_factor" => {
+                    let factor: f32 = value.parse().wrap_err_with(invalid)?;
+                    settings.zoom_factor = factor.clamp(MIN_ZOOM_FACTOR, MAX_ZOOM_FACTOR);
+                }
+                "invert_zoom" => settings.invert_zoom = value.parse().wrap_err_with(invalid)?,
+                _ => warn!("skipping unknown setting \"{}\"", key),
+            }
+        }
+        Ok(settings)
+    }
+}
+
+impl SettingsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            rule_text: ImString::with_capacity(MAX_RULE_LEN),
+            shown_rule: None,
+            rule_error: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    // lays out the window, the settings are changed as soon as a control is
+    pub fn build(&mut self, ui: &Ui, settings: &mut Settings) {
+        // the rule may have been changed elsewhere, e.g. by loading a pattern
+        if self.shown_rule != Some(settings.rule) {
+            self.rule_text.clear();
+            self.rule_text.push_str(&settings.rule.to_string());
+            self.shown_rule = Some(settings.rule);
+            self.rule_error = None;
+        }
+
+        let Self {
+            open,
+            rule_text,
+            shown_rule,
+            rule_error,
+        } = self;
+        Window::new(im_str!("Settings"))
+            .opened(open)
+            .always_auto_resize(true)
+            .build(ui, || {
+                if CollapsingHeader::new(im_str!("Engine"))
+                    .default_open(true)
+                    .build(ui)
+                {
+                    if ui
+                        .input_text(im_str!("rule"), rule_text)
+                        .enter_returns_true(true)
+                        .build()
+                    {
+                        match rule_text.to_str().trim().parse() {
+                            Ok(rule) => {
+                                settings.rule = rule;
+                                *shown_rule = Some(rule);
+                                *rule_error = None;
+                            }
+                            Err(err) => *rule_error = Some(format!("{}", err)),
+                        }
+                    }
+                    if let Some(err) = rule_error {
+                        ui.text_colored(ERROR_COLOR, err);
+                    }
+                    Slider::new(im_str!("step (log2 of generations)"))
+                        .range(0..=MAX_STEP_LOG)
+                        .build(ui, &mut settings.step_log);
+                    Slider::new(im_str!("steps per second"))
+                        .range(MIN_RATE..=MAX_RATE)
+                        .flags(SliderFlags::LOGARITHMIC)
+                        .display_format(im_str!("%.1f"))
+                        .build(ui, &mut settings.rate);
+                    ui.checkbox(im_str!("automatic step"), &mut settings.auto_step);
+                    combo(
+                        ui,
+                        im_str!("garbage collection"),
+                        &mut settings.gc_policy,
+                        &GcPolicy::ALL,
+                    );
+                    Slider::new(im_str!("memory limit (MB)"))
+                        .range(MIN_MEMORY_LIMIT..=MAX_MEMORY_LIMIT)
+                        .flags(SliderFlags::LOGARITHMIC)
+                        .build(ui, &mut settings.memory_limit);
+                }
+                if CollapsingHeader::new(im_str!("Rendering"))
+                    .default_open(true)
+                    .build(ui)
+                {
+                    combo(ui, im_str!("theme"), &mut settings.theme, &Theme::ALL);
+                    ui.checkbox(im_str!("grid"), &mut settings.grid);
+                    ui.checkbox(im_str!("vsync"), &mut settings.vsync);
+                    ui.text_disabled("vsync takes effect on the next start");
+                }
+                if CollapsingHeader::new(im_str!("Input"))
+                    .default_open(true)
+                    .build(ui)
+                {
+                    Slider::new(im_str!("camera speed (cells)"))
+                        .range(MIN_CAMERA_SPEED..=MAX_CAMERA_SPEED)
+                        .flags(SliderFlags::LOGARITHMIC)
+                        .build(ui, &mut settings.camera_speed);
+                    Slider::new(im_str!("zoom per notch"))
+                        .range(MIN_ZOOM_FACTOR..=MAX_ZOOM_FACTOR)
+                        .build(ui, &mut settings.zoom_factor);
+                    ui.checkbox(im_str!("invert zoom"), &mut settings.invert_zoom);
+                }
+            });
+    }
+}
+
+// a combo box choosing one of `options` by its name
+fn combo<T: Copy + PartialEq + fmt::Display>(
+    ui: &Ui,
+    label: &ImStr,
+    value: &mut T,
+    options: &[T],
+) -> bool {
+    let mut index = options
+        .iter()
+        .position(|option| option == value)
+        .unwrap_or(0);
+    let changed = ComboBox::new(label).build_simple(ui, &mut index, options, &option_name);
+    *value = options[index];
+    changed
+}
+
+fn option_name<T: fmt::Display>(option: &T) -> Cow<'_, ImStr> {
+    Cow::Owned(ImString::new(option.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_survive_the_file() {
+        let settings = Settings {
+            rule: "B36/S23".parse().unwrap(),
+            step_log: 12,
+            rate: 2.5,
+            auto_step: true,
+            memory_limit: 512,
+            gc_policy: GcPolicy::EveryStep,
+            theme: Theme::Light,
+            grid: true,
+            vsync: false,
+            camera_speed: 4.0,
+            zoom_factor: 1.25,
+            invert_zoom: true,
+        };
+        assert_eq!(settings.to_string().parse::<Settings>().unwrap(), settings);
+    }
+
+    #[test]
+    fn missing_and_unknown_keys_are_fine() {
+        let settings: Settings = "# comment\n\ngrid = true\nfuture_option = 3\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                grid: true,
+                ..Settings::default()
+            }
+        );
+        assert!("grid = maybe".parse::<Settings>().is_err());
+        assert!("theme = purple".parse::<Settings>().is_err());
+    }
+}
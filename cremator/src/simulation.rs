@@ -0,0 +1,366 @@
+#[allow(unused)]
+pub use tracing::{
+    debug, debug_span, error, error_span, info, info_span, instrument, trace, trace_span, warn,
+    warn_span,
+};
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use la::{format, Activity, Rect, Snapshot, Stats, Universe};
+
+use crate::scheduler::Scheduler;
+
+pub const MAX_STEP_LOG: u8 = 60;
+// steps per second
+pub const DEFAULT_RATE: f64 = 6.0;
+pub const MAX_RATE: f64 = 1000.0;
+const RATE_FACTOR: f64 = 1.5;
+// how long the thread sleeps when there is nothing to do
+const IDLE_TIME: Duration = Duration::from_millis(1);
+// the auto mode doubles the step while a step takes less than this share of the frame budget,
+// a doubled step takes at most about twice as long
+const AUTO_GROW_SHARE: u32 = 4;
+// node table size above which the auto mode backs off, larger steps touch more nodes
+const AUTO_MEMORY_LIMIT: usize = 1 << 30;
+
+// run/pause state, step size and pace, one step advances the universe by 2^step_log generations
+pub struct Simulation {
+    running: bool,
+    step_log: u8,
+    // single steps requested while paused
+    pending_steps: u32,
+    rate: f64,
+    next_step: Instant,
+    // the step size follows the frame budget
+    auto: bool,
+}
+
+// advances the universe on its own cadence, independently of the frame rate
+pub struct SimulationThread {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+// a change of the universe waiting for the next step boundary
+struct PendingEdit {
+    // cells which may change, highlighted until the edit is applied
+    area: Option<Rect>,
+    apply: Box<dyn FnOnce(&mut Universe) + Send>,
+}
+
+struct Shared {
+    universe: Mutex<Universe>,
+    // applied in order by the simulation thread before its next step
+    pending: Mutex<Vec<PendingEdit>>,
+    controls: Mutex<Simulation>,
+    scheduler: Arc<Mutex<Scheduler>>,
+    // compacted copy of the last published state, waiting to be picked up by the renderer
+    latest: Mutex<Option<Arc<Universe>>>,
+    // statistics of the live universe at the time of the last publish
+    stats: Mutex<Stats>,
+    // cells born and died between the last two published generations
+    activity: Mutex<Activity>,
+    // the last published state, pinned in the live universe to compare the next one with
+    published: Mutex<Option<Snapshot>>,
+    stop: AtomicBool,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self {
+            running: true,
+            step_log: 0,
+            pending_steps: 0,
+            rate: DEFAULT_RATE,
+            next_step: Instant::now(),
+            auto: false,
+        }
+    }
+
+    pub fn toggle_running(&mut self) {
+        self.running = !self.running;
+        self.next_step = Instant::now();
+        info!(
+            "simulation {}",
+            if self.running { "running" } else { "paused" }
+        );
+    }
+
+    pub fn single_step(&mut self) {
+        self.pending_steps += 1;
+    }
+
+    // changing the step by hand leaves the auto mode
+    pub fn grow_step(&mut self) {
+        self.auto = false;
+        self.step_log = (self.step_log + 1).min(MAX_STEP_LOG);
+        self.log_step();
+    }
+
+    pub fn shrink_step(&mut self) {
+        self.auto = false;
+        self.step_log = self.step_log.saturating_sub(1);
+        self.log_step();
+    }
+
+    pub fn set_step_log(&mut self, step_log: u8) {
+        self.auto = false;
+        self.step_log = step_log.min(MAX_STEP_LOG);
+        self.log_step();
+    }
+
+    pub fn toggle_auto(&mut self) {
+        self.set_auto(!self.auto);
+    }
+
+    pub fn set_auto(&mut self, auto: bool) {
+        self.auto = auto;
+        info!("automatic step size: {}", self.auto);
+    }
+
+    pub fn is_auto(&self) -> bool {
+        self.auto
+    }
+
+    // adapts the step to how long the last one took and how large the node table has become
+    fn tune(&mut self, step_time: Duration, budget: Duration, memory: usize) {
+        if !self.auto {
+            return;
+        }
+        let step_log = if memory > AUTO_MEMORY_LIMIT || step_time > budget {
+            self.step_log.saturating_sub(1)
+        } else if step_time * AUTO_GROW_SHARE < budget {
+            (self.step_log + 1).min(MAX_STEP_LOG)
+        } else {
+            self.step_log
+        };
+        if step_log != self.step_log {
+            self.step_log = step_log;
+            debug!(
+                "automatic step size: {} generations",
+                format::engineering(self.step())
+            );
+        }
+    }
+
+    fn log_step(&self) {
+        info!(
+            "step size: {} generations",
+            format::engineering(self.step())
+        );
+    }
+
+    pub fn step(&self) -> u128 {
+        1 << self.step_log
+    }
+
+    pub fn step_log(&self) -> u8 {
+        self.step_log
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn speed_up(&mut self) {
+        self.set_rate(self.rate * RATE_FACTOR);
+    }
+
+    pub fn slow_down(&mut self) {
+        self.set_rate(self.rate / RATE_FACTOR);
+    }
+
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate.min(MAX_RATE);
+        info!("simulation rate: {:.1} steps/s", self.rate);
+    }
+
+    // steps which are due now
+    fn due_steps(&mut self, now: Instant) -> u32 {
+        let mut steps = std::mem::take(&mut self.pending_steps);
+        if self.running && now >= self.next_step {
+            steps += 1;
+            let interval = Duration::from_secs_f64(1.0 / self.rate);
+            // don't try to catch up after the thread fell behind
+            self.next_step = (self.next_step + interval).max(now);
+        }
+        steps
+    }
+}
+
+impl SimulationThread {
+    pub fn spawn(universe: Universe, scheduler: Arc<Mutex<Scheduler>>) -> Self {
+        let shared = Arc::new(Shared {
+            latest: Mutex::new(Some(Arc::new(universe.compacted()))),
+            stats: Mutex::new(universe.stats()),
+            activity: Mutex::new(Activity::default()),
+            published: Mutex::new(None),
+            universe: Mutex::new(universe),
+            pending: Mutex::new(Vec::new()),
+            controls: Mutex::new(Simulation::new()),
+            scheduler,
+            stop: AtomicBool::new(false),
+        });
+        let handle = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("simulation".to_owned())
+                .spawn(move || shared.run())
+                .expect("failed to spawn the simulation thread")
+        };
+        Self {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn controls(&self) -> MutexGuard<'_, Simulation> {
+        self.shared.controls.lock().unwrap()
+    }
+
+    // read only access to the live universe
+    pub fn with_universe<R>(&self, f: impl FnOnce(&Universe) -> R) -> R {
+        f(&self.shared.universe.lock().unwrap())
+    }
+
+    // changes the live universe and publishes the result
+    pub fn edit<R>(&self, f: impl FnOnce(&mut Universe) -> R) -> R {
+        let mut universe = self.shared.universe.lock().unwrap();
+        // queued edits came first
+        self.shared.apply_pending(&mut universe);
+        let result = f(&mut universe);
+        self.shared.publish(&mut universe);
+        result
+    }
+
+    // hands a change to the simulation thread, which applies it between two steps, so the
+    // caller never waits for a running step and the edit lands on a generation boundary
+    pub fn queue_edit(&self, area: Option<Rect>, f: impl FnOnce(&mut Universe) + Send + 'static) {
+        self.shared.pending.lock().unwrap().push(PendingEdit {
+            area,
+            apply: Box::new(f),
+        });
+    }
+
+    // the areas of the edits which haven't been applied yet
+    pub fn pending_areas(&self) -> Vec<Rect> {
+        self.shared
+            .pending
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|edit| edit.area)
+            .collect()
+    }
+
+    // the state published since the last call, if any
+    pub fn take_latest(&self) -> Option<Arc<Universe>> {
+        self.shared.latest.lock().unwrap().take()
+    }
+
+    pub fn stats(&self) -> Stats {
+        *self.shared.stats.lock().unwrap()
+    }
+
+    pub fn activity(&self) -> Activity {
+        *self.shared.activity.lock().unwrap()
+    }
+}
+
+impl Drop for SimulationThread {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Shared {
+    fn run(&self) {
+        while !self.stop.load(Ordering::Relaxed) {
+            if !self.pending.lock().unwrap().is_empty() {
+                let mut universe = self.universe.lock().unwrap();
+                self.apply_pending(&mut universe);
+                self.publish(&mut universe);
+            }
+            let (steps, step) = {
+                let mut controls = self.controls.lock().unwrap();
+                (controls.due_steps(Instant::now()), controls.step())
+            };
+            if steps == 0 {
+                thread::sleep(IDLE_TIME);
+                continue;
+            }
+
+            let budget = self.scheduler.lock().unwrap().budget().simulation;
+            let start = Instant::now();
+            let mut universe = self.universe.lock().unwrap();
+            let mut done = 0;
+            while done < steps && start.elapsed() < budget {
+                self.apply_pending(&mut universe);
+                universe.advance(step);
+                done += 1;
+            }
+            if done < steps {
+                debug!("simulation budget exhausted after {}/{} steps", done, steps);
+            }
+            if done > 0 {
+                let step_time = start.elapsed() / done;
+                let memory = universe.memory_usage();
+                self.controls
+                    .lock()
+                    .unwrap()
+                    .tune(step_time, budget, memory);
+            }
+            self.publish(&mut universe);
+            drop(universe);
+
+            self.scheduler
+                .lock()
+                .unwrap()
+                .record_simulation(start.elapsed(), u128::from(done) * step);
+        }
+    }
+
+    fn apply_pending(&self, universe: &mut Universe) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return;
+        }
+        debug!(
+            "applying {} edits at generation {}",
+            pending.len(),
+            format::engineering(universe.generation())
+        );
+        for edit in pending {
+            (edit.apply)(universe);
+        }
+    }
+
+    fn publish(&self, universe: &mut Universe) {
+        *self.latest.lock().unwrap() = Some(Arc::new(universe.compacted()));
+        *self.stats.lock().unwrap() = universe.stats();
+
+        // edits between two generations are not activity
+        let mut published = self.published.lock().unwrap();
+        if let Some(previous) = published.take() {
+            if previous.generation() != universe.generation() {
+                *self.activity.lock().unwrap() = universe.activity_since(&previous);
+            }
+            universe.release(previous);
+        }
+        *published = Some(universe.snapshot());
+    }
+}
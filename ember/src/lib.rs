@@ -0,0 +1,98 @@
+// lifeash for the browser, built with `wasm-pack build ember --target web`
+// a page advances the universe and draws the cells of the visible part into a canvas, see www/
+// coordinates are 32-bit because that is what a canvas can show, generations and populations are
+// javascript numbers, exact up to 2^53
+
+use wasm_bindgen::prelude::*;
+
+use lifeash::prelude::*;
+
+#[wasm_bindgen]
+pub struct Universe {
+    universe: lifeash::Universe,
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// an empty universe with Conway's rule
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            universe: lifeash::Universe::new(),
+        }
+    }
+
+    /// reads an rle pattern with its rule, the north west corner at the origin
+    #[wasm_bindgen(js_name = fromRle)]
+    pub fn from_rle(input: &str) -> Result<Universe, JsError> {
+        Ok(Self {
+            universe: lifeash::Universe::from_rle(input)?,
+        })
+    }
+
+    /// reads a macrocell file, for patterns too large for rle
+    #[wasm_bindgen(js_name = fromMacrocell)]
+    pub fn from_macrocell(input: &str) -> Result<Universe, JsError> {
+        Ok(Self {
+            universe: lifeash::Universe::from_macrocell(input)?,
+        })
+    }
+
+    /// the rulestring, e.g. "B3/S23"
+    #[wasm_bindgen(getter)]
+    pub fn rule(&self) -> String {
+        self.universe.rule().to_string()
+    }
+
+    #[wasm_bindgen(js_name = setRule)]
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsError> {
+        self.universe.set_rule(rule.parse()?);
+        Ok(())
+    }
+
+    /// advances by `generations` at once, large steps are cheap for regular patterns
+    pub fn step(&mut self, generations: u32) {
+        self.universe.advance(u128::from(generations));
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> f64 {
+        self.universe.generation() as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> f64 {
+        self.universe.population() as f64
+    }
+
+    #[wasm_bindgen(js_name = setCell)]
+    pub fn set_cell(&mut self, x: i32, y: i32, alive: bool) {
+        let cell = if alive { Cell::Alive } else { Cell::Dead };
+        self.universe.set_cell((i64::from(x), i64::from(y)), cell);
+    }
+
+    /// the alive cells of the rectangle, both corners included, as x and y pairs
+    #[wasm_bindgen(js_name = liveCellsIn)]
+    pub fn live_cells_in(&self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Vec<i32> {
+        let rect = Rect::new(
+            (i64::from(min_x), i64::from(min_y)),
+            (i64::from(max_x), i64::from(max_y)),
+        );
+        self.universe
+            .live_cells_in(rect)
+            .flat_map(|pos| [pos.x as i32, pos.y as i32])
+            .collect()
+    }
+
+    /// min x, min y, max x and max y of the alive cells, clamped to 32 bits, empty if there are
+    /// none
+    #[wasm_bindgen(js_name = boundingBox)]
+    pub fn bounding_box(&self) -> Vec<i32> {
+        let clamp = |n: i64| n.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
+        match self.universe.bounding_box() {
+            Some((min, max)) => vec![clamp(min.x), clamp(min.y), clamp(max.x), clamp(max.y)],
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+import init, { Universe } from "../pkg/ember.js";
+
+// pixels per cell
+const SCALE = 4;
+const R_PENTOMINO = "x = 3, y = 3, rule = B3/S23\nb2o$2o$bo!";
+// frames drawn by the trail, older ones dimmer
+const TRAIL_LENGTH = 8;
+
+await init();
+const canvas = document.getElementById("canvas");
+const hud = document.getElementById("hud");
+const context = canvas.getContext("2d");
+const universe = Universe.fromRle(R_PENTOMINO);
+let steps = 1;
+// the cells of the previous frames, the latest first, null while the trail is off
+let trail = null;
+
+function fillCells(cells, halfWidth, halfHeight) {
+  for (let i = 0; i < cells.length; i += 2) {
+    context.fillRect((cells[i] + halfWidth) * SCALE, (cells[i + 1] + halfHeight) * SCALE, SCALE, SCALE);
+  }
+}
+
+// the view is centered on the origin
+function draw() {
+  canvas.width = window.innerWidth;
+  canvas.height = window.innerHeight;
+  const [halfWidth, halfHeight] = [canvas.width, canvas.height].map((side) => Math.ceil(side / SCALE / 2));
+  context.fillStyle = "#111";
+  context.fillRect(0, 0, canvas.width, canvas.height);
+  if (trail) {
+    // the oldest first, so that newer frames are drawn over it
+    for (let age = trail.length - 1; age >= 0; age--) {
+      context.fillStyle = `rgba(255, 136, 0, ${(TRAIL_LENGTH - age) / (TRAIL_LENGTH + 1) / 2})`;
+      fillCells(trail[age], halfWidth, halfHeight);
+    }
+  }
+  context.fillStyle = "#f80";
+  const cells = universe.liveCellsIn(-halfWidth, -halfHeight, halfWidth, halfHeight);
+  fillCells(cells, halfWidth, halfHeight);
+  if (trail) {
+    trail.unshift(cells);
+    trail.length = Math.min(trail.length, TRAIL_LENGTH);
+  }
+  hud.textContent = `generation ${universe.generation}, population ${universe.population}, ` +
+    `${steps} per frame (+/-), trail ${trail ? "on" : "off"} (h)`;
+}
+
+function frame() {
+  universe.step(steps);
+  draw();
+  requestAnimationFrame(frame);
+}
+
+window.addEventListener("keydown", (event) => {
+  if (event.key === "+") steps *= 2;
+  if (event.key === "-") steps = Math.max(1, steps / 2);
+  if (event.key === "h") trail = trail ? null : [];
+});
+requestAnimationFrame(frame);
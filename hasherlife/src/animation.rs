@@ -0,0 +1,105 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::Result;
+use eyre::{bail, WrapErr};
+use image::{codecs::gif, Delay, Frame, RgbaImage};
+
+// how much effort the gif encoder spends on finding a palette, from 1 to 30, the frames are
+// nearly gray so the fastest search loses nothing visible
+const GIF_SPEED: i32 = 30;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    // animated png, which keeps the exact colors and is smaller for large frames
+    Apng,
+}
+
+impl Format {
+    // checked before the run, so that a typo doesn't throw away a long computation
+    pub fn from_extension(path: &Path) -> Result<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("gif") => Ok(Self::Gif),
+            Some("png") => Ok(Self::Apng),
+            _ => bail!(
+                "can't animate into {}, use a .gif or .png file",
+                path.display()
+            ),
+        }
+    }
+}
+
+// writes the frames of an animation one by one, so that they never have to be kept in memory
+pub struct Recorder {
+    path: PathBuf,
+    encoder: Encoder,
+}
+
+enum Encoder {
+    Gif(gif::GifEncoder<BufWriter<File>>, Delay),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+impl Recorder {
+    // every frame must have the given size
+    pub fn create(
+        path: &Path,
+        format: Format,
+        frames: u32,
+        fps: u16,
+        (width, height): (u32, u32),
+    ) -> Result<Self> {
+        let context = || format!("failed to create {}", path.display());
+        let file = BufWriter::new(File::create(path).wrap_err_with(context)?);
+        let encoder = match format {
+            Format::Gif => {
+                let mut encoder = gif::GifEncoder::new_with_speed(file, GIF_SPEED);
+                encoder
+                    .set_repeat(gif::Repeat::Infinite)
+                    .wrap_err_with(context)?;
+                Encoder::Gif(encoder, Delay::from_numer_denom_ms(1000, u32::from(fps)))
+            }
+            Format::Apng => {
+                let mut encoder = png::Encoder::new(file, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder
+                    .set_animated(frames, 0)
+                    .and_then(|()| encoder.set_frame_delay(1, fps))
+                    .wrap_err_with(context)?;
+                Encoder::Apng(encoder.write_header().wrap_err_with(context)?)
+            }
+        };
+        Ok(Self {
+            path: path.to_owned(),
+            encoder,
+        })
+    }
+
+    pub fn push(&mut self, frame: RgbaImage) -> Result<()> {
+        let path = &self.path;
+        let context = || format!("failed to write {}", path.display());
+        match &mut self.encoder {
+            Encoder::Gif(encoder, delay) => encoder
+                .encode_frame(Frame::from_parts(frame, 0, 0, *delay))
+                .wrap_err_with(context),
+            Encoder::Apng(writer) => writer.write_image_data(&frame).wrap_err_with(context),
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        let path = self.path;
+        match self.encoder {
+            // the trailer is written when the encoder is dropped
+            Encoder::Gif(..) => Ok(()),
+            Encoder::Apng(writer) => writer
+                .finish()
+                .wrap_err_with(|| format!("failed to write {}", path.display())),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+// crash reports: a panic writes its message, a backtrace, the command line and the steps of the
+// run so far into the data directory, and the next start points to the report
+
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    env,
+    fmt::{self, Write as _},
+    fs, io,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// steps kept for the report
+const LOGGED_ACTIONS: usize = 64;
+// names the report which hasn't been pointed to yet
+const PENDING_FILE: &str = "last-crash";
+
+// the latest steps of the run, with the statistics of the universe at the time
+static ACTIONS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+// HASHERLIFE_DATA names the directory for crash reports instead of hasherlife in the data
+// directory of the user
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("HASHERLIFE_DATA") {
+        return Some(PathBuf::from(dir));
+    }
+    let dir = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(dir.join("hasherlife"))
+}
+
+// chains a hook after the installed one, which writes a report of the first panic into `dir`
+pub fn install(dir: PathBuf) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if REPORTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        match write_report(&dir, &report(info)) {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(err) => eprintln!("failed to write a crash report: {}", err),
+        }
+    }));
+}
+
+// remembered for the report
+pub fn record(action: String) {
+    let mut actions = ACTIONS.lock().unwrap();
+    if actions.len() == LOGGED_ACTIONS {
+        actions.pop_front();
+    }
+    actions.push_back(action);
+}
+
+// the report of the last crash if it hasn't been pointed to yet
+pub fn take_pending(dir: &Path) -> Option<PathBuf> {
+    let pending = dir.join(PENDING_FILE);
+    let path = PathBuf::from(fs::read_to_string(&pending).ok()?.trim_end());
+    // the next start shouldn't mention it again
+    let _ = fs::remove_file(&pending);
+    Some(path).filter(|path| path.exists())
+}
+
+fn report(info: &PanicHookInfo<'_>) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    let mut report = String::new();
+    // writing into a string doesn't fail
+    let _ = write_sections(&mut report, info, message);
+    report
+}
+
+fn write_sections(report: &mut String, info: &PanicHookInfo<'_>, message: &str) -> fmt::Result {
+    writeln!(
+        report,
+        "{} {} crashed",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )?;
+    let args: Vec<_> = env::args().collect();
+    writeln!(report, "command line: {}", args.join(" "))?;
+    write!(report, "panicked")?;
+    if let Some(location) = info.location() {
+        write!(report, " at {}", location)?;
+    }
+    writeln!(report, ":\n{}\n", message)?;
+    writeln!(report, "steps:")?;
+    // the panic may have happened while recording a step
+    match ACTIONS.try_lock() {
+        Ok(actions) => {
+            for action in actions.iter() {
+                writeln!(report, "  {}", action)?;
+            }
+        }
+        Err(_) => writeln!(report, "  unavailable")?,
+    }
+    writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture())
+}
+
+// writes the report next to the earlier ones and marks it as not pointed to yet
+fn write_report(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let path = dir.join(format!("crash-{}.txt", secs));
+    fs::write(&path, report)?;
+    fs::write(dir.join(PENDING_FILE), path.to_string_lossy().as_bytes())?;
+    Ok(path)
+}
@@ -0,0 +1,578 @@
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufReader, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::Result;
+use eyre::{bail, eyre, WrapErr};
+
+use lifeash::{census, format, io::ParseError, kernels, prelude::*, KernelKind};
+
+mod animation;
+mod crash;
+mod plot;
+
+// longer side of the images without --scale
+const SNAPSHOT_PIXELS: f64 = 1024.0;
+const ANIMATION_PIXELS: f64 = 512.0;
+const MAX_CELL_PIXELS: f64 = 16.0;
+
+// runs a pattern file for a number of generations without a display and writes the result
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    run: Option<Run>,
+    /// file to write the result to, standard output if missing
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// format of the result, guessed from the extension of --out, rle otherwise
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+    /// print statistics about the run to standard error
+    #[arg(long)]
+    stats: bool,
+    /// draw population and bounding box over the run into a .png or .svg image
+    #[arg(long)]
+    plot: Option<PathBuf>,
+    /// points of the plot, the run is paused this many times to measure the universe
+    #[arg(long, default_value_t = 100, requires = "plot")]
+    samples: u128,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// draw the pattern at the end of the run into a png image
+    Snapshot(Snapshot),
+    /// draw the run frame by frame into a gif or an animated png
+    Animate(Animate),
+    /// run random soups, the same seed always gives the same soup
+    Soup(Soup),
+    /// run random soups until they settle and count the objects they leave by apgcode
+    Census(Census),
+}
+
+// the pattern and how long to run it
+#[derive(clap::Args)]
+struct Run {
+    /// rle or macrocell (.mc) pattern file
+    input: PathBuf,
+    /// generations to run
+    #[arg(short, long, default_value_t = 0)]
+    steps: u128,
+    /// rule to run the pattern with instead of the one in its header, e.g. B36/S23
+    #[arg(short, long)]
+    rule: Option<Rule>,
+    /// leaf evolution kernel: auto, lookup, table, bitboard or popcount, auto picks the best
+    /// one for the rule and the cpu
+    #[arg(short, long, default_value_t = KernelKind::Auto)]
+    kernel: KernelKind,
+    /// keep at most this many cached results of a level as "level=results", trades time for
+    /// memory, can be repeated
+    #[arg(long, value_parser = parse_cache_limit)]
+    cache_limit: Vec<(u8, usize)>,
+}
+
+#[derive(clap::Args)]
+struct Snapshot {
+    #[command(flatten)]
+    run: Run,
+    /// png file to draw into
+    #[arg(short, long)]
+    out: PathBuf,
+    /// pixels per cell, below 1 cells are shaded by their density, by default the bounding box
+    /// fits into 1024 pixels
+    #[arg(long)]
+    scale: Option<f64>,
+    /// empty cells drawn around the bounding box
+    #[arg(long, default_value_t = 1)]
+    margin: i64,
+}
+
+#[derive(clap::Args)]
+struct Animate {
+    #[command(flatten)]
+    run: Run,
+    /// gif or animated png file to draw into
+    #[arg(short, long)]
+    out: PathBuf,
+    /// number of frames, the first one shows the pattern after --steps generations
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    frames: u32,
+    /// generations between two frames
+    #[arg(long, default_value_t = 1)]
+    step_size: u128,
+    /// frames per second
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..))]
+    fps: u16,
+    /// region to draw as "min x,min y,max x,max y", by default everything alive in any frame
+    #[arg(long, value_parser = parse_rect, allow_hyphen_values = true)]
+    viewport: Option<Rect>,
+    /// pixels per cell, below 1 cells are shaded by their density, by default the viewport fits
+    /// into 512 pixels
+    #[arg(long)]
+    scale: Option<f64>,
+    /// empty cells drawn around the bounding boxes when there is no viewport
+    #[arg(long, default_value_t = 1, conflicts_with = "viewport")]
+    margin: i64,
+}
+
+#[derive(clap::Args)]
+struct Soup {
+    /// columns of the soup
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(i64).range(1..))]
+    width: i64,
+    /// rows of the soup, as many as columns if missing
+    #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+    height: Option<i64>,
+    /// probability of a cell to be alive
+    #[arg(short, long, default_value_t = 0.5, value_parser = parse_density)]
+    density: f64,
+    /// seed of the first soup, the others follow one by one
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// number of soups
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    count: u64,
+    /// generations to run every soup
+    #[arg(short, long, default_value_t = 0)]
+    steps: u128,
+    /// rule to run the soups with, B3/S23 if missing
+    #[arg(short, long)]
+    rule: Option<Rule>,
+    /// leaf evolution kernel: auto, lookup, table, bitboard or popcount
+    #[arg(short, long, default_value_t = KernelKind::Auto)]
+    kernel: KernelKind,
+    /// file to write the result of a single soup to
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// format of the result, guessed from the extension of --out, rle otherwise
+    #[arg(short, long, value_enum, requires = "out")]
+    format: Option<Format>,
+}
+
+#[derive(clap::Args)]
+struct Census {
+    /// columns of the soups
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(i64).range(1..))]
+    width: i64,
+    /// rows of the soups, as many as columns if missing
+    #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+    height: Option<i64>,
+    /// probability of a cell to be alive
+    #[arg(short, long, default_value_t = 0.5, value_parser = parse_density)]
+    density: f64,
+    /// seed of the first soup, the others follow one by one
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// number of soups
+    #[arg(short, long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    count: u64,
+    /// two-state rule to run the soups with, B3/S23 if missing
+    #[arg(short, long)]
+    rule: Option<Rule>,
+    /// generations after which a soup that hasn't settled counts as pathological
+    #[arg(long, default_value_t = 50_000)]
+    max_generations: u128,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Rle,
+    Macrocell,
+    Plaintext,
+}
+
+impl Format {
+    fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str)? {
+            "rle" => Some(Self::Rle),
+            "mc" => Some(Self::Macrocell),
+            "cells" => Some(Self::Plaintext),
+            _ => None,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    if let Some(dir) = crash::data_dir() {
+        if let Some(report) = crash::take_pending(&dir) {
+            eprintln!(
+                "note: hasherlife crashed the last time it ran, please attach {} to a bug report",
+                report.display()
+            );
+        }
+        crash::install(dir);
+    }
+    let args = Args::parse();
+    let run = match args.command {
+        Some(Command::Snapshot(snapshot)) => return snapshot.draw(),
+        Some(Command::Animate(animate)) => return animate.draw(),
+        Some(Command::Soup(soup)) => return soup.run(),
+        Some(Command::Census(census)) => return census.run(),
+        None => args
+            .run
+            .as_ref()
+            .expect("the pattern is required without a subcommand"),
+    };
+
+    let mut universe = run.load()?;
+    let plot = match &args.plot {
+        Some(path) => Some((path, plot::Image::from_extension(path)?)),
+        None => None,
+    };
+    let start = Instant::now();
+    match plot {
+        Some((path, image)) => {
+            let samples = sample(&mut universe, run.steps, args.samples);
+            image.write(path, &samples)?;
+        }
+        None => universe.advance(run.steps),
+    }
+    let elapsed = start.elapsed();
+
+    write(&universe, args.out.as_deref(), args.format)?;
+
+    if args.stats {
+        let stats = universe.stats();
+        eprintln!("generation: {}", format::engineering(universe.generation()));
+        eprintln!("population: {}", format::si(universe.population()));
+        if let Some((min, max)) = universe.bounding_box() {
+            eprintln!(
+                "bounding box: {}x{} at ({}, {})",
+                max.x - min.x + 1,
+                max.y - min.y + 1,
+                min.x,
+                min.y
+            );
+        }
+        eprintln!(
+            "kernel: {} (cpu feature: {})",
+            universe.kernel(),
+            kernels::cpu_feature().unwrap_or("none")
+        );
+        eprintln!("nodes: {}", format::si(stats.nodes as u128));
+        eprintln!("cache hit rate: {:.1}%", stats.cache_hit_rate() * 100.0);
+        eprintln!("level\tnodes\tresults\tmemory\thit rate");
+        for level in universe.level_stats() {
+            eprintln!(
+                "{}\t{}\t{}\t{}B\t{:.1}%",
+                level.level,
+                format::si(level.nodes as u128),
+                format::si(level.cached_results as u128),
+                format::si(level.memory as u128),
+                level.cache_hit_rate() * 100.0
+            );
+        }
+        eprintln!("time: {:?}", elapsed);
+    }
+    Ok(())
+}
+
+impl Run {
+    fn load(&self) -> Result<Universe> {
+        let mut universe = load(&self.input)?;
+        if let Some(rule) = self.rule {
+            universe.set_rule(rule);
+        }
+        universe.set_kernel(self.kernel);
+        for &(level, limit) in &self.cache_limit {
+            universe.set_level_cache_limit(level, Some(limit));
+        }
+        record(
+            &format!(
+                "loaded {}, running {} generations",
+                self.input.display(),
+                self.steps
+            ),
+            &universe,
+        );
+        Ok(universe)
+    }
+}
+
+impl Snapshot {
+    fn draw(&self) -> Result<()> {
+        // checked before the run, so that a typo doesn't throw away a long computation
+        if self.out.extension().and_then(OsStr::to_str) != Some("png") {
+            bail!("can't draw to {}, use a .png file", self.out.display());
+        }
+        check_scale(self.scale)?;
+
+        let mut universe = self.run.load()?;
+        universe.advance(self.run.steps);
+        let (min, max) = universe
+            .bounding_box()
+            .ok_or_else(|| eyre!("nothing is alive at generation {}", universe.generation()))?;
+        let margin = Offset::new(self.margin, self.margin);
+        let rect = Rect::new(min - margin, max + margin);
+        let scale = self.scale.unwrap_or_else(|| fit(rect, SNAPSHOT_PIXELS));
+        universe
+            .render_image(rect, scale)
+            .save(&self.out)
+            .wrap_err_with(|| format!("failed to write {}", self.out.display()))
+    }
+}
+
+impl Animate {
+    fn draw(&self) -> Result<()> {
+        let format = animation::Format::from_extension(&self.out)?;
+        check_scale(self.scale)?;
+
+        let mut universe = self.run.load()?;
+        universe.advance(self.run.steps);
+        let viewport = match self.viewport {
+            Some(viewport) => viewport,
+            None => self.extent(&mut universe)?,
+        };
+        let scale = self
+            .scale
+            .unwrap_or_else(|| fit(viewport, ANIMATION_PIXELS));
+
+        let first = universe.render_image(viewport, scale);
+        let mut recorder = animation::Recorder::create(
+            &self.out,
+            format,
+            self.frames,
+            self.fps,
+            first.dimensions(),
+        )?;
+        recorder.push(first)?;
+        for _ in 1..self.frames {
+            universe.advance(self.step_size);
+            recorder.push(universe.render_image(viewport, scale))?;
+        }
+        recorder.finish()
+    }
+
+    // the bounding box of all frames with the margin, runs the frames once and goes back to the
+    // first one
+    fn extent(&self, universe: &mut Universe) -> Result<Rect> {
+        let snapshot = universe.snapshot();
+        let mut extent = universe.bounding_box();
+        for _ in 1..self.frames {
+            universe.advance(self.step_size);
+            if let Some((min, max)) = universe.bounding_box() {
+                extent = Some(match extent {
+                    Some((a, b)) => (
+                        Position::new(a.x.min(min.x), a.y.min(min.y)),
+                        Position::new(b.x.max(max.x), b.y.max(max.y)),
+                    ),
+                    None => (min, max),
+                });
+            }
+        }
+        universe.restore(&snapshot);
+        universe.release(snapshot);
+
+        let (min, max) = extent.ok_or_else(|| eyre!("nothing is alive in any frame"))?;
+        let margin = Offset::new(self.margin, self.margin);
+        Ok(Rect::new(min - margin, max + margin))
+    }
+}
+
+impl Soup {
+    // prints a table of the soups after the run, one per line
+    fn run(&self) -> Result<()> {
+        if self.out.is_some() && self.count > 1 {
+            bail!("--out takes a single soup, not {}", self.count);
+        }
+        let rect = Rect::new(
+            Position::ORIGIN,
+            (self.width - 1, self.height.unwrap_or(self.width) - 1),
+        );
+
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "seed\tpopulation\twidth\theight")?;
+        for i in 0..self.count {
+            let seed = self.seed.wrapping_add(i);
+            crash::record(format!("soup {}", seed));
+            let mut universe = Universe::builder()
+                .rule(self.rule.unwrap_or_default())
+                .kernel(self.kernel)
+                .build();
+            universe.fill_random(rect, self.density, seed);
+            universe.advance(self.steps);
+
+            let (width, height) = match universe.bounding_box() {
+                Some((min, max)) => (max.x - min.x + 1, max.y - min.y + 1),
+                None => (0, 0),
+            };
+            writeln!(
+                stdout,
+                "{}\t{}\t{}\t{}",
+                seed,
+                universe.population(),
+                width,
+                height
+            )?;
+            if let Some(out) = &self.out {
+                write(&universe, Some(out), self.format)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Census {
+    // prints the objects with their counts, the most common first
+    fn run(&self) -> Result<()> {
+        let rule = self.rule.unwrap_or_default();
+        if rule.states() != 2 {
+            bail!(
+                "apgcodes need a rule with two states, {} has {}",
+                rule,
+                rule.states()
+            );
+        }
+        crash::record(format!(
+            "census of {} soups from seed {}",
+            self.count, self.seed
+        ));
+        let mut tally = census::Census::new(rule)
+            .soup_size(self.width, self.height.unwrap_or(self.width))
+            .density(self.density)
+            .max_generations(self.max_generations);
+        tally.run((0..self.count).map(|i| self.seed.wrapping_add(i)));
+
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "count\tapgcode")?;
+        for (apgcode, count) in tally.tally() {
+            writeln!(stdout, "{}\t{}", count, apgcode)?;
+        }
+        eprintln!(
+            "{} soups, {} of them pathological",
+            tally.soups(),
+            tally.pathological()
+        );
+        Ok(())
+    }
+}
+
+// writes the universe to `out`, standard output if missing
+fn write(universe: &Universe, out: Option<&Path>, format: Option<Format>) -> Result<()> {
+    let format = format
+        .or_else(|| out.and_then(Format::from_extension))
+        .unwrap_or(Format::Rle);
+    let output = match format {
+        Format::Rle => universe.to_rle(),
+        Format::Macrocell => universe.to_macrocell(),
+        Format::Plaintext => match universe.bounding_box() {
+            Some((min, max)) => universe.to_plaintext(Rect::new(min, max)),
+            None => String::new(),
+        },
+    };
+    match out {
+        Some(path) => {
+            fs::write(path, output).wrap_err_with(|| format!("failed to write {}", path.display()))
+        }
+        None => Ok(io::stdout().write_all(output.as_bytes())?),
+    }
+}
+
+fn parse_density(s: &str) -> Result<f64, String> {
+    let density: f64 = s.parse().map_err(|err| format!("{}", err))?;
+    if (0.0..=1.0).contains(&density) {
+        Ok(density)
+    } else {
+        Err(format!("{} is not between 0 and 1", density))
+    }
+}
+
+fn check_scale(scale: Option<f64>) -> Result<()> {
+    match scale {
+        Some(scale) if scale <= 0.0 => bail!("the scale must be positive, not {}", scale),
+        _ => Ok(()),
+    }
+}
+
+// the scale at which the longer side of `rect` takes `pixels` pixels, small patterns stop
+// growing at the cell size
+fn fit(rect: Rect, pixels: f64) -> f64 {
+    let side = rect.width().max(rect.height()) as f64;
+    (pixels / side).min(MAX_CELL_PIXELS)
+}
+
+// "min x,min y,max x,max y"
+fn parse_rect(s: &str) -> Result<Rect, String> {
+    let coords = s
+        .split(',')
+        .map(|coord| coord.trim().parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    match coords[..] {
+        [min_x, min_y, max_x, max_y] => Ok(Rect::new((min_x, min_y), (max_x, max_y))),
+        _ => Err("expected \"min x,min y,max x,max y\"".to_owned()),
+    }
+}
+
+fn parse_cache_limit(s: &str) -> Result<(u8, usize), String> {
+    let (level, limit) = s
+        .split_once('=')
+        .ok_or_else(|| "expected \"level=results\"".to_owned())?;
+    let level: u8 = level.trim().parse().map_err(|err| format!("{}", err))?;
+    if level > 63 {
+        return Err(format!("there is no level {}", level));
+    }
+    let limit = limit.trim().parse().map_err(|err| format!("{}", err))?;
+    Ok((level, limit))
+}
+
+// runs `steps` generations in `count` chunks as even as possible, measuring before every chunk
+// and at the end
+fn sample(universe: &mut Universe, steps: u128, count: u128) -> Vec<plot::Sample> {
+    let count = count.clamp(1, steps.max(1));
+    let mut samples = vec![plot::Sample::of(universe)];
+    for i in 0..count {
+        universe.advance(steps / count + u128::from(i < steps % count));
+        record("sampled", universe);
+        samples.push(plot::Sample::of(universe));
+    }
+    samples
+}
+
+// the state of the universe for the crash report
+fn record(action: &str, universe: &Universe) {
+    crash::record(format!(
+        "{}: generation {}, population {}, {} nodes, {:.1}% cache hits",
+        action,
+        format::engineering(universe.generation()),
+        format::si(universe.population()),
+        format::si(universe.stats().nodes as u128),
+        universe.stats().cache_hit_rate() * 100.0
+    ));
+}
+
+// macrocell files keep their tree, everything else is streamed as rle
+fn load(path: &Path) -> Result<Universe> {
+    let context = || format!("failed to load {}", path.display());
+    if Format::from_extension(path) == Some(Format::Macrocell) {
+        let input = fs::read_to_string(path).wrap_err_with(context)?;
+        return match Universe::from_macrocell(&input) {
+            Err(err @ ParseError::HashMismatch { .. }) => {
+                eprintln!("warning: {}: {}", path.display(), err);
+                Universe::from_macrocell_unverified(&input).wrap_err_with(context)
+            }
+            result => result.wrap_err_with(context),
+        };
+    }
+    let file = File::open(path).wrap_err_with(context)?;
+    let mut universe = Universe::new();
+    let header = universe
+        .read_rle(BufReader::new(file), Position::ORIGIN, |_| {})
+        .wrap_err_with(context)?;
+    universe.set_rule(header.rule.unwrap_or_default());
+    Ok(universe)
+}
@@ -0,0 +1,142 @@
+use std::{ffi::OsStr, ops::Range, path::Path};
+
+use color_eyre::Result;
+use eyre::{bail, WrapErr};
+use plotters::{
+    coord::{types::RangedCoordf64, Shift},
+    prelude::*,
+};
+
+use lifeash::{format, prelude::*};
+
+const SIZE: (u32, u32) = (1024, 768);
+const FONT: &str = "sans-serif";
+
+// the state of the universe at one point of the run
+pub struct Sample {
+    pub generation: u128,
+    pub population: u128,
+    // width and height of the bounding box, zero once everything died
+    pub width: i64,
+    pub height: i64,
+}
+
+impl Sample {
+    pub fn of(universe: &Universe) -> Self {
+        let (width, height) = match universe.bounding_box() {
+            Some((min, max)) => (max.x - min.x + 1, max.y - min.y + 1),
+            None => (0, 0),
+        };
+        Self {
+            generation: universe.generation(),
+            population: universe.population(),
+            width,
+            height,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Image {
+    Png,
+    Svg,
+}
+
+impl Image {
+    // checked before the run, so that a typo doesn't throw away a long computation
+    pub fn from_extension(path: &Path) -> Result<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("png") => Ok(Self::Png),
+            Some("svg") => Ok(Self::Svg),
+            _ => bail!("can't plot to {}, use a .png or .svg file", path.display()),
+        }
+    }
+
+    // draws population and bounding box over the generations
+    pub fn write(self, path: &Path, samples: &[Sample]) -> Result<()> {
+        match self {
+            Self::Png => draw(BitMapBackend::new(path, SIZE).into_drawing_area(), samples),
+            Self::Svg => draw(SVGBackend::new(path, SIZE).into_drawing_area(), samples),
+        }
+        .wrap_err_with(|| format!("failed to plot to {}", path.display()))
+    }
+}
+
+fn draw<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, samples: &[Sample]) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let (top, bottom) = root.split_vertically(SIZE.1 / 2);
+    let generation = |s: &Sample| s.generation as f64;
+    let generations =
+        samples.first().map_or(0.0, generation)..samples.last().map_or(0.0, generation).max(1.0);
+
+    let population = samples.iter().map(|s| s.population as f64);
+    let mut chart = cell_chart(
+        &top,
+        "population",
+        generations.clone(),
+        max(population.clone()),
+    )?;
+    chart.draw_series(LineSeries::new(
+        samples.iter().map(generation).zip(population),
+        BLUE.stroke_width(2),
+    ))?;
+
+    let sides = samples
+        .iter()
+        .flat_map(|s| [s.width as f64, s.height as f64]);
+    let mut chart = cell_chart(&bottom, "bounding box", generations, max(sides))?;
+    for (label, color, side) in [
+        ("width", RED, (|s: &Sample| s.width) as fn(&Sample) -> i64),
+        ("height", GREEN, |s: &Sample| s.height),
+    ] {
+        chart
+            .draw_series(LineSeries::new(
+                samples.iter().map(|s| (generation(s), side(s) as f64)),
+                color.stroke_width(2),
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .border_style(BLACK)
+        .background_style(WHITE)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+// a chart of cell counts over the generations, with its axes drawn
+fn cell_chart<'a, DB: DrawingBackend>(
+    area: &'a DrawingArea<DB, Shift>,
+    caption: &str,
+    generations: Range<f64>,
+    max: f64,
+) -> Result<ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>>
+where
+    DB::ErrorType: 'static,
+{
+    let mut chart = ChartBuilder::on(area)
+        .caption(caption, (FONT, 20))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(generations, 0.0..max)?;
+    chart
+        .configure_mesh()
+        .x_desc("generation")
+        .x_label_formatter(&|&x| format::si(x as u128))
+        .y_label_formatter(&|&y| format::si(y as u128))
+        .draw()?;
+    Ok(chart)
+}
+
+// upper end of an axis, never empty so that flat series still get a chart
+fn max(values: impl Iterator<Item = f64>) -> f64 {
+    values.fold(1.0, f64::max) * 1.05
+}
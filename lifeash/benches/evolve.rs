@@ -0,0 +1,103 @@
+// throughput of the evolution kernel in generations per second
+// every benchmark also prints the node table and cache statistics of one run, so regressions in
+// hashing or memoization show up even when the timing noise hides them
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use lifeash::{patterns, prelude::*};
+
+const HALFMAX: &str = include_str!("halfmax.rle");
+// generation at which the r-pentomino has stabilized
+const R_PENTOMINO_STABLE: u128 = 1103;
+const SOUP_SIDE: i64 = 256;
+
+fn report(name: &str, universe: &Universe) {
+    let stats = universe.stats();
+    println!(
+        "{}: generation {}, population {}, {} nodes, cache hit rate {:.1}%",
+        name,
+        universe.generation(),
+        universe.population(),
+        stats.nodes,
+        stats.cache_hit_rate() * 100.0
+    );
+}
+
+// runs `generations` on a fresh copy of the universe `setup` builds in every iteration
+fn bench_run(c: &mut Criterion, name: &str, setup: impl Fn() -> Universe, generations: u128) {
+    let mut universe = setup();
+    universe.advance(generations);
+    report(name, &universe);
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(generations as u64));
+    group.sample_size(10);
+    group.bench_function("advance", |b| {
+        b.iter_batched(
+            &setup,
+            |mut universe| {
+                universe.advance(generations);
+                universe
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+// quadratic growth, the pattern keeps producing new nodes
+fn breeder(c: &mut Criterion) {
+    for &generations in &[1 << 10, 1 << 14] {
+        bench_run(
+            c,
+            &format!("halfmax/{}", generations),
+            || Universe::from_rle(HALFMAX).unwrap(),
+            generations,
+        );
+    }
+}
+
+// chaotic at first, then periodic ash which the cache serves
+fn methuselah(c: &mut Criterion) {
+    let pattern = patterns::get("r-pentomino").unwrap().pattern();
+    bench_run(
+        c,
+        "r-pentomino",
+        || {
+            let mut universe = Universe::new();
+            universe.stamp(&pattern, Position::ORIGIN);
+            universe
+        },
+        R_PENTOMINO_STABLE,
+    );
+}
+
+// a dense random soup, mostly cache misses
+fn soup(c: &mut Criterion) {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut cells = Vec::new();
+    for y in 0..SOUP_SIDE {
+        for x in 0..SOUP_SIDE {
+            // xorshift, deterministic so runs are comparable
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            if seed & 1 == 1 {
+                cells.push((Position::new(x, y), Cell::Alive));
+            }
+        }
+    }
+    bench_run(
+        c,
+        &format!("soup {}x{}", SOUP_SIDE, SOUP_SIDE),
+        || {
+            let mut universe = Universe::new();
+            universe.set_cells(cells.iter().copied());
+            universe
+        },
+        1 << 10,
+    );
+}
+
+criterion_group!(benches, breeder, methuselah, soup);
+criterion_main!(benches);
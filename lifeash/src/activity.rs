@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::Cell,
+    node::Node,
+    universe::{Id, Snapshot, Universe},
+};
+
+// cells which came alive or stopped being alive between two states of a universe
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Activity {
+    pub births: u128,
+    pub deaths: u128,
+}
+
+impl Universe {
+    /// the cells born and died between the snapshot and now, subtrees which are shared by
+    /// both states are skipped as a whole
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// let snapshot = universe.snapshot();
+    /// universe.evolve();
+    /// let activity = universe.activity_since(&snapshot);
+    /// // the blinker turns, the ends die and two cells are born next to the middle
+    /// assert_eq!((activity.births, activity.deaths), (2, 2));
+    /// universe.release(snapshot);
+    /// ```
+    pub fn activity_since(&mut self, snapshot: &Snapshot) -> Activity {
+        let (before, after) = self.aligned_with(snapshot);
+        self.tree_activity(before, after, &mut HashMap::new())
+    }
+
+    fn tree_activity(
+        &self,
+        before: Id,
+        after: Id,
+        memo: &mut HashMap<(Id, Id), Activity>,
+    ) -> Activity {
+        if before == after {
+            return Activity::default();
+        }
+        if let Some(&activity) = memo.get(&(before, after)) {
+            return activity;
+        }
+        let activity = match (before.node(self), after.node(self)) {
+            (Node::Leaf(before), Node::Leaf(after)) => {
+                let (was, is) = (before.0 == Cell::Alive, after.0 == Cell::Alive);
+                Activity {
+                    births: u128::from(!was && is),
+                    deaths: u128::from(was && !is),
+                }
+            }
+            (Node::Inode(before), Node::Inode(after)) => [
+                (before.nw, after.nw),
+                (before.ne, after.ne),
+                (before.sw, after.sw),
+                (before.se, after.se),
+            ]
+            .iter()
+            .fold(Activity::default(), |sum, &(before, after)| {
+                let activity = self.tree_activity(before, after, memo);
+                Activity {
+                    births: sum.births + activity.births,
+                    deaths: sum.deaths + activity.deaths,
+                }
+            }),
+            _ => unreachable!("trees of the same level"),
+        };
+        memo.insert((before, after), activity);
+        activity
+    }
+}
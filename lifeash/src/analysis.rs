@@ -0,0 +1,171 @@
+// questions about how a pattern behaves over time
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    census::Kind,
+    core::{Offset, Position, Rect},
+    universe::{cells_hash128, Universe},
+};
+
+// generations remembered by `Universe::run_until_stable`, longer periods aren't found
+const STABLE_HISTORY: usize = 4096;
+
+// a pattern which comes back after `period` generations, moved by `displacement`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Periodicity {
+    // the smallest period
+    pub period: u64,
+    // cells moved per period, zero unless it is a ship
+    pub displacement: Offset,
+}
+
+impl Periodicity {
+    /// a still life, an oscillator or a ship
+    ///
+    /// ```
+    /// use lifeash::{analysis, census::Kind, prelude::*};
+    ///
+    /// let blinker = Universe::from_rle("3o!").unwrap();
+    /// let periodicity = analysis::detect_period(&blinker, 10).unwrap();
+    /// assert_eq!(periodicity.kind(), Kind::Oscillator);
+    /// ```
+    pub fn kind(&self) -> Kind {
+        if self.displacement != Offset::new(0, 0) {
+            Kind::Ship
+        } else if self.period > 1 {
+            Kind::Oscillator
+        } else {
+            Kind::StillLife
+        }
+    }
+}
+
+// a universe which has become periodic
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stabilization {
+    // the first generation of the periodic part
+    pub generation: u128,
+    pub periodicity: Periodicity,
+}
+
+impl Universe {
+    /// runs one generation after another until the universe repeats itself, possibly moved,
+    /// for at most `max_gens` generations, and stops at the first repetition
+    /// the states are told apart by a 128-bit hash of their cells relative to the bounding box,
+    /// only those of the last 4096 generations are kept
+    /// escaping ships keep a universe from ever becoming periodic as a whole
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut pi = Universe::from_rle("3o$obo$obo!").unwrap();
+    /// let stable = pi.run_until_stable(1000).unwrap();
+    /// assert_eq!(stable.generation, 173);
+    /// assert_eq!(stable.periodicity.period, 2);
+    /// assert_eq!(pi.generation(), 175);
+    ///
+    /// let mut r_pentomino = Universe::from_rle("b2o$2o$bo!").unwrap();
+    /// assert_eq!(r_pentomino.run_until_stable(100), None);
+    /// assert_eq!(r_pentomino.generation(), 100);
+    /// ```
+    pub fn run_until_stable(&mut self, max_gens: u128) -> Option<Stabilization> {
+        // the generation and the corner of the bounding box of every remembered state
+        let mut seen: HashMap<u128, (u128, Position)> = HashMap::new();
+        let mut order = VecDeque::with_capacity(STABLE_HISTORY);
+        let end = self.generation() + max_gens;
+        loop {
+            let generation = self.generation();
+            let (min, hash) = match self.bounding_box() {
+                Some((min, max)) => (min, cells_hash128(self.cells_in(Rect::new(min, max)), min)),
+                None => (Position::ORIGIN, 0),
+            };
+            // the first repetition, an earlier one would have been found earlier
+            if let Some(&(start, start_min)) = seen.get(&hash) {
+                return Some(Stabilization {
+                    generation: start,
+                    periodicity: Periodicity {
+                        period: (generation - start) as u64,
+                        displacement: Offset::new(min.x - start_min.x, min.y - start_min.y),
+                    },
+                });
+            }
+            if generation >= end {
+                return None;
+            }
+            if order.len() == STABLE_HISTORY {
+                seen.remove(&order.pop_front().unwrap());
+            }
+            seen.insert(hash, (generation, min));
+            order.push_back(hash);
+            self.evolve();
+        }
+    }
+}
+
+/// the period of the whole universe and how far it moves per period, if it repeats within
+/// `max_period` generations, the universe itself is left alone
+/// a state without displacement is compared with the start by its canonical tree, which is the
+/// same node exactly if the cells are the same, a moved one by a 128-bit hash of its cells
+/// relative to their bounding box
+///
+/// ```
+/// use lifeash::{analysis, prelude::*};
+///
+/// let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+/// let periodicity = analysis::detect_period(&glider, 10).unwrap();
+/// assert_eq!(periodicity.period, 4);
+/// assert_eq!(periodicity.displacement, Offset::new(1, 1));
+///
+/// let r_pentomino = Universe::from_rle("b2o$2o$bo!").unwrap();
+/// assert_eq!(analysis::detect_period(&r_pentomino, 100), None);
+/// ```
+pub fn detect_period(universe: &Universe, max_period: u64) -> Option<Periodicity> {
+    let mut copy = universe.compacted();
+    let start = copy.snapshot();
+    let population = copy.population();
+    let bounds = copy.bounding_box();
+    let shape = bounds.map(|(min, max)| cells_hash128(copy.cells_in(Rect::new(min, max)), min));
+
+    let mut found = None;
+    for period in 1..=max_period {
+        copy.evolve();
+        if copy.population() != population {
+            continue;
+        }
+        let (before, after) = match (bounds, copy.bounding_box()) {
+            (Some(before), Some(after)) => (before, after),
+            // nothing to move, an empty universe stays empty
+            _ => {
+                found = Some(Periodicity {
+                    period,
+                    displacement: Offset::new(0, 0),
+                });
+                break;
+            }
+        };
+        let displacement = Offset::new(after.0.x - before.0.x, after.0.y - before.0.y);
+        // the bounding box has to move as a whole
+        if Offset::new(after.1.x - before.1.x, after.1.y - before.1.y) != displacement {
+            continue;
+        }
+        let same = if displacement == Offset::new(0, 0) {
+            let (before, after) = copy.aligned_with(&start);
+            before == after
+        } else {
+            let cells = copy.cells_in(Rect::new(after.0, after.1));
+            Some(cells_hash128(cells, after.0)) == shape
+        };
+        if same {
+            found = Some(Periodicity {
+                period,
+                displacement,
+            });
+            break;
+        }
+    }
+    copy.release(start);
+    found
+}
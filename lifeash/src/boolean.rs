@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::{Cell, Position},
+    node::Node,
+    pattern::Pattern,
+    universe::{Id, Universe},
+};
+
+// cells in any state but dead belong to the set, where both do the cell of the first universe
+// is kept, except by xor which leaves a live cell wherever the states differ
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl SetOp {
+    fn leaf(self, a: Cell, b: Cell) -> Cell {
+        let (in_a, in_b) = (a != Cell::Dead, b != Cell::Dead);
+        match self {
+            SetOp::Union if in_a => a,
+            SetOp::Union => b,
+            SetOp::Intersection if in_a && in_b => a,
+            SetOp::Difference if in_a && !in_b => a,
+            SetOp::Xor if a != b => Cell::Alive,
+            _ => Cell::Dead,
+        }
+    }
+}
+
+impl Universe {
+    /// combines the cells of `other` into this universe, e.g. the difference with a rectangle
+    /// clears it
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, SetOp};
+    ///
+    /// let mut universe = Universe::from_rle("3o$3o$3o!").unwrap();
+    /// let mut middle = Universe::new();
+    /// middle.set_cell((1, 1), Cell::Alive);
+    ///
+    /// universe.combine(&middle, SetOp::Difference);
+    /// assert_eq!(universe.population(), 8);
+    /// universe.combine(&middle, SetOp::Xor);
+    /// assert_eq!(universe.population(), 9);
+    /// ```
+    pub fn combine(&mut self, other: &Universe, op: SetOp) {
+        self.record_edit();
+        let (a, b) = self.align_with_root(other);
+        let root = self.combine_tree(a, b, op, &mut HashMap::new());
+        self.set_root(root);
+    }
+
+    /// the cells of both universes, in a new one with the rule of this one
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let block = Universe::from_rle("2o$2o!").unwrap();
+    /// let blinker = Universe::from_rle("3o!").unwrap();
+    /// assert_eq!(block.union(&blinker).population(), 5);
+    /// ```
+    pub fn union(&self, other: &Universe) -> Universe {
+        self.combined(other, SetOp::Union)
+    }
+
+    /// the cells which both universes have, in a new one with the rule of this one
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let block = Universe::from_rle("2o$2o!").unwrap();
+    /// let blinker = Universe::from_rle("3o!").unwrap();
+    /// assert_eq!(block.intersection(&blinker).population(), 2);
+    /// ```
+    pub fn intersection(&self, other: &Universe) -> Universe {
+        self.combined(other, SetOp::Intersection)
+    }
+
+    /// the cells of this universe which `other` doesn't have, in a new one with the rule of this
+    /// one
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let block = Universe::from_rle("2o$2o!").unwrap();
+    /// let blinker = Universe::from_rle("3o!").unwrap();
+    /// assert_eq!(block.difference(&blinker).population(), 2);
+    /// assert_eq!(blinker.difference(&block).population(), 1);
+    /// ```
+    pub fn difference(&self, other: &Universe) -> Universe {
+        self.combined(other, SetOp::Difference)
+    }
+
+    // both trees are copied into the new universe, where subtrees with the same cells are the
+    // same node and are resolved as a whole
+    pub(crate) fn combined(&self, other: &Universe, op: SetOp) -> Universe {
+        let mut combined = Universe::builder()
+            .rule(self.rule())
+            .backend(self.backend())
+            .build();
+        let root = combined.import_tree(self, self.root(), &mut HashMap::new());
+        combined.set_root(root);
+        let (a, b) = combined.align_with_root(other);
+        let root = combined.combine_tree(a, b, op, &mut HashMap::new());
+        combined.set_root(root);
+        combined
+    }
+
+    // combines two trees of the same level, empty and equal subtrees are resolved without
+    // descending
+    fn combine_tree(&mut self, a: Id, b: Id, op: SetOp, memo: &mut HashMap<(Id, Id), Id>) -> Id {
+        let (a_node, b_node) = (a.node(self), b.node(self));
+        let level = a_node.level();
+        let (a_empty, b_empty) = (a_node.population() == 0, b_node.population() == 0);
+
+        match op {
+            SetOp::Union | SetOp::Intersection if a == b => return a,
+            SetOp::Difference | SetOp::Xor if a == b => return self.new_empty_tree(level),
+            // xor can't return a side as it is, its dying cells become alive
+            SetOp::Union if a_empty => return b,
+            SetOp::Union | SetOp::Difference if b_empty => return a,
+            SetOp::Intersection | SetOp::Difference if a_empty => return a,
+            SetOp::Intersection if b_empty => return b,
+            SetOp::Union if a_node.is_full() => return a,
+            SetOp::Intersection if b_node.is_full() => return a,
+            SetOp::Difference if b_node.is_full() => return self.new_empty_tree(level),
+            _ => {}
+        }
+        if let Some(&id) = memo.get(&(a, b)) {
+            return id;
+        }
+
+        let id = match (a_node, b_node) {
+            (Node::Leaf(a), Node::Leaf(b)) => {
+                let cell = op.leaf(a.0, b.0);
+                self.new_leaf(cell)
+            }
+            (Node::Inode(a), Node::Inode(b)) => {
+                let pairs = [(a.nw, b.nw), (a.ne, b.ne), (a.sw, b.sw), (a.se, b.se)];
+                let [nw, ne, sw, se] = pairs.map(|(a, b)| self.combine_tree(a, b, op, memo));
+                self.new_inode(nw, ne, sw, se)
+            }
+            _ => unreachable!("trees of the same level"),
+        };
+        memo.insert((a, b), id);
+        id
+    }
+}
+
+impl Pattern {
+    /// the cells of both patterns, with the name, comments and rule of this one
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let a = Pattern { cells: vec![Position::new(0, 0)], ..Pattern::default() };
+    /// let b = Pattern { cells: vec![Position::new(-1, 0)], ..Pattern::default() };
+    /// assert_eq!(a.union(&b).cells, [(-1, 0), (0, 0)].map(Position::from));
+    /// ```
+    pub fn union(&self, other: &Pattern) -> Pattern {
+        self.combined(other, SetOp::Union)
+    }
+
+    /// the cells which both patterns have, with the name, comments and rule of this one
+    pub fn intersection(&self, other: &Pattern) -> Pattern {
+        self.combined(other, SetOp::Intersection)
+    }
+
+    /// the cells of this pattern which `other` doesn't have, with the name, comments and rule
+    /// of this one
+    pub fn difference(&self, other: &Pattern) -> Pattern {
+        self.combined(other, SetOp::Difference)
+    }
+
+    /// the cells which only one of the patterns has, with the name, comments and rule of this
+    /// one
+    pub fn xor(&self, other: &Pattern) -> Pattern {
+        self.combined(other, SetOp::Xor)
+    }
+
+    // both patterns are stamped at the origin, the result is sorted like `Universe::extract`
+    fn combined(&self, other: &Pattern, op: SetOp) -> Pattern {
+        let mut universe = Universe::new();
+        universe.stamp(self, Position::ORIGIN);
+        let mut other_universe = Universe::new();
+        other_universe.stamp(other, Position::ORIGIN);
+        universe.combine(&other_universe, op);
+        let mut cells: Vec<Position> = universe.live_cells().collect();
+        cells.sort_by_key(|pos| (pos.y, pos.x));
+        Pattern {
+            name: self.name.clone(),
+            comments: self.comments.clone(),
+            rule: self.rule,
+            cells,
+        }
+    }
+}
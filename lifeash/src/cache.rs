@@ -0,0 +1,152 @@
+// the cached results level by level, so that the cache can be tuned for the pattern: wide
+// patterns fill the low levels with results which are rarely asked for again, while deep but
+// sparse ones live from the few results of the high levels
+
+use std::{collections::VecDeque, iter};
+
+use crate::{
+    core::Level,
+    node::Node,
+    universe::{Id, Universe, NODE_BYTES},
+};
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LevelCache {
+    pub(crate) hits: u64,
+    pub(crate) misses: u64,
+    // results kept at most at this level, the oldest ones are dropped first
+    pub(crate) limit: Option<usize>,
+    // the trees holding a result, oldest first, only tracked while there is a limit
+    pub(crate) cached: VecDeque<Id>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct LevelStats {
+    pub level: u8,
+    // nodes of this level in the table, including the copies of the worker threads
+    pub nodes: usize,
+    // nodes which hold an evolved result
+    pub cached_results: usize,
+    // estimated size of the nodes in bytes
+    pub memory: usize,
+    // lookups of results of this level which were found in the cache or had to be computed
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub cache_limit: Option<usize>,
+}
+
+impl LevelStats {
+    /// fraction of the results of this level served from the cache
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(1000);
+    /// let levels = universe.level_stats();
+    /// assert!(levels.iter().any(|level| level.cache_hit_rate() > 0.5));
+    /// ```
+    pub fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl Universe {
+    /// the nodes, cached results and lookups of every level, from the leaves up to the highest
+    /// level in use
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(1000);
+    /// let levels = universe.level_stats();
+    /// assert_eq!(levels[0].level, 0);
+    /// let nodes: usize = levels.iter().map(|level| level.nodes).sum();
+    /// assert_eq!(nodes, universe.stats().nodes);
+    /// let misses: u64 = levels.iter().map(|level| level.cache_misses).sum();
+    /// assert_eq!(misses, universe.stats().cache_misses);
+    /// ```
+    pub fn level_stats(&self) -> Vec<LevelStats> {
+        let mut levels: Vec<_> = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(level, cache)| LevelStats {
+                level: level as u8,
+                cache_hits: cache.hits,
+                cache_misses: cache.misses,
+                cache_limit: cache.limit,
+                ..LevelStats::default()
+            })
+            .collect();
+        for universe in iter::once(self).chain(self.workers.iter().map(|worker| &worker.universe)) {
+            for id in universe.live_ids() {
+                let (level, cached) = match id.node(universe) {
+                    Node::Leaf(_) => (0, false),
+                    Node::Inode(inode) => (inode.level.value(), inode.has_result()),
+                };
+                let stats = &mut levels[usize::from(level)];
+                stats.nodes += 1;
+                stats.cached_results += usize::from(cached);
+                stats.memory += NODE_BYTES;
+            }
+        }
+
+        let used = levels.iter().rposition(|level| {
+            level.nodes > 0
+                || level.cache_hits + level.cache_misses > 0
+                || level.cache_limit.is_some()
+        });
+        levels.truncate(used.map_or(0, |level| level + 1));
+        levels
+    }
+
+    /// keeps at most `limit` results of trees of `level`, dropping the oldest ones first, or
+    /// any number if `None`
+    /// the dropped results are computed again when they are needed, so this trades time for
+    /// memory, see also `UniverseBuilder::level_cache_limit`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(1000);
+    /// universe.set_level_cache_limit(4, Some(10));
+    /// assert!(universe.level_stats()[4].cached_results <= 10);
+    /// universe.advance(1000);
+    /// assert!(universe.level_stats()[4].cached_results <= 10);
+    /// assert_eq!(universe.population(), 5);
+    /// ```
+    pub fn set_level_cache_limit(&mut self, level: u8, limit: Option<usize>) {
+        assert!(
+            level <= Level::MAX_LEVEL.value(),
+            "there is no level {}",
+            level
+        );
+        let cached = match limit {
+            Some(_) => self
+                .live_ids()
+                .filter(|id| match id.node(self) {
+                    Node::Inode(inode) => inode.level == level && inode.has_result(),
+                    Node::Leaf(_) => false,
+                })
+                .collect(),
+            None => VecDeque::new(),
+        };
+        let cache = &mut self.levels[usize::from(level)];
+        cache.limit = limit;
+        cache.cached = cached;
+        self.trim_cache(level);
+
+        for worker in &mut self.workers {
+            worker.universe.set_level_cache_limit(level, limit);
+        }
+    }
+}
@@ -0,0 +1,509 @@
+// ash censuses in the spirit of apgsearch: random soups run until they settle into periodic ash,
+// which is split into its objects and tallied by their apgcodes
+// the soups are the ones of `Universe::fill_random`, so every soup can be repeated from its seed
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    core::{Cell, Offset, Position, Rect},
+    pattern::Pattern,
+    rule::Rule,
+    universe::Universe,
+    wechsler::{apgcode, normalized},
+};
+
+// the population is sampled this often, a multiple of the periods of the common objects, so
+// that their ash has the same population in every sample
+const SAMPLE_INTERVAL: u128 = 60;
+// how often a period has to repeat in the samples before the ash is split
+const REPEATS: u64 = 4;
+// objects which touch in one phase may be apart in another, so the ash is split in this many
+// phases and the finest split is kept
+const SPLIT_PHASES: u128 = 4;
+// generations run to check that the objects don't interact, at most this many of their periods
+const MAX_CHECKED_PERIODS: u64 = 4;
+// cells searched around the position given to `object_at`, larger objects aren't found
+const PICK_RADIUS: i64 = 64;
+// common objects of conway's life, by their apgcode
+const NAMES: [(&str, &str); 26] = [
+    ("xs4_33", "block"),
+    ("xs4_252", "tub"),
+    ("xs5_253", "boat"),
+    ("xs6_696", "beehive"),
+    ("xs6_356", "ship"),
+    ("xs6_25a4", "barge"),
+    ("xs6_39c", "aircraft carrier"),
+    ("xs6_bd", "snake"),
+    ("xs7_2596", "loaf"),
+    ("xs7_25ac", "long boat"),
+    ("xs7_178c", "eater 1"),
+    ("xs8_6996", "pond"),
+    ("xs8_69ic", "mango"),
+    ("xs8_35ac", "long ship"),
+    ("xs8_25ak8", "long barge"),
+    ("xs9_31ego", "integral sign"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xp2_2a54", "clock"),
+    ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", "pulsar"),
+    ("xp15_4r4z4r4", "pentadecathlon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+    ("xq4_27dee6", "middleweight spaceship"),
+    ("xq4_27deee6", "heavyweight spaceship"),
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Kind {
+    StillLife,
+    Oscillator,
+    Ship,
+}
+
+// a periodic object on its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Object {
+    // e.g. xs4_33 for the block, xp2_7 for the blinker or xq4_153 for the glider
+    pub apgcode: String,
+    pub kind: Kind,
+    pub period: u64,
+    // cells moved per period, zero unless it is a ship
+    pub displacement: Offset,
+    // the phase it was found in
+    pub cells: Vec<Position>,
+}
+
+// the objects a soup settled into
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Ash {
+    pub seed: u64,
+    // the generation at which the soup was found to be periodic, it may have been so earlier
+    pub generation: u128,
+    pub objects: Vec<Object>,
+}
+
+/// the object formed by the cells of `pattern` if they repeat within `max_period` generations,
+/// possibly moved, the rule of the pattern is ignored
+///
+/// ```
+/// use lifeash::{census::{self, Kind}, prelude::*};
+///
+/// let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+/// let glider = glider.extract(Rect::new((0, 0), (2, 2)));
+/// let object = census::identify(&glider, Rule::CONWAY, 10).unwrap();
+/// assert_eq!(object.apgcode, "xq4_153");
+/// assert_eq!(object.kind, Kind::Ship);
+/// assert_eq!(object.displacement, Offset::new(1, 1));
+///
+/// let r_pentomino = Universe::from_rle("b2o$2o$bo!").unwrap();
+/// let r_pentomino = r_pentomino.extract(Rect::new((0, 0), (2, 2)));
+/// assert_eq!(census::identify(&r_pentomino, Rule::CONWAY, 100), None);
+/// ```
+pub fn identify(pattern: &Pattern, rule: Rule, max_period: u64) -> Option<Object> {
+    assert_two_states(rule);
+    let mut universe = Universe::builder().rule(rule).build();
+    universe.stamp(pattern, Position::ORIGIN);
+    let cells: Vec<_> = universe.live_cells().collect();
+    let (start, origin) = normalized(cells.clone())?;
+
+    let mut phases = vec![start];
+    for period in 1..=max_period {
+        universe.evolve();
+        let (phase, min) = normalized(universe.live_cells().collect())?;
+        if phase == phases[0] {
+            let displacement = Offset::new(min.x - origin.x, min.y - origin.y);
+            let kind = if displacement != Offset::new(0, 0) {
+                Kind::Ship
+            } else if period > 1 {
+                Kind::Oscillator
+            } else {
+                Kind::StillLife
+            };
+            return Some(Object {
+                apgcode: apgcode(kind, period, &phases),
+                kind,
+                period,
+                displacement,
+                cells,
+            });
+        }
+        phases.push(phase);
+    }
+    None
+}
+
+/// the object which the live cell at or next to `pos` belongs to, with the live cells connected
+/// to it, if it repeats within `max_period` generations
+/// objects reaching further than 64 cells from `pos` aren't found, the rule needs two states
+///
+/// ```
+/// use lifeash::{census, prelude::*};
+///
+/// let universe = Universe::from_rle("3o5b2o$8b2o!").unwrap();
+/// let blinker = census::object_at(&universe, Position::new(1, 1), 10).unwrap();
+/// assert_eq!(blinker.apgcode, "xp2_7");
+/// assert_eq!(blinker.cells.len(), 3);
+/// assert_eq!(census::object_at(&universe, Position::new(4, 0), 10), None);
+/// ```
+pub fn object_at(universe: &Universe, pos: Position, max_period: u64) -> Option<Object> {
+    let window = Rect::new(
+        (pos.x - PICK_RADIUS, pos.y - PICK_RADIUS),
+        (pos.x + PICK_RADIUS, pos.y + PICK_RADIUS),
+    );
+    let occupied: HashSet<_> = universe.live_cells_in(window).collect();
+    let start = [
+        (0, 0),
+        (0, -1),
+        (-1, 0),
+        (1, 0),
+        (0, 1),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+        (1, 1),
+    ]
+    .iter()
+    .map(|&(dx, dy)| Position::new(pos.x + dx, pos.y + dy))
+    .find(|pos| occupied.contains(pos))?;
+    // cells one empty cell apart, like the arms of a pulsar, may belong to the same object
+    (1..=2).find_map(|reach| {
+        let cells = cluster(start, &occupied, reach);
+        let cut = cells.iter().any(|pos| {
+            pos.x <= window.min.x
+                || pos.x >= window.max.x
+                || pos.y <= window.min.y
+                || pos.y >= window.max.y
+        });
+        if cut {
+            return None;
+        }
+        let pattern = Pattern {
+            cells,
+            ..Pattern::default()
+        };
+        identify(&pattern, universe.rule(), max_period)
+            .filter(|object| undisturbed(object, &occupied, universe.rule()))
+    })
+}
+
+// whether the object runs through its phases on its own in the same way as among `occupied`
+fn undisturbed(object: &Object, occupied: &HashSet<Position>, rule: Rule) -> bool {
+    let mut alone = Universe::builder().rule(rule).build();
+    alone.set_cells(object.cells.iter().map(|&pos| (pos, Cell::Alive)));
+    let mut among = Universe::builder().rule(rule).build();
+    among.set_cells(occupied.iter().map(|&pos| (pos, Cell::Alive)));
+    (0..=object.period).all(|_| {
+        let (min, max) = alone.bounding_box().unwrap();
+        // with the cells next to it, which would be born or die if it was disturbed
+        let around = Rect::new((min.x - 1, min.y - 1), (max.x + 1, max.y + 1));
+        let same = sorted(alone.live_cells_in(around).collect())
+            == sorted(among.live_cells_in(around).collect());
+        alone.evolve();
+        among.evolve();
+        same
+    })
+}
+
+/// the name of a common object of Conway's Life
+///
+/// ```
+/// use lifeash::census;
+///
+/// assert_eq!(census::common_name("xq4_153"), Some("glider"));
+/// assert_eq!(census::common_name("xs8_33y633"), None);
+/// ```
+pub fn common_name(apgcode: &str) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find(|&&(code, _)| code == apgcode)
+        .map(|&(_, name)| name)
+}
+
+// runs random soups and counts the objects they settle into
+pub struct Census {
+    rule: Rule,
+    width: i64,
+    height: i64,
+    density: f64,
+    max_generations: u128,
+    max_period: u64,
+    soups: u64,
+    // soups which didn't settle within the generations
+    pathological: u64,
+    tally: HashMap<String, u64>,
+}
+
+impl Census {
+    /// 16x16 soups with half of the cells alive, like apgsearch
+    ///
+    /// ```
+    /// use lifeash::{census::Census, prelude::*};
+    ///
+    /// let mut census = Census::new(Rule::CONWAY);
+    /// census.run(0..2);
+    /// assert_eq!(census.soups(), 2);
+    /// let (apgcode, _) = census.tally()[0];
+    /// assert_eq!(apgcode, "xs4_33");
+    /// ```
+    pub fn new(rule: Rule) -> Self {
+        assert_two_states(rule);
+        Self {
+            rule,
+            width: 16,
+            height: 16,
+            density: 0.5,
+            max_generations: 50_000,
+            max_period: 60,
+            soups: 0,
+            pathological: 0,
+            tally: HashMap::new(),
+        }
+    }
+
+    pub fn soup_size(mut self, width: i64, height: i64) -> Self {
+        assert!(width > 0 && height > 0, "soups can't be empty");
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// soups which are still active after `generations` count as pathological
+    ///
+    /// ```
+    /// use lifeash::{census::Census, prelude::*};
+    ///
+    /// let mut census = Census::new(Rule::CONWAY).max_generations(10);
+    /// census.run(0..5);
+    /// assert_eq!(census.pathological(), 5);
+    /// assert!(census.tally().is_empty());
+    /// ```
+    pub fn max_generations(mut self, generations: u128) -> Self {
+        self.max_generations = generations;
+        self
+    }
+
+    /// objects with longer periods are not recognized, the soups containing them count as
+    /// pathological
+    pub fn max_period(mut self, period: u64) -> Self {
+        self.max_period = period;
+        self
+    }
+
+    /// runs the soup of `seed` until it is periodic and splits it into its objects, `None` if it
+    /// doesn't settle within the generations
+    /// the census itself is not changed, so soups can be run on several threads
+    ///
+    /// ```
+    /// use lifeash::{census::Census, prelude::*};
+    ///
+    /// let census = Census::new(Rule::CONWAY);
+    /// let ash = census.run_soup(1).unwrap();
+    /// let mut universe = Universe::new();
+    /// universe.fill_random(Rect::new((0, 0), (15, 15)), 0.5, 1);
+    /// universe.advance(ash.generation);
+    /// let population: usize = ash.objects.iter().map(|object| object.cells.len()).sum();
+    /// assert_eq!(population as u128, universe.population());
+    /// ```
+    pub fn run_soup(&self, seed: u64) -> Option<Ash> {
+        let mut universe = Universe::builder().rule(self.rule).build();
+        let rect = Rect::new((0, 0), (self.width - 1, self.height - 1));
+        universe.fill_random(rect, self.density, seed);
+
+        // a period of the ash shows up as a period of the samples, which can be shorter
+        let mut samples = vec![universe.population()];
+        while universe.generation() < self.max_generations {
+            universe.advance(SAMPLE_INTERVAL);
+            samples.push(universe.population());
+            if population_period(&samples, self.max_period).is_none() {
+                continue;
+            }
+            if let Some((phase, objects)) = self.split(&universe) {
+                return Some(Ash {
+                    seed,
+                    generation: universe.generation() + phase,
+                    objects,
+                });
+            }
+        }
+        None
+    }
+
+    /// runs the soups of all seeds and adds their objects to the tally
+    pub fn run(&mut self, seeds: impl IntoIterator<Item = u64>) {
+        for seed in seeds {
+            let ash = self.run_soup(seed);
+            self.add(ash.as_ref());
+        }
+    }
+
+    /// counts the objects of a soup, `None` for a pathological one
+    ///
+    /// ```
+    /// use lifeash::{census::Census, prelude::*};
+    ///
+    /// let mut census = Census::new(Rule::CONWAY);
+    /// let ash = census.run_soup(7);
+    /// census.add(ash.as_ref());
+    /// census.add(None);
+    /// assert_eq!((census.soups(), census.pathological()), (2, 1));
+    /// ```
+    pub fn add(&mut self, ash: Option<&Ash>) {
+        self.soups += 1;
+        match ash {
+            Some(ash) => {
+                for object in &ash.objects {
+                    *self.tally.entry(object.apgcode.clone()).or_insert(0) += 1;
+                }
+            }
+            None => self.pathological += 1,
+        }
+    }
+
+    pub fn soups(&self) -> u64 {
+        self.soups
+    }
+
+    pub fn pathological(&self) -> u64 {
+        self.pathological
+    }
+
+    /// the apgcodes with how often they were found, the most common first
+    pub fn tally(&self) -> Vec<(&str, u64)> {
+        let mut tally: Vec<_> = self
+            .tally
+            .iter()
+            .map(|(apgcode, &count)| (apgcode.as_str(), count))
+            .collect();
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        tally
+    }
+
+    // the phase after the generation of `universe` and the objects of the ash if it is made of
+    // periodic objects which don't interact
+    fn split(&self, universe: &Universe) -> Option<(u128, Vec<Object>)> {
+        let mut copy = universe.compacted();
+        let mut finest: Option<(u128, Vec<Object>)> = None;
+        for phase in 0..SPLIT_PHASES {
+            let cells = sorted(copy.live_cells().collect());
+            let occupied: HashSet<_> = cells.iter().copied().collect();
+            // objects may also interact with cells which are one empty cell away
+            let objects = (1..=2).find_map(|reach| {
+                let objects = clusters(&cells, &occupied, reach)
+                    .into_iter()
+                    .map(|cells| {
+                        let pattern = Pattern {
+                            cells,
+                            ..Pattern::default()
+                        };
+                        identify(&pattern, self.rule, self.max_period)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(objects).filter(|objects| self.independent(objects, &copy))
+            });
+            match (objects, &finest) {
+                (Some(objects), Some((_, finest))) if objects.len() <= finest.len() => {}
+                (Some(objects), _) => finest = Some((phase, objects)),
+                (None, _) => {}
+            }
+            copy.evolve();
+        }
+        finest
+    }
+
+    // whether the objects run on their own give the same cells as the universe over all
+    // combinations of their phases
+    fn independent(&self, objects: &[Object], universe: &Universe) -> bool {
+        let periods = objects
+            .iter()
+            .fold(1, |periods, object| lcm(periods, object.period));
+        let generations = periods.min(MAX_CHECKED_PERIODS * self.max_period);
+
+        let mut universe = universe.compacted();
+        let mut parts: Vec<_> = objects
+            .iter()
+            .map(|object| {
+                let mut part = Universe::builder().rule(self.rule).build();
+                part.set_cells(object.cells.iter().map(|&pos| (pos, Cell::Alive)));
+                part
+            })
+            .collect();
+        (0..generations).all(|_| {
+            universe.evolve();
+            let mut cells = Vec::new();
+            for part in &mut parts {
+                part.evolve();
+                cells.extend(part.live_cells());
+            }
+            sorted(cells) == sorted(universe.live_cells().collect())
+        })
+    }
+}
+
+fn assert_two_states(rule: Rule) {
+    assert_eq!(rule.states(), 2, "apgcodes need a rule with two states");
+}
+
+// the smallest period the populations have repeated with at least `REPEATS` times at the end
+fn population_period(populations: &[u128], max_period: u64) -> Option<u64> {
+    let len = populations.len();
+    (1..=max_period).find(|&period| {
+        let (window, period) = ((period * REPEATS) as usize, period as usize);
+        window + period <= len
+            && (len - window..len).all(|i| populations[i] == populations[i - period])
+    })
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+// `cells` grouped into clusters, where cells within `reach` of each other are in the same one
+fn clusters(cells: &[Position], occupied: &HashSet<Position>, reach: i64) -> Vec<Vec<Position>> {
+    let mut seen = HashSet::new();
+    let mut clusters = Vec::new();
+    for &start in cells {
+        if seen.contains(&start) {
+            continue;
+        }
+        let cluster = cluster(start, occupied, reach);
+        seen.extend(cluster.iter().copied());
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+// the cells of `occupied` connected to `start` by steps of at most `reach`, sorted
+fn cluster(start: Position, occupied: &HashSet<Position>, reach: i64) -> Vec<Position> {
+    let mut cluster = HashSet::new();
+    cluster.insert(start);
+    let mut stack = vec![start];
+    while let Some(pos) = stack.pop() {
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let next = Position::new(pos.x + dx, pos.y + dy);
+                if occupied.contains(&next) && cluster.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+    }
+    sorted(cluster.into_iter().collect())
+}
+
+fn sorted(mut cells: Vec<Position>) -> Vec<Position> {
+    cells.sort_unstable();
+    cells
+}
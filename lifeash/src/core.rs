@@ -4,14 +4,64 @@ use std::{
     ops::{Add, AddAssign, Sub, SubAssign},
 };
 
-#[repr(u8)]
+// the state of a cell, numbered like in Golly: 0 is dead, 1 alive and 2 and above are the
+// dying states of Generations rules, which decay by one state per generation
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
-    Dead = 0u8,
-    Alive = 1u8,
+    Dead,
+    Alive,
+    Dying(u8),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+impl Cell {
+    /// the state number, 0 for dead cells and 1 for alive cells
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Cell::Alive.state(), 1);
+    /// assert_eq!(Cell::Dying(3).state(), 3);
+    /// ```
+    pub fn state(self) -> u8 {
+        match self {
+            Cell::Dead => 0,
+            Cell::Alive => 1,
+            Cell::Dying(state) => state,
+        }
+    }
+
+    /// the inverse of `state`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Cell::from_state(0), Cell::Dead);
+    /// assert_eq!(Cell::from_state(2), Cell::Dying(2));
+    /// ```
+    pub fn from_state(state: u8) -> Self {
+        match state {
+            0 => Cell::Dead,
+            1 => Cell::Alive,
+            state => Cell::Dying(state),
+        }
+    }
+}
+
+// dying cells are killed
+impl std::ops::Not for Cell {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Cell::Dead => Cell::Alive,
+            Cell::Alive | Cell::Dying(_) => Cell::Dead,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub x: i64,
     pub y: i64,
@@ -27,12 +77,21 @@ pub(crate) enum Quadrant {
 }
 
 // use enum instead with East, West, etc. variants?
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offset {
     pub dx: i64,
     pub dy: i64,
 }
 
+// axis aligned rectangle, both corners are inclusive
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub min: Position,
+    pub max: Position,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct Level(u8);
 
@@ -43,9 +102,16 @@ impl From<(i64, i64)> for Position {
 }
 
 impl Position {
-    #[allow(dead_code)]
     pub const ORIGIN: Self = Self::new(0, 0);
 
+    /// a cell position, y grows downwards
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let pos = Position::new(3, -2) + Offset::new(1, 1);
+    /// assert_eq!(pos, Position::new(4, -1));
+    /// ```
     pub const fn new(x: i64, y: i64) -> Self {
         Self { x, y }
     }
@@ -132,11 +198,130 @@ impl SubAssign<Offset> for Position {
 }
 
 impl Offset {
+    /// a relative movement between two positions
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut pos = Position::ORIGIN;
+    /// pos += Offset::new(2, 5);
+    /// assert_eq!(pos, Position::new(2, 5));
+    /// ```
     pub const fn new(dx: i64, dy: i64) -> Self {
         Self { dx, dy }
     }
 }
 
+impl Rect {
+    /// the corners can be given in any order
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let rect = Rect::new((2, 3), (-1, 0));
+    /// assert_eq!(rect.min, Position::new(-1, 0));
+    /// assert_eq!(rect.max, Position::new(2, 3));
+    /// ```
+    pub fn new(a: impl Into<Position>, b: impl Into<Position>) -> Self {
+        let (a, b) = (a.into(), b.into());
+        Self {
+            min: Position::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Position::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// number of columns, both corners included
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Rect::new((0, 0), (3, 1)).width(), 4);
+    /// ```
+    pub fn width(self) -> u64 {
+        (self.max.x - self.min.x) as u64 + 1
+    }
+
+    /// number of rows, both corners included
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Rect::new((0, 0), (3, 1)).height(), 2);
+    /// ```
+    pub fn height(self) -> u64 {
+        (self.max.y - self.min.y) as u64 + 1
+    }
+
+    /// whether `pos` lies inside the rectangle or on its border
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let rect = Rect::new((0, 0), (3, 3));
+    /// assert!(rect.contains((3, 0)));
+    /// assert!(!rect.contains((4, 0)));
+    /// ```
+    pub fn contains(self, pos: impl Into<Position>) -> bool {
+        let pos = pos.into();
+        (self.min.x..=self.max.x).contains(&pos.x) && (self.min.y..=self.max.y).contains(&pos.y)
+    }
+
+    /// whether the rectangles share at least one position
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let rect = Rect::new((0, 0), (3, 3));
+    /// assert!(rect.intersects(Rect::new((3, 3), (5, 5))));
+    /// assert!(!rect.intersects(Rect::new((4, 0), (5, 5))));
+    /// ```
+    pub fn intersects(self, other: Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// the overlapping part of both rectangles, if any
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let rect = Rect::new((0, 0), (3, 3));
+    /// assert_eq!(
+    ///     rect.intersection(Rect::new((2, -1), (5, 1))),
+    ///     Some(Rect::new((2, 0), (3, 1)))
+    /// );
+    /// assert_eq!(rect.intersection(Rect::new((4, 4), (5, 5))), None);
+    /// ```
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        if self.intersects(other) {
+            Some(Self {
+                min: Position::new(self.min.x.max(other.min.x), self.min.y.max(other.min.y)),
+                max: Position::new(self.max.x.min(other.max.x), self.max.y.min(other.max.y)),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// iterates row by row
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let positions: Vec<_> = Rect::new((0, 0), (1, 1)).positions().collect();
+    /// assert_eq!(
+    ///     positions,
+    ///     [(0, 0), (1, 0), (0, 1), (1, 1)].map(Position::from)
+    /// );
+    /// ```
+    pub fn positions(self) -> impl Iterator<Item = Position> {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Position::new(x, y)))
+    }
+}
+
 impl PartialEq<u8> for Level {
     fn eq(&self, n: &u8) -> bool {
         self.0 == *n
@@ -225,6 +410,10 @@ impl Level {
         Self(n)
     }
 
+    pub(crate) const fn value(self) -> u8 {
+        self.0
+    }
+
     pub(crate) const fn side_len(self) -> u64 {
         1 << self.0
     }
@@ -251,7 +440,6 @@ impl Level {
         self.min_coord()..self.max_coord()
     }
 
-    #[allow(dead_code)]
     pub(crate) fn min_pos(self) -> Position {
         let min = Self::min_coord(self);
         (min, min).into()
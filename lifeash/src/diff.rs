@@ -0,0 +1,43 @@
+use crate::{
+    boolean::SetOp,
+    core::{Position, Rect},
+    universe::Universe,
+};
+
+impl Universe {
+    /// a universe with a live cell wherever the cells of the two universes differ, with the rule
+    /// of this one
+    /// subtrees with the same cells are the same node and are skipped as a whole
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut blinker = Universe::from_rle("3o!").unwrap();
+    /// let before = blinker.compacted();
+    /// blinker.evolve();
+    /// let xor = blinker.xor(&before);
+    /// // the ends died, two cells were born next to the middle
+    /// assert_eq!(xor.population(), 4);
+    /// assert_eq!(blinker.xor(&blinker).population(), 0);
+    /// ```
+    pub fn xor(&self, other: &Universe) -> Universe {
+        self.combined(other, SetOp::Xor)
+    }
+
+    /// the positions within `rect` whose cells differ between the two universes, in no
+    /// particular order
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let mut moved = glider.compacted();
+    /// moved.step(4);
+    /// let mut cells = moved.diff_cells(&glider, Rect::new((0, 0), (2, 0)));
+    /// cells.sort();
+    /// assert_eq!(cells, [Position::new(1, 0)]);
+    /// ```
+    pub fn diff_cells(&self, other: &Universe, rect: Rect) -> Vec<Position> {
+        self.xor(other).live_cells_in(rect).collect()
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use crate::{mask::MaskOp, universe::Universe};
+
+impl Universe {
+    /// every cell which is alive in the current state or any of the next `generations` states
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let blinker = Universe::from_rle("3o!").unwrap();
+    /// let envelope = blinker.envelope(1);
+    /// // the horizontal and the vertical phase
+    /// assert_eq!(envelope.population(), 5);
+    /// ```
+    pub fn envelope(&self, generations: u64) -> Universe {
+        let mut work = Universe::builder()
+            .rule(self.rule())
+            .backend(self.backend())
+            .build();
+        let root = work.import_tree(self, self.root(), &mut HashMap::new());
+        work.set_root(root);
+
+        let mut envelope = Universe::builder()
+            .rule(self.rule())
+            .backend(self.backend())
+            .build();
+        envelope.apply_mask(&work, MaskOp::FillInside);
+        for _ in 0..generations {
+            work.evolve();
+            envelope.apply_mask(&work, MaskOp::FillInside);
+        }
+        envelope
+    }
+}
@@ -0,0 +1,82 @@
+// human readable numbers for counts that easily outgrow what anyone wants to read digit by digit
+
+const SI_PREFIXES: [&str; 11] = ["", "k", "M", "G", "T", "P", "E", "Z", "Y", "R", "Q"];
+
+/// 1234567 as 1,234,567
+///
+/// ```
+/// use lifeash::format;
+///
+/// assert_eq!(format::thousands(1234567), "1,234,567");
+/// assert_eq!(format::thousands(999), "999");
+/// ```
+pub fn thousands(n: u128) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// small numbers are written out, larger ones like 3.2×10^21 with an exponent divisible by three
+///
+/// ```
+/// use lifeash::format;
+///
+/// assert_eq!(format::engineering(999_999), "999999");
+/// assert_eq!(format::engineering(3_200_000_000_000_000_000_000), "3.2×10^21");
+/// ```
+pub fn engineering(n: u128) -> String {
+    if n < 1_000_000 {
+        return n.to_string();
+    }
+    let (mantissa, exp) = mantissa(n);
+    format!("{}×10^{}", mantissa, exp)
+}
+
+/// 3.2M, falls back to engineering notation beyond the largest prefix
+///
+/// ```
+/// use lifeash::format;
+///
+/// assert_eq!(format::si(3_250_000), "3.2M");
+/// assert_eq!(format::si(u128::MAX), format::engineering(u128::MAX));
+/// ```
+pub fn si(n: u128) -> String {
+    if n < 1000 {
+        return n.to_string();
+    }
+    let (mantissa, exp) = mantissa(n);
+    match SI_PREFIXES.get(exp / 3) {
+        Some(prefix) => format!("{}{}", mantissa, prefix),
+        None => engineering(n),
+    }
+}
+
+/// only the order of magnitude, like ≈10^21
+///
+/// ```
+/// use lifeash::format;
+///
+/// assert_eq!(format::approximate(3_200_000_000_000_000_000_000), "≈10^21");
+/// assert_eq!(format::approximate(42), "42");
+/// ```
+pub fn approximate(n: u128) -> String {
+    if n < 1000 {
+        return n.to_string();
+    }
+    format!("≈10^{}", n.to_string().len() - 1)
+}
+
+// truncated mantissa with one decimal and an exponent divisible by three, for n >= 1000
+fn mantissa(n: u128) -> (String, usize) {
+    let digits = n.to_string();
+    let exp = (digits.len() - 1) / 3 * 3;
+    let int_len = digits.len() - exp;
+    let mantissa = format!("{}.{}", &digits[..int_len], &digits[int_len..=int_len]);
+    (mantissa, exp)
+}
@@ -0,0 +1,106 @@
+use crate::{
+    core::{Offset, Position, Rect},
+    node::Node,
+    universe::{Id, Universe},
+};
+
+impl Universe {
+    /// keeps the cells of `rect` as they are while the rest evolves, they still count as
+    /// neighbors of the cells around them
+    /// while anything is frozen the universe only advances one generation at a time, so a step
+    /// costs as much as its generations one by one, and `advance` panics on more than
+    /// `Universe::MAX_FROZEN_ADVANCE` generations at once
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// // the west end of the blinker is held in place
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// universe.freeze(Rect::new((0, 0), (0, 0)));
+    /// universe.evolve();
+    /// assert_eq!(universe.get_cell((0, 0)), Cell::Alive);
+    /// assert_eq!(universe.get_cell((1, -1)), Cell::Alive);
+    /// assert_eq!(universe.population(), 4);
+    /// ```
+    pub fn freeze(&mut self, rect: Rect) {
+        if !self.frozen().contains(&rect) {
+            self.frozen_mut().push(rect);
+        }
+    }
+
+    /// lets a region frozen with `freeze` evolve again, returns false if it wasn't frozen
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("o!").unwrap();
+    /// let rect = Rect::new((0, 0), (0, 0));
+    /// universe.freeze(rect);
+    /// universe.evolve();
+    /// assert_eq!(universe.population(), 1);
+    /// assert!(universe.thaw(rect));
+    /// assert!(!universe.thaw(rect));
+    /// universe.evolve();
+    /// assert_eq!(universe.population(), 0);
+    /// ```
+    pub fn thaw(&mut self, rect: Rect) -> bool {
+        let frozen = self.frozen_mut();
+        let len = frozen.len();
+        frozen.retain(|&frozen| frozen != rect);
+        frozen.len() != len
+    }
+
+    // the frozen regions have to be within the center of the root, which is what a step returns
+    pub(crate) fn expand_to_frozen(&mut self) {
+        let corners: Vec<Position> = self
+            .frozen()
+            .iter()
+            .flat_map(|rect| vec![rect.min, rect.max])
+            .collect();
+        for corner in corners {
+            while !corner.in_bounds(self.root().node(self).level() - 1) {
+                self.expand();
+            }
+        }
+    }
+
+    // puts the frozen cells of the root `before` a step back into the evolved root
+    pub(crate) fn restore_frozen(&mut self, before: Id) {
+        if self.frozen().is_empty() {
+            return;
+        }
+        // the step returned the center of the old root, surrounding it with an empty border
+        // aligns both trees again
+        let mut root = self.expand_tree(self.root());
+        let min = root.node(self).level().min_pos();
+        for rect in self.frozen().to_vec() {
+            root = self.splice_tree(root, before, min, rect);
+        }
+        self.set_root(root);
+    }
+
+    // `tree` with the cells of `rect` taken from `frozen`, both of the same level at `min`
+    fn splice_tree(&mut self, tree: Id, frozen: Id, min: Position, rect: Rect) -> Id {
+        let side = tree.node(self).level().side_len() as i64;
+        let area = Rect::new(min, min + Offset::new(side - 1, side - 1));
+        if !area.intersects(rect) || tree == frozen {
+            return tree;
+        }
+        if rect.contains(area.min) && rect.contains(area.max) {
+            return frozen;
+        }
+
+        match (tree.node(self).clone(), frozen.node(self).clone()) {
+            (Node::Inode(t), Node::Inode(f)) => {
+                let half = side / 2;
+                let nw = self.splice_tree(t.nw, f.nw, min, rect);
+                let ne = self.splice_tree(t.ne, f.ne, min + Offset::new(half, 0), rect);
+                let sw = self.splice_tree(t.sw, f.sw, min + Offset::new(0, half), rect);
+                let se = self.splice_tree(t.se, f.se, min + Offset::new(half, half), rect);
+                self.new_inode(nw, ne, sw, se)
+            }
+            // single cells are either inside or outside of the rectangle
+            _ => unreachable!("trees of different levels"),
+        }
+    }
+}
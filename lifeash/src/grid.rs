@@ -0,0 +1,162 @@
+// dense copies of a window of the universe, for code that wants plain arrays instead of trees,
+// e.g. image writers or an fft
+
+use crate::{
+    core::{Cell, Position, Rect},
+    universe::Universe,
+};
+
+const WORD_BITS: u64 = u64::BITS as u64;
+
+// one bit per cell, set for alive cells, row by row
+// every row starts at a new word and bit `x % 64` of word `x / 64` is column `x`, so a row
+// unpacks with the least significant bit first
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: u64,
+    height: u64,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// a grid with all bits cleared
+    ///
+    /// ```
+    /// use lifeash::BitGrid;
+    ///
+    /// let grid = BitGrid::new(100, 2);
+    /// assert_eq!(grid.words_per_row(), 2);
+    /// assert_eq!(grid.as_words(), [0; 4]);
+    /// ```
+    pub fn new(width: u64, height: u64) -> Self {
+        let words = width.div_ceil(WORD_BITS) * height;
+        Self {
+            width,
+            height,
+            words: vec![0; words as usize],
+        }
+    }
+
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn words_per_row(&self) -> u64 {
+        self.width.div_ceil(WORD_BITS)
+    }
+
+    /// whether the cell in column `x` and row `y` is alive, counted from the top left corner
+    ///
+    /// ```
+    /// use lifeash::BitGrid;
+    ///
+    /// let mut grid = BitGrid::new(3, 3);
+    /// grid.set(2, 1, true);
+    /// assert!(grid.get(2, 1));
+    /// assert!(!grid.get(1, 2));
+    /// ```
+    pub fn get(&self, x: u64, y: u64) -> bool {
+        let (word, bit) = self.index(x, y);
+        self.words[word] & bit != 0
+    }
+
+    pub fn set(&mut self, x: u64, y: u64, alive: bool) {
+        let (word, bit) = self.index(x, y);
+        if alive {
+            self.words[word] |= bit;
+        } else {
+            self.words[word] &= !bit;
+        }
+    }
+
+    /// the words of row `y`, the bits past the width are always cleared
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("obo$3o!").unwrap();
+    /// let grid = universe.to_bit_grid(Rect::new((0, 0), (2, 1)));
+    /// assert_eq!(grid.row(0), [0b101]);
+    /// assert_eq!(grid.row(1), [0b111]);
+    /// ```
+    pub fn row(&self, y: u64) -> &[u64] {
+        assert!(y < self.height, "row {} is outside of the grid", y);
+        let len = self.words_per_row() as usize;
+        let start = y as usize * len;
+        &self.words[start..start + len]
+    }
+
+    /// all rows one after another
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// number of set bits
+    pub fn count_ones(&self) -> u64 {
+        self.words
+            .iter()
+            .map(|word| u64::from(word.count_ones()))
+            .sum()
+    }
+
+    fn index(&self, x: u64, y: u64) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside of the grid",
+            x,
+            y
+        );
+        let word = y * self.words_per_row() + x / WORD_BITS;
+        (word as usize, 1 << (x % WORD_BITS))
+    }
+}
+
+impl Universe {
+    /// the cells within `rect` as rows from top to bottom, indexed by `[y - min.y][x - min.x]`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("bo$2o!").unwrap();
+    /// assert_eq!(
+    ///     universe.to_grid(Rect::new((0, 0), (1, 1))),
+    ///     [[Cell::Dead, Cell::Alive], [Cell::Alive, Cell::Alive]]
+    /// );
+    /// ```
+    pub fn to_grid(&self, rect: Rect) -> Vec<Vec<Cell>> {
+        let mut grid = vec![vec![Cell::Dead; rect.width() as usize]; rect.height() as usize];
+        for (pos, cell) in self.cells_in(rect) {
+            let (x, y) = offset(rect, pos);
+            grid[y as usize][x as usize] = cell;
+        }
+        grid
+    }
+
+    /// like `to_grid`, packed into one bit per cell, dying cells count as dead
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let grid = universe.to_bit_grid(Rect::new((-10, -10), (99, 9)));
+    /// assert_eq!((grid.width(), grid.height()), (110, 20));
+    /// assert_eq!(grid.count_ones(), 5);
+    /// assert!(grid.get(11, 10));
+    /// ```
+    pub fn to_bit_grid(&self, rect: Rect) -> BitGrid {
+        let mut grid = BitGrid::new(rect.width(), rect.height());
+        for pos in self.live_cells_in(rect) {
+            let (x, y) = offset(rect, pos);
+            grid.set(x, y, true);
+        }
+        grid
+    }
+}
+
+fn offset(rect: Rect, pos: Position) -> (u64, u64) {
+    ((pos.x - rect.min.x) as u64, (pos.y - rect.min.y) as u64)
+}
@@ -0,0 +1,224 @@
+// the table of canonical nodes compares whole nodes, so a hash collision only costs time and can't
+// merge two different nodes, the `verify-hashing` feature checks that this holds
+// every node is also filed under its 64-bit hash, nodes sharing a hash are compared in full, and a
+// node equal to another one means the table failed to find it
+
+#[cfg(feature = "formats-extra")]
+use std::collections::HashMap;
+#[cfg(feature = "verify-hashing")]
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+#[cfg(feature = "formats-extra")]
+use crate::{
+    core::{Cell, Level},
+    universe::{mix, Universe, HIGH_SEED},
+};
+use crate::{node::Node, universe::Id};
+
+#[cfg(feature = "verify-hashing")]
+#[derive(Debug, Default)]
+pub(crate) struct CollisionCheck {
+    by_hash: HashMap<u64, Vec<Id>>,
+}
+
+#[cfg(feature = "verify-hashing")]
+impl CollisionCheck {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn hash(node: &Node) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        node.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // the number of stored nodes with the hash of the new one
+    pub(crate) fn insert(&mut self, nodes: &[Node], node: &Node, id: Id) -> u64 {
+        let ids = self.by_hash.entry(Self::hash(node)).or_default();
+        for &other in ids.iter() {
+            assert!(
+                nodes[other.index()] != *node,
+                "{:?} is stored twice, as {:?} and {:?}",
+                node,
+                other,
+                id
+            );
+        }
+        ids.push(id);
+        ids.len() as u64 - 1
+    }
+
+    // the table found `id` for `node`
+    pub(crate) fn check_hit(&self, nodes: &[Node], node: &Node, id: Id) {
+        assert!(
+            nodes[id.index()] == *node,
+            "{:?} was found as {:?}, which is {:?}",
+            node,
+            id,
+            nodes[id.index()]
+        );
+    }
+
+    pub(crate) fn remove(&mut self, node: &Node, id: Id) {
+        let hash = Self::hash(node);
+        if let Some(ids) = self.by_hash.get_mut(&hash) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.by_hash.remove(&hash);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "verify-hashing"))]
+#[derive(Debug)]
+pub(crate) struct CollisionCheck;
+
+#[cfg(not(feature = "verify-hashing"))]
+impl CollisionCheck {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub(crate) fn insert(&mut self, _nodes: &[Node], _node: &Node, _id: Id) -> u64 {
+        0
+    }
+
+    #[inline(always)]
+    pub(crate) fn check_hit(&self, _nodes: &[Node], _node: &Node, _id: Id) {}
+
+    #[inline(always)]
+    pub(crate) fn remove(&mut self, _node: &Node, _id: Id) {}
+}
+
+// a 128-bit hash of what a save file holds, stable across builds and platforms unlike the hashers
+// of std, so that files can be verified when they are loaded again
+// trees are hashed by their structure, in time proportional to their nodes rather than their cells
+#[cfg(feature = "formats-extra")]
+pub(crate) struct Digest {
+    low: u64,
+    high: u64,
+}
+
+#[cfg(feature = "formats-extra")]
+impl Digest {
+    pub(crate) fn new() -> Self {
+        Self {
+            low: 0,
+            high: HIGH_SEED,
+        }
+    }
+
+    pub(crate) fn write_u128(&mut self, n: u128) {
+        for word in [n as u64, (n >> 64) as u64] {
+            self.low = mix(self.low ^ word);
+            self.high = mix(self.high ^ word);
+        }
+    }
+
+    pub(crate) fn write_str(&mut self, s: &str) {
+        self.write_u128(s.len() as u128);
+        for chunk in s.as_bytes().chunks(16) {
+            let mut bytes = [0; 16];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            self.write_u128(u128::from_le_bytes(bytes));
+        }
+    }
+
+    // the tree is shrunk to the smallest level which holds all of its cells first, so that it
+    // hashes the same however far it was expanded
+    // `two_state` hashes dying cells as dead, for formats which don't keep them
+    pub(crate) fn write_tree(&mut self, universe: &Universe, root: Id, two_state: bool) {
+        let mut trees = TreeHashes {
+            universe,
+            two_state,
+            hashes: HashMap::new(),
+            empty: Vec::new(),
+        };
+        let inode = root.inode(universe);
+        let (mut level, mut children) = (inode.level, [inode.nw, inode.ne, inode.sw, inode.se]);
+        while level > 3 {
+            let [nw, ne, sw, se] = children.map(|child| child.inode(universe));
+            let border = [
+                nw.nw, nw.ne, nw.sw, ne.nw, ne.ne, ne.se, sw.nw, sw.sw, sw.se, se.nw, se.sw, se.se,
+            ];
+            let empty = trees.empty(level - 2);
+            if border.iter().any(|&tree| trees.hash(tree) != empty) {
+                break;
+            }
+            children = [nw.se, ne.sw, sw.ne, se.nw];
+            level -= 1;
+        }
+        self.write_u128(trees.inode(level, children));
+    }
+
+    pub(crate) fn finish(&self) -> u128 {
+        u128::from(self.high) << 64 | u128::from(self.low)
+    }
+}
+
+#[cfg(feature = "formats-extra")]
+struct TreeHashes<'a> {
+    universe: &'a Universe,
+    two_state: bool,
+    hashes: HashMap<Id, u128>,
+    // the hashes of the empty trees by level
+    empty: Vec<u128>,
+}
+
+#[cfg(feature = "formats-extra")]
+impl TreeHashes<'_> {
+    fn hash(&mut self, tree: Id) -> u128 {
+        if let Some(&hash) = self.hashes.get(&tree) {
+            return hash;
+        }
+        let hash = match tree.node(self.universe) {
+            Node::Leaf(leaf) => match leaf.0 {
+                Cell::Dying(_) if self.two_state => Self::leaf(Cell::Dead),
+                cell => Self::leaf(cell),
+            },
+            Node::Inode(inode) => {
+                let children = [inode.nw, inode.ne, inode.sw, inode.se];
+                self.inode(inode.level, children)
+            }
+        };
+        self.hashes.insert(tree, hash);
+        hash
+    }
+
+    fn leaf(cell: Cell) -> u128 {
+        let mut digest = Digest::new();
+        digest.write_u128(u128::from(cell.state()));
+        digest.finish()
+    }
+
+    fn inode(&mut self, level: Level, children: [Id; 4]) -> u128 {
+        let children = children.map(|child| self.hash(child));
+        Self::combine(level, children)
+    }
+
+    fn combine(level: Level, children: [u128; 4]) -> u128 {
+        let mut digest = Digest::new();
+        digest.write_u128(u128::from(level.value()));
+        for child in children {
+            digest.write_u128(child);
+        }
+        digest.finish()
+    }
+
+    fn empty(&mut self, level: Level) -> u128 {
+        while self.empty.len() <= level.value() as usize {
+            let hash = match self.empty.last() {
+                Some(&child) => Self::combine(Level::new(self.empty.len() as u8), [child; 4]),
+                None => Self::leaf(Cell::Dead),
+            };
+            self.empty.push(hash);
+        }
+        self.empty[level.value() as usize]
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+
+use crate::universe::{Snapshot, Universe};
+
+// bounded undo and redo stacks of the states before edits, cheap because the trees are shared
+// evolving the universe is not an edit, undoing goes back to the state before the last edit
+#[derive(Debug, Default)]
+pub struct History {
+    depth: usize,
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    pub(crate) fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            ..Self::default()
+        }
+    }
+
+    /// how many edits can be undone at most, 0 disables the history
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Universe::new().history().depth(), 0);
+    /// assert_eq!(Universe::builder().history_depth(10).build().history().depth(), 10);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// whether there is an edit to undo
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().history_depth(10).build();
+    /// assert!(!universe.history().can_undo());
+    /// universe.set_cell((0, 0), Cell::Alive);
+    /// assert!(universe.history().can_undo());
+    /// ```
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// whether there is an undone edit to redo
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().history_depth(10).build();
+    /// universe.set_cell((0, 0), Cell::Alive);
+    /// universe.undo();
+    /// assert!(universe.history().can_redo());
+    /// ```
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+impl Universe {
+    // called by every edit before it changes the tree
+    pub(crate) fn record_edit(&mut self) {
+        if self.history().depth == 0 {
+            return;
+        }
+        self.with_history(|universe, history| {
+            history.undo.push_back(universe.snapshot());
+            while history.undo.len() > history.depth {
+                universe.release(history.undo.pop_front().unwrap());
+            }
+            for snapshot in history.redo.drain(..) {
+                universe.release(snapshot);
+            }
+        });
+    }
+
+    /// goes back to the state before the last edit, returns false if there is none
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().history_depth(10).build();
+    /// universe.set_cell((0, 0), Cell::Alive);
+    /// universe.set_cell((1, 0), Cell::Alive);
+    /// assert!(universe.undo());
+    /// assert_eq!(universe.population(), 1);
+    /// assert!(universe.undo());
+    /// assert!(!universe.undo());
+    /// assert_eq!(universe.population(), 0);
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        self.with_history(|universe, history| match history.undo.pop_back() {
+            Some(snapshot) => {
+                history.redo.push(universe.snapshot());
+                universe.restore(&snapshot);
+                universe.release(snapshot);
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// reapplies the last undone edit, returns false if there is none
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().history_depth(10).build();
+    /// universe.set_cell((0, 0), Cell::Alive);
+    /// universe.undo();
+    /// assert!(universe.redo());
+    /// assert_eq!(universe.population(), 1);
+    /// // a new edit discards what could be redone
+    /// universe.undo();
+    /// universe.set_cell((5, 5), Cell::Alive);
+    /// assert!(!universe.redo());
+    /// ```
+    pub fn redo(&mut self) -> bool {
+        self.with_history(|universe, history| match history.redo.pop() {
+            Some(snapshot) => {
+                history.undo.push_back(universe.snapshot());
+                universe.restore(&snapshot);
+                universe.release(snapshot);
+                true
+            }
+            None => false,
+        })
+    }
+}
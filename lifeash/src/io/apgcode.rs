@@ -0,0 +1,107 @@
+// apgcodes as used by apgsearch and Catagolue, see https://www.conwaylife.com/wiki/Apgcode
+// the cells are written in the extended Wechsler format, prefixed by the kind of object:
+// "xs<population>_" for still lifes, "xp<period>_" for oscillators, "xq<period>_" for spaceships
+
+use super::ParseError;
+use crate::{
+    census::Kind,
+    core::Position,
+    pattern::Pattern,
+    universe::Universe,
+    wechsler::{self, normalized, STRIP_HEIGHT},
+};
+
+// patterns which don't repeat within this many generations have no apgcode
+const MAX_PERIOD: u32 = 1024;
+
+impl Pattern {
+    /// the canonical code is the shortest, then alphabetically first, over all phases and orientations
+    ///
+    /// ```
+    /// use lifeash::{io::rle, Pattern};
+    ///
+    /// let glider: Pattern = rle::parse("bo$2bo$3o!").unwrap().into();
+    /// assert_eq!(glider.to_apgcode().as_deref(), Some("xq4_153"));
+    /// ```
+    pub fn to_apgcode(&self) -> Option<String> {
+        let mut universe = Universe::builder()
+            .rule(self.rule.unwrap_or_default())
+            .build();
+        universe.stamp(self, Position::ORIGIN);
+        let start = normalized(universe.live_cells().collect())?;
+
+        let mut phases = vec![start.clone()];
+        let (period, moved) = loop {
+            universe.evolve();
+            let (phase, min) = normalized(universe.live_cells().collect())?;
+            if phase == start.0 {
+                break (phases.len(), min != start.1);
+            }
+            if phases.len() as u32 >= MAX_PERIOD {
+                return None;
+            }
+            phases.push((phase, min));
+        };
+
+        let kind = match (period, moved) {
+            (1, false) => Kind::StillLife,
+            (_, false) => Kind::Oscillator,
+            (_, true) => Kind::Ship,
+        };
+        let phases: Vec<_> = phases.into_iter().map(|(cells, _)| cells).collect();
+        Some(wechsler::apgcode(kind, period as u64, &phases))
+    }
+
+    /// the cells of the code, without checking that it is canonical
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let block = Pattern::from_apgcode("xs4_33").unwrap();
+    /// assert_eq!(block.cells.len(), 4);
+    /// assert_eq!(block.to_apgcode().as_deref(), Some("xs4_33"));
+    /// ```
+    pub fn from_apgcode(code: &str) -> Result<Pattern, ParseError> {
+        let invalid = || ParseError::InvalidApgcode(code.to_owned());
+        let (prefix, cells) = code.split_once('_').ok_or_else(invalid)?;
+        let count = prefix
+            .strip_prefix("xs")
+            .or_else(|| prefix.strip_prefix("xp"))
+            .or_else(|| prefix.strip_prefix("xq"))
+            .ok_or_else(invalid)?;
+        count.parse::<u32>().map_err(|_| invalid())?;
+
+        let mut pattern = Pattern::default();
+        let (mut x, mut strip) = (0i64, 0i64);
+        let mut chars = cells.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                'z' => {
+                    x = 0;
+                    strip += 1;
+                }
+                'w' => x += 2,
+                'x' => x += 3,
+                'y' => {
+                    let run = chars
+                        .next()
+                        .and_then(|c| c.to_digit(36))
+                        .ok_or_else(invalid)?;
+                    x += 4 + i64::from(run);
+                }
+                _ => {
+                    let column = c.to_digit(32).ok_or_else(invalid)?;
+                    for bit in 0..STRIP_HEIGHT {
+                        if column & 1 << bit != 0 {
+                            pattern
+                                .cells
+                                .push(Position::new(x, strip * STRIP_HEIGHT + bit));
+                        }
+                    }
+                    x += 1;
+                }
+            }
+        }
+        Ok(pattern)
+    }
+}
@@ -0,0 +1,462 @@
+// a compact binary encoding of the whole universe, to stop a long run and resume it exactly
+//
+// "LASH", version, rule, backend, generation, frozen regions, the nodes with the root last and a
+// hash of the content, checked when the checkpoint is read
+// every node is written once after its children, and with the cache after its result too,
+// they are referred to by the distance back to their index, which is mostly small
+// integers are LEB128 varints, signed ones zigzag encoded, strings are prefixed with their length
+//
+// checkpoints of every earlier version are still read, `migrate_tag` maps their nodes:
+// 1: the first version
+// 2: 4x4 blocks without dying cells are written as a 16-bit mask instead of their 21 nodes, and
+//    the cache is a flag of the node tag
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    io::{self, BufReader, BufWriter, Read, Write},
+};
+
+use super::ParseError;
+use crate::{
+    core::{Cell, Level, Position, Rect},
+    hashing::Digest,
+    ltl::Backend,
+    node::{Inode, Leaf, Node},
+    rule::Rule,
+    universe::{Id, Universe},
+};
+
+const MAGIC: &[u8; 4] = b"LASH";
+const VERSION: u8 = 2;
+const OLDEST_VERSION: u8 = 1;
+
+const LEAF: u8 = 0;
+const INODE: u8 = 1;
+// a level 2 node as a 4x4 mask of alive cells, row major with the north west cell in bit 15
+const BLOCK: u8 = 3;
+// set on inodes and blocks which are followed by the step and the distance of their cached result
+const CACHED: u8 = 0x80;
+// the tag of cached inodes in version 1
+const CACHED_INODE_V1: u8 = 2;
+
+const LIFE: u8 = 0;
+const LARGER_THAN_LIFE: u8 = 1;
+
+// rulestrings are short, anything longer is garbage
+const MAX_STRING_LEN: u128 = 256;
+
+impl Universe {
+    /// writes the tree, generation, rule, backend and frozen regions, `deserialize` reads them
+    /// back into a universe which evolves exactly like this one
+    /// settings like the kernel and the garbage collection threshold aren't part of the state,
+    /// neither are the history, the timeline and snapshots
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(1000);
+    /// let mut checkpoint = Vec::new();
+    /// universe.serialize(&mut checkpoint).unwrap();
+    ///
+    /// let mut resumed = Universe::deserialize(&checkpoint[..]).unwrap();
+    /// assert_eq!(resumed.generation(), 1000);
+    /// resumed.advance(1000);
+    /// universe.advance(1000);
+    /// assert_eq!(resumed.content_hash(), universe.content_hash());
+    /// ```
+    pub fn serialize(&self, writer: impl Write) -> io::Result<()> {
+        self.write_checkpoint(writer, false)
+    }
+
+    /// like `serialize`, but also writes the cached results and the trees they lead to, which
+    /// saves recomputing them after a restart at the cost of a larger checkpoint
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(1000);
+    /// let (mut with_cache, mut without) = (Vec::new(), Vec::new());
+    /// universe.serialize_with_cache(&mut with_cache).unwrap();
+    /// universe.serialize(&mut without).unwrap();
+    /// assert!(with_cache.len() > without.len());
+    ///
+    /// let mut resumed = Universe::deserialize(&with_cache[..]).unwrap();
+    /// resumed.advance(1000);
+    /// assert!(resumed.stats().cache_hits > 0);
+    /// ```
+    pub fn serialize_with_cache(&self, writer: impl Write) -> io::Result<()> {
+        self.write_checkpoint(writer, true)
+    }
+
+    /// reads a universe written by `serialize` or `serialize_with_cache`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("x = 3, y = 1, rule = B36/S23\n3o!").unwrap();
+    /// universe.freeze(Rect::new((0, 0), (0, 0)));
+    /// let mut checkpoint = Vec::new();
+    /// universe.serialize(&mut checkpoint).unwrap();
+    ///
+    /// let resumed = Universe::deserialize(&checkpoint[..]).unwrap();
+    /// assert_eq!(resumed.rule(), universe.rule());
+    /// assert_eq!(resumed.frozen(), universe.frozen());
+    /// assert!(Universe::deserialize(&b"x = 3, y = 1"[..]).is_err());
+    ///
+    /// // the last bytes are the hash of the content
+    /// let last = checkpoint.len() - 1;
+    /// checkpoint[last] ^= 1;
+    /// assert!(Universe::deserialize(&checkpoint[..]).is_err());
+    /// ```
+    pub fn deserialize(reader: impl Read) -> Result<Self, ParseError> {
+        let mut reader = BufReader::new(reader);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ParseError::InvalidHeader(
+                String::from_utf8_lossy(&magic).into_owned(),
+            ));
+        }
+        let version = read_byte(&mut reader)?;
+        if !(OLDEST_VERSION..=VERSION).contains(&version) {
+            return Err(invalid(format!("unsupported version {}", version)));
+        }
+
+        let rule: Rule = read_string(&mut reader)?.parse()?;
+        let backend = match read_byte(&mut reader)? {
+            LIFE => Backend::Life,
+            LARGER_THAN_LIFE => Backend::LargerThanLife(read_string(&mut reader)?.parse()?),
+            tag => return Err(invalid(format!("unknown backend {}", tag))),
+        };
+        let states = match backend {
+            Backend::Life => rule.states(),
+            Backend::LargerThanLife(rule) => rule.states(),
+        };
+        let mut universe = Universe::builder().rule(rule).backend(backend).build();
+        universe.generation = read_varint(&mut reader)?;
+        for _ in 0..read_varint(&mut reader)? {
+            let mut corner = || -> Result<Position, ParseError> {
+                Ok(Position::new(
+                    read_signed(&mut reader)?,
+                    read_signed(&mut reader)?,
+                ))
+            };
+            let (min, max) = (corner()?, corner()?);
+            universe.freeze(Rect::new(min, max));
+        }
+
+        let count = usize::try_from(read_varint(&mut reader)?)
+            .map_err(|_| invalid("too many nodes".to_owned()))?;
+        // the count isn't trusted with more memory than the input could describe
+        universe.reserve(count.min(1 << 20));
+        let mut nodes = Vec::with_capacity(count.min(1 << 20));
+        for _ in 0..count {
+            let id = universe.read_node(&mut reader, version, &nodes, states)?;
+            nodes.push(id);
+        }
+        let root = *nodes.last().ok_or(ParseError::MissingRoot)?;
+        if root.node(&universe).level() < 3 {
+            return Err(invalid("the root is below level 3".to_owned()));
+        }
+        universe.set_root(root);
+
+        let mut hash = [0; 16];
+        reader.read_exact(&mut hash)?;
+        let (expected, found) = (u128::from_le_bytes(hash), universe.checkpoint_hash());
+        if found != expected {
+            return Err(ParseError::HashMismatch { expected, found });
+        }
+        Ok(universe)
+    }
+
+    fn checkpoint_hash(&self) -> u128 {
+        let mut digest = Digest::new();
+        digest.write_str(&self.rule().to_string());
+        if let Backend::LargerThanLife(rule) = self.backend() {
+            digest.write_str(&rule.to_string());
+        }
+        digest.write_u128(self.generation());
+        for rect in self.frozen() {
+            for n in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+                digest.write_u128(n as u128);
+            }
+        }
+        digest.write_tree(self, self.root(), false);
+        digest.finish()
+    }
+
+    fn write_checkpoint(&self, writer: impl Write, cache: bool) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_string(&mut writer, &self.rule().to_string())?;
+        match self.backend() {
+            Backend::Life => writer.write_all(&[LIFE])?,
+            Backend::LargerThanLife(rule) => {
+                writer.write_all(&[LARGER_THAN_LIFE])?;
+                write_string(&mut writer, &rule.to_string())?;
+            }
+        }
+        write_varint(&mut writer, self.generation())?;
+        write_varint(&mut writer, self.frozen().len() as u128)?;
+        for rect in self.frozen() {
+            for n in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+                write_signed(&mut writer, n)?;
+            }
+        }
+
+        let order = self.checkpoint_order(cache);
+        let indices: HashMap<Id, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        write_varint(&mut writer, order.len() as u128)?;
+        for (index, &id) in order.iter().enumerate() {
+            let distance = |child: Id| (index - indices[&child]) as u128;
+            let inode = match id.node(self) {
+                Node::Leaf(leaf) => {
+                    writer.write_all(&[LEAF, leaf.0.state()])?;
+                    continue;
+                }
+                Node::Inode(inode) => inode,
+            };
+            let result = self.written_result(inode).filter(|_| cache);
+            let flag = if result.is_some() { CACHED } else { 0 };
+            match self.block_bits(id) {
+                Some(bits) => {
+                    writer.write_all(&[BLOCK | flag])?;
+                    writer.write_all(&bits.to_le_bytes())?;
+                }
+                None => {
+                    writer.write_all(&[INODE | flag])?;
+                    for child in [inode.nw, inode.ne, inode.sw, inode.se] {
+                        write_varint(&mut writer, distance(child))?;
+                    }
+                }
+            }
+            if let Some((step_log, result)) = result {
+                writer.write_all(&[step_log])?;
+                write_varint(&mut writer, distance(result))?;
+            }
+        }
+        writer.write_all(&self.checkpoint_hash().to_le_bytes())?;
+        writer.flush()
+    }
+
+    // the nodes to write, every one after those it refers to, without the nodes below blocks
+    // unless something else refers to them
+    fn checkpoint_order(&self, cache: bool) -> Vec<Id> {
+        let mut indices = HashMap::new();
+        let mut order = Vec::new();
+        self.order_nodes(self.root(), cache, &mut indices, &mut order);
+        // the parents come later, so walking backwards sees them first
+        let mut needed = HashSet::new();
+        needed.insert(self.root());
+        for &id in order.iter().rev() {
+            if !needed.contains(&id) {
+                continue;
+            }
+            if let Node::Inode(inode) = id.node(self) {
+                if self.block_bits(id).is_none() {
+                    needed.extend([inode.nw, inode.ne, inode.sw, inode.se]);
+                }
+                if let Some((_, result)) = self.written_result(inode).filter(|_| cache) {
+                    needed.insert(result);
+                }
+            }
+        }
+        order.retain(|id| needed.contains(id));
+        order
+    }
+
+    // a node has room for one result, the full step is kept over a smaller one as it is the one
+    // long runs are resumed with
+    fn written_result(&self, inode: &Inode) -> Option<(u8, Id)> {
+        let full_step = || inode.level.value() - self.leaf_evolve_level();
+        inode
+            .result
+            .map(|result| (full_step(), result))
+            .or(inode.slow_result)
+    }
+
+    // the cells of a level 2 node as a mask, if none of them is dying
+    fn block_bits(&self, id: Id) -> Option<u16> {
+        let inode = id.inode(self);
+        if inode.level != 2 {
+            return None;
+        }
+        let mut bits = 0;
+        for (quadrant, x, y) in [
+            (inode.nw, 0, 0),
+            (inode.ne, 2, 0),
+            (inode.sw, 0, 2),
+            (inode.se, 2, 2),
+        ] {
+            let quadrant = quadrant.inode(self);
+            for (leaf, dx, dy) in [
+                (quadrant.nw, 0, 0),
+                (quadrant.ne, 1, 0),
+                (quadrant.sw, 0, 1),
+                (quadrant.se, 1, 1),
+            ] {
+                match leaf.node(self) {
+                    Node::Leaf(Leaf(Cell::Alive)) => bits |= 1 << (15 - (y + dy) * 4 - (x + dx)),
+                    Node::Leaf(Leaf(Cell::Dead)) => {}
+                    _ => return None,
+                }
+            }
+        }
+        Some(bits)
+    }
+
+    fn new_block(&mut self, bits: u16) -> Id {
+        let cell = |x: u16, y: u16| match bits >> (15 - y * 4 - x) & 1 {
+            1 => Cell::Alive,
+            _ => Cell::Dead,
+        };
+        let [nw, ne, sw, se] = [(0, 0), (2, 0), (0, 2), (2, 2)].map(|(x, y)| {
+            let [nw, ne, sw, se] = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(dx, dy)| self.new_leaf(cell(x + dx, y + dy)));
+            self.new_inode(nw, ne, sw, se)
+        });
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    fn read_node(
+        &mut self,
+        reader: &mut impl Read,
+        version: u8,
+        nodes: &[Id],
+        states: u8,
+    ) -> Result<Id, ParseError> {
+        let index = nodes.len();
+        let corrupt = || invalid(format!("invalid node {}", index));
+        let earlier = |reader: &mut _| -> Result<Id, ParseError> {
+            let distance = read_varint(reader)?;
+            usize::try_from(distance)
+                .ok()
+                .filter(|&distance| (1..=index).contains(&distance))
+                .map(|distance| nodes[index - distance])
+                .ok_or_else(corrupt)
+        };
+        let tag = migrate_tag(version, read_byte(reader)?).ok_or_else(corrupt)?;
+        let id = match tag & !CACHED {
+            LEAF if tag == LEAF => {
+                let state = read_byte(reader)?;
+                if state >= states {
+                    return Err(corrupt());
+                }
+                return Ok(self.new_leaf(Cell::from_state(state)));
+            }
+            INODE => {
+                let children = [
+                    earlier(reader)?,
+                    earlier(reader)?,
+                    earlier(reader)?,
+                    earlier(reader)?,
+                ];
+                let level = children[0].node(self).level();
+                if level >= Level::MAX_LEVEL
+                    || children
+                        .iter()
+                        .any(|child| child.node(self).level() != level)
+                {
+                    return Err(corrupt());
+                }
+                let [nw, ne, sw, se] = children;
+                self.new_inode(nw, ne, sw, se)
+            }
+            BLOCK => {
+                let mut bits = [0; 2];
+                reader.read_exact(&mut bits)?;
+                self.new_block(u16::from_le_bytes(bits))
+            }
+            _ => return Err(corrupt()),
+        };
+        if tag & CACHED != 0 {
+            let step_log = read_byte(reader)?;
+            let result = earlier(reader)?;
+            // the result is the center of the node, one level lower
+            let level = id.node(self).level().value();
+            let leaf = self.leaf_evolve_level();
+            if level < leaf || step_log > level - leaf || result.node(self).level() != level - 1 {
+                return Err(corrupt());
+            }
+            self.cache_result(id, step_log, result);
+        }
+        Ok(id)
+    }
+}
+
+// the tag of a node in the current version, none if it is invalid in `version`
+fn migrate_tag(version: u8, tag: u8) -> Option<u8> {
+    match (version, tag) {
+        (1, LEAF | INODE) => Some(tag),
+        (1, CACHED_INODE_V1) => Some(INODE | CACHED),
+        (1, _) => None,
+        _ => Some(tag),
+    }
+}
+
+fn invalid(reason: String) -> ParseError {
+    ParseError::InvalidCheckpoint(reason)
+}
+
+fn write_varint(writer: &mut impl Write, mut n: u128) -> io::Result<()> {
+    while n >= 0x80 {
+        writer.write_all(&[n as u8 | 0x80])?;
+        n >>= 7;
+    }
+    writer.write_all(&[n as u8])
+}
+
+fn write_signed(writer: &mut impl Write, n: i64) -> io::Result<()> {
+    write_varint(writer, ((n << 1) ^ (n >> 63)) as u64 as u128)
+}
+
+fn write_string(writer: &mut impl Write, s: &str) -> io::Result<()> {
+    write_varint(writer, s.len() as u128)?;
+    writer.write_all(s.as_bytes())
+}
+
+fn read_byte(reader: &mut impl Read) -> Result<u8, ParseError> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_varint(reader: &mut impl Read) -> Result<u128, ParseError> {
+    let mut n = 0;
+    for shift in (0..128).step_by(7) {
+        let bits = u128::from(read_byte(reader)?);
+        // the last byte only has room for two bits
+        if shift > 121 && (bits & 0x7f) >> (128 - shift) != 0 {
+            break;
+        }
+        n |= (bits & 0x7f) << shift;
+        if bits & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(invalid("integer out of range".to_owned()))
+}
+
+fn read_signed(reader: &mut impl Read) -> Result<i64, ParseError> {
+    let n = u64::try_from(read_varint(reader)?)
+        .map_err(|_| invalid("coordinate out of range".to_owned()))?;
+    Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String, ParseError> {
+    let len = read_varint(reader)?;
+    if len > MAX_STRING_LEN {
+        return Err(invalid("string too long".to_owned()));
+    }
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid("string is not utf-8".to_owned()))
+}
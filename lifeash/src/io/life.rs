@@ -0,0 +1,117 @@
+// the plaintext coordinate formats Life 1.05 and Life 1.06 as described on
+// https://www.conwaylife.com/wiki/Life_1.05 and https://www.conwaylife.com/wiki/Life_1.06
+
+use super::ParseError;
+use crate::{core::Position, pattern::Pattern, rule::Rule};
+
+const HEADER_105: &str = "#Life 1.05";
+const HEADER_106: &str = "#Life 1.06";
+
+/// picks the parser by the header line
+///
+/// ```
+/// use lifeash::io::life;
+///
+/// let pattern = life::parse("#Life 1.06\n0 -1\n1 0\n").unwrap();
+/// assert_eq!(pattern.cells.len(), 2);
+/// assert!(life::parse("x = 1, y = 1\no!").is_err());
+/// ```
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let header = input.lines().next().unwrap_or_default().trim();
+    if header.starts_with(HEADER_105) {
+        parse_105(input)
+    } else if header.starts_with(HEADER_106) {
+        parse_106(input)
+    } else {
+        Err(ParseError::InvalidHeader(header.to_owned()))
+    }
+}
+
+/// one "x y" pair of a live cell per line
+///
+/// ```
+/// use lifeash::{io::life, Position};
+///
+/// let pattern = life::parse_106("#Life 1.06\n-1 2\n").unwrap();
+/// assert_eq!(pattern.cells, [Position::new(-1, 2)]);
+/// ```
+pub fn parse_106(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        pattern.cells.push(parse_coordinates(line, line_number)?);
+    }
+    Ok(pattern)
+}
+
+/// blocks of '.' and '*' rows, each placed by a preceding "#P x y" line
+///
+/// ```
+/// use lifeash::{io::life, Position};
+///
+/// let pattern = life::parse_105("#Life 1.05\n#P -1 4\n.*\n").unwrap();
+/// assert_eq!(pattern.cells, [Position::new(0, 4)]);
+/// ```
+pub fn parse_105(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut block = Position::ORIGIN;
+    let mut y = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+
+        if let Some(line) = line.strip_prefix('#') {
+            let mut chars = line.chars();
+            let tag = chars.next();
+            let text = chars.as_str().trim();
+            match tag {
+                Some('D') | Some('C') => pattern.comments.push(text.to_owned()),
+                Some('N') => pattern.rule = Some(Rule::CONWAY),
+                // survival/birth order
+                Some('R') => pattern.rule = Some(text.parse()?),
+                Some('P') => {
+                    block = parse_coordinates(text, line_number)?;
+                    y = 0;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                '*' => pattern
+                    .cells
+                    .push(Position::new(block.x + x as i64, block.y + y)),
+                _ => {
+                    return Err(ParseError::UnexpectedChar {
+                        line: line_number,
+                        c,
+                    })
+                }
+            }
+        }
+        y += 1;
+    }
+    Ok(pattern)
+}
+
+fn parse_coordinates(text: &str, line_number: usize) -> Result<Position, ParseError> {
+    let invalid = || ParseError::InvalidCoordinates { line: line_number };
+    let mut numbers = text
+        .split_whitespace()
+        .map(|n| n.parse::<i64>().map_err(|_| invalid()));
+    match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(x), Some(y), None) => Ok(Position::new(x?, y?)),
+        _ => Err(invalid()),
+    }
+}
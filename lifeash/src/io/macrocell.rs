@@ -0,0 +1,317 @@
+// Golly's macrocell format as described on https://www.conwaylife.com/wiki/Macrocell
+// every unique node is written once, so the file keeps the structural sharing of the quadtree
+
+use std::collections::HashMap;
+
+use super::ParseError;
+use crate::{
+    core::{Cell, Level},
+    hashing::Digest,
+    node::Node,
+    rule::Rule,
+    universe::{Id, Universe},
+};
+
+const HEADER: &str = "[M2]";
+// a comment, other readers like Golly ignore the line
+const HASH_COMMENT: &str = "C hash";
+// macrocell leaves are 8x8 blocks
+const LEAF_LEVEL: u8 = 3;
+const LEAF_SIDE: i64 = 8;
+// nodes of the universe below one macrocell leaf, at most 16 + 4 + 1 inodes
+const NODES_PER_LEAF: usize = 21;
+// evolving a tree adds its results and the intermediate nodes of the nine overlapping
+// subsquares, a few times the nodes of the tree itself
+const EVOLVE_FACTOR: usize = 4;
+
+impl Universe {
+    /// reads the macrocell format, the universe keeps the sharing of the file
+    /// files written by `to_macrocell` carry a hash of their content, which has to match
+    ///
+    /// ```
+    /// use lifeash::{io::ParseError, prelude::*};
+    ///
+    /// let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let copy = Universe::from_macrocell(&glider.to_macrocell()).unwrap();
+    /// assert_eq!(copy.content_hash(), glider.content_hash());
+    ///
+    /// // a cell went missing
+    /// let corrupted = glider.to_macrocell().replace(".*$", ".$");
+    /// assert!(matches!(
+    ///     Universe::from_macrocell(&corrupted),
+    ///     Err(ParseError::HashMismatch { .. })
+    /// ));
+    /// ```
+    pub fn from_macrocell(input: &str) -> Result<Self, ParseError> {
+        Self::read_macrocell(input, true)
+    }
+
+    /// reads the macrocell format without checking the hash, e.g. to load a corrupted file anyway
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let corrupted = glider.to_macrocell().replace(".*$", ".$");
+    /// let copy = Universe::from_macrocell_unverified(&corrupted).unwrap();
+    /// assert_eq!(copy.population(), 3);
+    /// ```
+    pub fn from_macrocell_unverified(input: &str) -> Result<Self, ParseError> {
+        Self::read_macrocell(input, false)
+    }
+
+    fn read_macrocell(input: &str, verify: bool) -> Result<Self, ParseError> {
+        let mut lines = input.lines().enumerate();
+        match lines.next() {
+            Some((_, line)) if line.starts_with(HEADER) => {}
+            Some((_, line)) => return Err(ParseError::InvalidHeader(line.to_owned())),
+            None => return Err(ParseError::MissingRoot),
+        }
+
+        // reserving the tables up front avoids rehashing them over and over while a large
+        // pattern is read and during its first generations
+        let (leaves, inodes) = count_nodes(input);
+        let mut universe = Self::new();
+        universe.reserve((leaves * NODES_PER_LEAF + inodes) * EVOLVE_FACTOR);
+        let mut rule = Rule::default();
+        let mut hash = None;
+        // the node on the n-th node line is stored at index n - 1, 0 refers to the empty node
+        let mut nodes: Vec<Id> = Vec::with_capacity(leaves + inodes);
+        let mut empty_trees: HashMap<u8, Id> = HashMap::new();
+
+        for (i, line) in lines {
+            let line_number = i + 1;
+            let line = line.trim();
+
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(rulestring) = comment.strip_prefix('R') {
+                    rule = rulestring.trim().parse()?;
+                } else if let Some(hex) = comment.strip_prefix(HASH_COMMENT) {
+                    let invalid = || ParseError::InvalidHeader(line.to_owned());
+                    hash = Some(u128::from_str_radix(hex.trim(), 16).map_err(|_| invalid())?);
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            let id = if line.starts_with(&['.', '*', '$'][..]) {
+                universe.parse_leaf(line, line_number)?
+            } else {
+                universe.parse_inode(line, line_number, &nodes, &mut empty_trees)?
+            };
+            nodes.push(id);
+        }
+
+        let root = *nodes.last().ok_or(ParseError::MissingRoot)?;
+        universe.set_rule(rule);
+        universe.set_root(root);
+        if let Some(expected) = hash.filter(|_| verify) {
+            let found = universe.macrocell_hash();
+            if found != expected {
+                return Err(ParseError::HashMismatch { expected, found });
+            }
+        }
+        Ok(universe)
+    }
+
+    // the rule and the tree, whose dying cells the format doesn't keep
+    fn macrocell_hash(&self) -> u128 {
+        let mut digest = Digest::new();
+        digest.write_str(&self.rule().to_string());
+        digest.write_tree(self, self.root(), true);
+        digest.finish()
+    }
+
+    // rows are separated by '$', '.' is dead and '*' is alive
+    fn parse_leaf(&mut self, line: &str, line_number: usize) -> Result<Id, ParseError> {
+        let mut leaf = self.new_empty_tree(Level::new(LEAF_LEVEL));
+        let (mut x, mut y) = (0i64, 0i64);
+        for c in line.chars() {
+            match c {
+                '.' => x += 1,
+                '*' => {
+                    if x >= LEAF_SIDE || y >= LEAF_SIDE {
+                        return Err(ParseError::InvalidNode { line: line_number });
+                    }
+                    let pos = (x - LEAF_SIDE / 2, y - LEAF_SIDE / 2);
+                    leaf = self.set_tree_cell(leaf, pos, Cell::Alive);
+                    x += 1;
+                }
+                '$' => {
+                    x = 0;
+                    y += 1;
+                }
+                _ => {
+                    return Err(ParseError::UnexpectedChar {
+                        line: line_number,
+                        c,
+                    })
+                }
+            }
+        }
+        Ok(leaf)
+    }
+
+    // "level nw ne sw se" where the children are 1-based node indices and 0 is the empty node
+    fn parse_inode(
+        &mut self,
+        line: &str,
+        line_number: usize,
+        nodes: &[Id],
+        empty_trees: &mut HashMap<u8, Id>,
+    ) -> Result<Id, ParseError> {
+        let invalid = || ParseError::InvalidNode { line: line_number };
+        let numbers = line
+            .split_whitespace()
+            .map(|n| n.parse::<usize>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if numbers.len() != 5 || numbers[0] <= LEAF_LEVEL as usize || numbers[0] > 63 {
+            return Err(invalid());
+        }
+
+        let child_level = numbers[0] as u8 - 1;
+        let mut children = [None; 4];
+        for (child, &index) in children.iter_mut().zip(&numbers[1..]) {
+            let id = match index {
+                0 => *empty_trees
+                    .entry(child_level)
+                    .or_insert_with(|| self.new_empty_tree(Level::new(child_level))),
+                _ => *nodes.get(index - 1).ok_or_else(invalid)?,
+            };
+            if id.node(self).level() != child_level {
+                return Err(invalid());
+            }
+            *child = Some(id);
+        }
+        let [nw, ne, sw, se] = children;
+        Ok(self.new_inode(nw.unwrap(), ne.unwrap(), sw.unwrap(), se.unwrap()))
+    }
+
+    /// writes every unique node of the tree once, and a hash of the content which
+    /// `from_macrocell` checks
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("2o$2o!").unwrap();
+    /// assert!(universe.to_macrocell().starts_with("[M2]"));
+    /// ```
+    pub fn to_macrocell(&self) -> String {
+        let mut out = format!(
+            "{}\n#R {}\n#{} {:032x}\n",
+            HEADER,
+            self.rule(),
+            HASH_COMMENT,
+            self.macrocell_hash()
+        );
+        let mut indices = HashMap::new();
+        let mut lines = Vec::new();
+        self.write_node(self.root(), &mut indices, &mut lines);
+        if lines.is_empty() {
+            // an empty universe still needs a root
+            lines.push("$".to_owned());
+        }
+        for line in lines {
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+
+    // returns the 1-based index of the line the node was written to, 0 for nodes without alive
+    // cells, which includes those holding only dying cells
+    fn write_node(
+        &self,
+        id: Id,
+        indices: &mut HashMap<Id, usize>,
+        lines: &mut Vec<String>,
+    ) -> usize {
+        let node = id.node(self);
+        if node.population() == 0 {
+            return 0;
+        }
+        if let Some(&index) = indices.get(&id) {
+            return index;
+        }
+
+        let line = match node {
+            Node::Inode(inode) if inode.level == LEAF_LEVEL => self.leaf_line(id),
+            Node::Inode(inode) => {
+                let (level, nw, ne, sw, se) = (inode.level, inode.nw, inode.ne, inode.sw, inode.se);
+                let children = [
+                    self.write_node(nw, indices, lines),
+                    self.write_node(ne, indices, lines),
+                    self.write_node(sw, indices, lines),
+                    self.write_node(se, indices, lines),
+                ];
+                if children == [0; 4] {
+                    None
+                } else {
+                    Some(format!(
+                        "{} {} {} {} {}",
+                        level.value(),
+                        children[0],
+                        children[1],
+                        children[2],
+                        children[3]
+                    ))
+                }
+            }
+            Node::Leaf(_) => unreachable!("the root is at least level 3"),
+        };
+        let line = match line {
+            Some(line) => line,
+            None => {
+                indices.insert(id, 0);
+                return 0;
+            }
+        };
+
+        lines.push(line);
+        indices.insert(id, lines.len());
+        lines.len()
+    }
+
+    // none if the leaf has no alive cells
+    fn leaf_line(&self, id: Id) -> Option<String> {
+        let rows: Vec<String> = (0..LEAF_SIDE)
+            .map(|y| {
+                let row: String = (0..LEAF_SIDE)
+                    .map(
+                        |x| match self.get_tree_cell(id, (x - LEAF_SIDE / 2, y - LEAF_SIDE / 2)) {
+                            Cell::Alive => '*',
+                            // the two state format has no dying cells
+                            Cell::Dead | Cell::Dying(_) => '.',
+                        },
+                    )
+                    .collect();
+                row.trim_end_matches('.').to_owned()
+            })
+            .collect();
+        let last = rows.iter().rposition(|row| !row.is_empty())?;
+        Some(
+            rows[..=last]
+                .iter()
+                .map(|row| format!("{}$", row))
+                .collect(),
+        )
+    }
+}
+
+// leaf and inode lines of a macrocell file, without validating them
+fn count_nodes(input: &str) -> (usize, usize) {
+    input
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .fold((0, 0), |(leaves, inodes), line| {
+            if line.starts_with(&['.', '*', '$'][..]) {
+                (leaves + 1, inodes)
+            } else {
+                (leaves, inodes + 1)
+            }
+        })
+}
@@ -0,0 +1,88 @@
+use std::{error::Error, fmt, io};
+
+use crate::rule::ParseRuleError;
+
+#[cfg(feature = "formats-extra")]
+pub mod apgcode;
+#[cfg(feature = "formats-extra")]
+pub mod checkpoint;
+#[cfg(feature = "formats-extra")]
+pub mod life;
+#[cfg(feature = "formats-extra")]
+pub mod macrocell;
+#[cfg(feature = "formats-extra")]
+pub mod plaintext;
+pub mod rle;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    InvalidHeader(String),
+    InvalidApgcode(String),
+    InvalidCheckpoint(String),
+    InvalidRule(ParseRuleError),
+    InvalidRunCount { line: usize },
+    InvalidCoordinates { line: usize },
+    InvalidNode { line: usize },
+    // the content doesn't match the hash the file was written with
+    HashMismatch { expected: u128, found: u128 },
+    // reading the input failed, only the message is kept so the error stays comparable
+    Io(String),
+    MissingRoot,
+    UnexpectedChar { line: usize, c: char },
+    // a cell state which the rule doesn't have, or a dying cell where only alive ones are kept
+    UnexpectedState { line: usize, state: u32 },
+    // the name of a pattern file whose extension isn't a known format
+    UnknownFormat(String),
+}
+
+impl From<ParseRuleError> for ParseError {
+    fn from(err: ParseRuleError) -> Self {
+        ParseError::InvalidRule(err)
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        ParseError::Io(err.to_string())
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader(header) => write!(f, "invalid header: \"{}\"", header),
+            Self::InvalidApgcode(code) => write!(f, "invalid apgcode: \"{}\"", code),
+            Self::InvalidCheckpoint(reason) => write!(f, "invalid checkpoint: {}", reason),
+            Self::InvalidRule(err) => write!(f, "invalid rule: {}", err),
+            Self::InvalidRunCount { line } => write!(f, "invalid run count on line {}", line),
+            Self::InvalidCoordinates { line } => {
+                write!(f, "invalid coordinates on line {}", line)
+            }
+            Self::InvalidNode { line } => write!(f, "invalid node on line {}", line),
+            Self::HashMismatch { expected, found } => write!(
+                f,
+                "the content hashes to {:032x} instead of {:032x}, the file is corrupted",
+                found, expected
+            ),
+            Self::Io(err) => write!(f, "failed to read the input: {}", err),
+            Self::MissingRoot => write!(f, "pattern contains no nodes"),
+            Self::UnexpectedChar { line, c } => {
+                write!(f, "unexpected character '{}' on line {}", c, line)
+            }
+            Self::UnexpectedState { line, state } => {
+                write!(f, "unexpected cell state {} on line {}", state, line)
+            }
+            Self::UnknownFormat(name) => write!(f, "unknown pattern format of \"{}\"", name),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidRule(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// the plaintext format as described on https://www.conwaylife.com/wiki/Plaintext
+
+use super::ParseError;
+use crate::{
+    core::{Cell, Position, Rect},
+    pattern::Pattern,
+    universe::Universe,
+};
+
+/// 'O' is alive, '.' is dead and lines starting with '!' are comments
+///
+/// ```
+/// use lifeash::io::plaintext;
+///
+/// let pattern = plaintext::parse("!Name: Blinker\nOOO\n").unwrap();
+/// assert_eq!(pattern.name.as_deref(), Some("Blinker"));
+/// assert_eq!(pattern.cells.len(), 3);
+/// ```
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut y = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = Some(name.trim().to_owned()),
+                None => pattern.comments.push(comment.trim().to_owned()),
+            }
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => pattern.cells.push(Position::new(x as i64, y)),
+                _ => {
+                    return Err(ParseError::UnexpectedChar {
+                        line: line_number,
+                        c,
+                    })
+                }
+            }
+        }
+        y += 1;
+    }
+    Ok(pattern)
+}
+
+impl Universe {
+    /// one line per row of `rect` without trailing dead cells
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// assert_eq!(
+    ///     universe.to_plaintext(Rect::new((0, 0), (2, 2))),
+    ///     ".O\n..O\nOOO\n"
+    /// );
+    /// ```
+    pub fn to_plaintext(&self, rect: Rect) -> String {
+        let mut out = String::new();
+        for y in rect.min.y..=rect.max.y {
+            let row: String = (rect.min.x..=rect.max.x)
+                .map(|x| match self.get_cell((x, y)) {
+                    Cell::Alive => 'O',
+                    Cell::Dead | Cell::Dying(_) => '.',
+                })
+                .collect();
+            out.push_str(row.trim_end_matches('.'));
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -0,0 +1,639 @@
+// run length encoded patterns as described on https://www.conwaylife.com/wiki/Run_Length_Encoded
+
+use std::io::{ErrorKind, Read};
+
+use super::ParseError;
+use crate::{
+    core::{Cell, Position, Rect},
+    pattern::Pattern,
+    rule::Rule,
+    universe::Universe,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Rle {
+    pub name: Option<String>,
+    pub comments: Vec<String>,
+    pub width: u64,
+    pub height: u64,
+    pub rule: Option<Rule>,
+    // live cells relative to the top left corner of the pattern
+    pub cells: Vec<Position>,
+}
+
+impl From<Rle> for Pattern {
+    fn from(rle: Rle) -> Self {
+        Pattern {
+            name: rle.name,
+            comments: rle.comments,
+            rule: rle.rule,
+            cells: rle.cells,
+        }
+    }
+}
+
+/// reads the header, the comments and the cells of an RLE file, a `Rle` only has alive cells so
+/// dying cells of Generations rules are an error
+///
+/// ```
+/// use lifeash::io::rle;
+///
+/// let rle = rle::parse("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+/// assert_eq!(rle.name.as_deref(), Some("Glider"));
+/// assert_eq!((rle.width, rle.height), (3, 3));
+/// assert_eq!(rle.cells.len(), 5);
+/// ```
+pub fn parse(input: &str) -> Result<Rle, ParseError> {
+    let mut parser = Parser::new();
+    let mut cells = Vec::new();
+    parser.feed(input.as_bytes(), &mut cells)?;
+    Ok(Rle {
+        cells,
+        ..parser.finish()?
+    })
+}
+
+// bytes read from the input at once
+const CHUNK_LEN: usize = 1 << 16;
+/// cells decoded before `Parser::feed_batched` hands them out
+pub const BATCH_LEN: usize = 1 << 16;
+
+// incremental parser which only buffers the current comment or header line,
+// the cells are handed out as soon as they are decoded
+#[derive(Debug, Clone)]
+pub struct Parser {
+    // everything but the cells
+    rle: Rle,
+    line_number: usize,
+    line_start: bool,
+    // comment or header line which is not complete yet
+    pending: Option<Vec<u8>>,
+    x: i64,
+    y: i64,
+    run: Option<i64>,
+    // the first letter of a state above 24, e.g. the `p` of `pA`
+    prefix: Option<u8>,
+    // states allowed in the cells, those of the rule in the header if missing
+    states: Option<u8>,
+    done: bool,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self {
+            rle: Rle::default(),
+            line_number: 1,
+            line_start: true,
+            pending: None,
+            x: 0,
+            y: 0,
+            run: None,
+            prefix: None,
+            states: None,
+            done: false,
+        }
+    }
+
+    /// decodes the next chunk of the input, which may end anywhere, and appends its live cells,
+    /// dying cells are an error as the positions can't keep their states
+    ///
+    /// ```
+    /// use lifeash::io::rle::{self, Parser};
+    ///
+    /// let input = "#N Glider\nx = 3, y = 3\nbo$2bo$3o!";
+    /// let mut parser = Parser::new();
+    /// let mut cells = Vec::new();
+    /// for chunk in input.as_bytes().chunks(4) {
+    ///     parser.feed(chunk, &mut cells).unwrap();
+    /// }
+    /// assert!(parser.is_done());
+    /// let header = parser.finish().unwrap();
+    /// assert_eq!(header.name.as_deref(), Some("Glider"));
+    /// assert_eq!(cells, rle::parse(input).unwrap().cells);
+    /// ```
+    pub fn feed(&mut self, chunk: &[u8], cells: &mut Vec<Position>) -> Result<(), ParseError> {
+        self.feed_with(chunk, 2, &mut |pos, _| {
+            cells.push(pos);
+            Ok::<_, ParseError>(())
+        })
+    }
+
+    /// like `feed`, but appends every cell which isn't dead with its state, the letters of
+    /// Generations rules are decoded like in Golly: `.` is dead, `A` alive and `B` onwards the
+    /// dying states, states which the rule of the header doesn't have are an error
+    ///
+    /// ```
+    /// use lifeash::{io::rle::Parser, prelude::*};
+    ///
+    /// let mut parser = Parser::new();
+    /// let mut cells = Vec::new();
+    /// parser.feed_states(b"x = 3, y = 1, rule = B2/S/C3\n.AB!", &mut cells).unwrap();
+    /// assert_eq!(
+    ///     cells,
+    ///     [
+    ///         (Position::new(1, 0), Cell::Alive),
+    ///         (Position::new(2, 0), Cell::Dying(2)),
+    ///     ]
+    /// );
+    ///
+    /// let mut parser = Parser::new();
+    /// assert!(parser.feed_states(b"x = 1, y = 1\nB!", &mut cells).is_err());
+    /// ```
+    pub fn feed_states(
+        &mut self,
+        chunk: &[u8],
+        cells: &mut Vec<(Position, Cell)>,
+    ) -> Result<(), ParseError> {
+        self.feed_with(chunk, u8::MAX, &mut |pos, cell| {
+            cells.push((pos, cell));
+            Ok::<_, ParseError>(())
+        })
+    }
+
+    /// like `feed_states`, but calls `flush` whenever `cells` holds `BATCH_LEN` cells, also in
+    /// the middle of a run, so that memory stays bounded however long the runs are, `flush` is
+    /// expected to empty `cells`
+    ///
+    /// ```
+    /// use lifeash::io::{rle::{Parser, BATCH_LEN}, ParseError};
+    ///
+    /// let mut parser = Parser::new();
+    /// let mut cells = Vec::new();
+    /// let mut flushed = 0;
+    /// parser
+    ///     .feed_batched(b"100000o!", &mut cells, |batch| {
+    ///         assert_eq!(batch.len(), BATCH_LEN);
+    ///         flushed += batch.drain(..).count();
+    ///         Ok::<_, ParseError>(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(flushed + cells.len(), 100000);
+    /// ```
+    pub fn feed_batched<E: From<ParseError>>(
+        &mut self,
+        chunk: &[u8],
+        cells: &mut Vec<(Position, Cell)>,
+        mut flush: impl FnMut(&mut Vec<(Position, Cell)>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.feed_with(chunk, u8::MAX, &mut |pos, cell| {
+            cells.push((pos, cell));
+            if cells.len() >= BATCH_LEN {
+                flush(cells)?;
+            }
+            Ok(())
+        })
+    }
+
+    // hands the cells which aren't dead to `emit`, states from `max_states` on are an error even
+    // if the rule has them
+    fn feed_with<E: From<ParseError>>(
+        &mut self,
+        chunk: &[u8],
+        max_states: u8,
+        emit: &mut impl FnMut(Position, Cell) -> Result<(), E>,
+    ) -> Result<(), E> {
+        for &byte in chunk {
+            if self.done {
+                break;
+            }
+            if byte == b'\n' {
+                if let Some(line) = self.pending.take() {
+                    self.parse_line(&line)?;
+                }
+                self.line_number += 1;
+                self.line_start = true;
+                continue;
+            }
+            if let Some(line) = &mut self.pending {
+                line.push(byte);
+                continue;
+            }
+            if self.line_start {
+                match byte {
+                    _ if byte.is_ascii_whitespace() => continue,
+                    b'#' | b'x' => {
+                        self.pending = Some(vec![byte]);
+                        continue;
+                    }
+                    _ => self.line_start = false,
+                }
+            }
+            let states = self
+                .states
+                .or_else(|| self.rle.rule.map(Rule::states))
+                .unwrap_or(2)
+                .min(max_states);
+            self.parse_cell(byte as char, states, emit)?;
+        }
+        Ok(())
+    }
+
+    /// whether the end of the pattern was reached, the rest of the input is ignored
+    ///
+    /// ```
+    /// use lifeash::io::rle::Parser;
+    ///
+    /// let mut parser = Parser::new();
+    /// parser.feed(b"3o$", &mut Vec::new()).unwrap();
+    /// assert!(!parser.is_done());
+    /// parser.feed(b"3o!", &mut Vec::new()).unwrap();
+    /// assert!(parser.is_done());
+    /// ```
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// the header and the comments, without cells
+    ///
+    /// ```
+    /// use lifeash::{io::rle::Parser, Rule};
+    ///
+    /// let mut parser = Parser::new();
+    /// parser.feed(b"x = 3, y = 1, rule = B36/S23", &mut Vec::new()).unwrap();
+    /// let header = parser.finish().unwrap();
+    /// assert_eq!(header.rule, Some(Rule::new(&[3, 6], &[2, 3])));
+    /// assert!(header.cells.is_empty());
+    /// ```
+    pub fn finish(mut self) -> Result<Rle, ParseError> {
+        if let Some(line) = self.pending.take() {
+            self.parse_line(&line)?;
+        }
+        Ok(self.rle)
+    }
+
+    // a complete comment or header line
+    fn parse_line(&mut self, line: &[u8]) -> Result<(), ParseError> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        match line.strip_prefix('#') {
+            Some(comment) => {
+                let mut chars = comment.chars();
+                let tag = chars.next();
+                let text = chars.as_str().trim();
+                match tag {
+                    Some('N') => self.rle.name = Some(text.to_owned()),
+                    Some('C') | Some('c') | Some('O') => self.rle.comments.push(text.to_owned()),
+                    Some('r') => self.rle.rule = Some(text.parse()?),
+                    _ => {}
+                }
+                Ok(())
+            }
+            None => parse_header(line, &mut self.rle),
+        }
+    }
+
+    fn parse_cell<E: From<ParseError>>(
+        &mut self,
+        c: char,
+        states: u8,
+        emit: &mut impl FnMut(Position, Cell) -> Result<(), E>,
+    ) -> Result<(), E> {
+        if let Some(prefix) = self.prefix.take() {
+            return match c {
+                'A'..='X' => {
+                    let state = STATE_LETTERS * u32::from(prefix - b'p' + 1) + letter_state(c);
+                    self.push_run(state, states, emit)
+                }
+                _ => Err(ParseError::UnexpectedChar {
+                    line: self.line_number,
+                    c,
+                }
+                .into()),
+            };
+        }
+        match c {
+            _ if c.is_ascii_digit() => {
+                let digit = i64::from(c.to_digit(10).unwrap());
+                self.run = self
+                    .run
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|r| r.checked_add(digit))
+                    .map(Some)
+                    .ok_or(ParseError::InvalidRunCount {
+                        line: self.line_number,
+                    })?;
+            }
+            'b' | '.' => self.x += self.run.take().unwrap_or(1),
+            '$' => {
+                self.y += self.run.take().unwrap_or(1);
+                self.x = 0;
+            }
+            '!' => self.done = true,
+            'p'..='y' => self.prefix = Some(c as u8),
+            'A'..='X' => self.push_run(letter_state(c), states, emit)?,
+            // `o` and any other letter are alive
+            _ if c.is_ascii_alphabetic() => self.push_run(1, states, emit)?,
+            _ if c.is_whitespace() => {}
+            _ => {
+                return Err(ParseError::UnexpectedChar {
+                    line: self.line_number,
+                    c,
+                }
+                .into())
+            }
+        }
+        Ok(())
+    }
+
+    fn push_run<E: From<ParseError>>(
+        &mut self,
+        state: u32,
+        states: u8,
+        emit: &mut impl FnMut(Position, Cell) -> Result<(), E>,
+    ) -> Result<(), E> {
+        if state >= u32::from(states) {
+            return Err(ParseError::UnexpectedState {
+                line: self.line_number,
+                state,
+            }
+            .into());
+        }
+        let cell = Cell::from_state(state as u8);
+        for _ in 0..self.run.take().unwrap_or(1) {
+            emit(Position::new(self.x, self.y), cell)?;
+            self.x += 1;
+        }
+        Ok(())
+    }
+}
+
+// `A` to `X` are the states 1 to 24, the states above have a prefix from `p` to `y`
+const STATE_LETTERS: u32 = 24;
+
+fn letter_state(c: char) -> u32 {
+    u32::from(c) - u32::from('A') + 1
+}
+
+// `b` and `o` with two states, otherwise the letters of Golly, `.` for dead cells
+fn state_tag(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => "b".to_owned(),
+        (_, false) => "o".to_owned(),
+        (0, true) => ".".to_owned(),
+        (state, true) => {
+            let index = u32::from(state) - 1;
+            let letter = char::from_u32(u32::from('A') + index % STATE_LETTERS).unwrap();
+            match index / STATE_LETTERS {
+                0 => letter.to_string(),
+                prefix => format!("{}{}", char::from(b'p' + prefix as u8 - 1), letter),
+            }
+        }
+    }
+}
+
+const MAX_LINE_LEN: usize = 70;
+
+/// the header followed by the cells, with lines no longer than 70 characters
+///
+/// ```
+/// use lifeash::io::rle;
+///
+/// let glider = rle::parse("x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+/// assert_eq!(rle::parse(&rle::write(&glider)).unwrap(), glider);
+/// ```
+pub fn write(rle: &Rle) -> String {
+    let cells = rle.cells.iter().map(|&pos| (pos, Cell::Alive)).collect();
+    write_cells(header(rle), cells, false)
+}
+
+fn header(rle: &Rle) -> String {
+    let mut out = String::new();
+    if let Some(name) = &rle.name {
+        out.push_str(&format!("#N {}\n", name));
+    }
+    for comment in &rle.comments {
+        out.push_str(&format!("#C {}\n", comment));
+    }
+    out.push_str(&format!(
+        "x = {}, y = {}, rule = {}\n",
+        rle.width,
+        rle.height,
+        rle.rule.unwrap_or_default()
+    ));
+    out
+}
+
+// the cells after the header, with the letters of Generations rules if `multi_state`
+fn write_cells(header: String, mut cells: Vec<(Position, Cell)>, multi_state: bool) -> String {
+    cells.sort_by_key(|(pos, _)| (pos.y, pos.x));
+    cells.dedup_by_key(|(pos, _)| *pos);
+
+    let mut writer = LineWriter::new(header);
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut i = 0;
+    while i < cells.len() {
+        let (start, cell) = cells[i];
+        if start.y > y {
+            writer.push(start.y - y, "$");
+            y = start.y;
+            x = 0;
+        }
+        if start.x > x {
+            writer.push(start.x - x, &state_tag(0, multi_state));
+        }
+        let mut run = 1;
+        while i + run < cells.len()
+            && cells[i + run] == (Position::new(start.x + run as i64, y), cell)
+        {
+            run += 1;
+        }
+        writer.push(run as i64, &state_tag(cell.state(), multi_state));
+        x = start.x + run as i64;
+        i += run;
+    }
+    writer.push(1, "!");
+    writer.finish()
+}
+
+// wraps the encoded cells into lines of at most 70 characters without splitting a run
+struct LineWriter {
+    out: String,
+    line: String,
+}
+
+impl LineWriter {
+    fn new(out: String) -> Self {
+        Self {
+            out,
+            line: String::new(),
+        }
+    }
+
+    fn push(&mut self, count: i64, tag: &str) {
+        let token = if count > 1 {
+            format!("{}{}", count, tag)
+        } else {
+            tag.to_string()
+        };
+        if self.line.len() + token.len() > MAX_LINE_LEN {
+            self.out.push_str(&self.line);
+            self.out.push('\n');
+            self.line.clear();
+        }
+        self.line.push_str(&token);
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str(&self.line);
+        self.out.push('\n');
+        self.out
+    }
+}
+
+// x = m, y = n, rule = abc
+fn parse_header(line: &str, rle: &mut Rle) -> Result<(), ParseError> {
+    let invalid = || ParseError::InvalidHeader(line.to_owned());
+
+    for entry in line.split(',') {
+        let mut parts = entry.splitn(2, '=');
+        let key = parts.next().unwrap().trim();
+        // continuation of a value containing commas, e.g. ":T100,100"
+        let value = match parts.next() {
+            Some(value) => value.trim(),
+            None => continue,
+        };
+        match key {
+            "x" => rle.width = value.parse().map_err(|_| invalid())?,
+            "y" => rle.height = value.parse().map_err(|_| invalid())?,
+            // drop bounded grid suffixes like ":T100,100"
+            "rule" => rle.rule = Some(value.split(':').next().unwrap().parse()?),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl Universe {
+    /// a new universe with the pattern at the origin and the rule of the header, including the
+    /// dying cells of Generations rules
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("x = 3, y = 1, rule = B36/S23\n3o!").unwrap();
+    /// assert_eq!(universe.population(), 3);
+    /// assert_eq!(universe.rule(), Rule::new(&[3, 6], &[2, 3]));
+    ///
+    /// let universe = Universe::from_rle("x = 2, y = 1, rule = B2/S/C3\nAB!").unwrap();
+    /// assert_eq!(universe.get_cell((1, 0)), Cell::Dying(2));
+    /// ```
+    pub fn from_rle(input: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser::new();
+        let mut cells = Vec::new();
+        parser.feed_states(input.as_bytes(), &mut cells)?;
+        let rle = parser.finish()?;
+        let mut universe = Self::new();
+        universe.set_rule(rle.rule.unwrap_or_default());
+        universe.set_cells(cells);
+        Ok(universe)
+    }
+
+    /// places the top left corner of the pattern at `pos`, the rule of the universe is kept
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.load_rle_at("2o$2o!", (-10, 5)).unwrap();
+    /// assert_eq!(universe.bounding_box(), Some(((-10, 5).into(), (-9, 6).into())));
+    /// ```
+    pub fn load_rle_at(&mut self, input: &str, pos: impl Into<Position>) -> Result<(), ParseError> {
+        self.read_rle(input.as_bytes(), pos, |_| {}).map(drop)
+    }
+
+    /// streams the pattern from `reader` into the universe with its top left corner at `pos`,
+    /// neither the input nor all of its cells are held in memory at once
+    ///
+    /// `progress` is called with the number of bytes read so far. The header and the comments
+    /// are returned, the rule of the universe is kept and dying cells have to be states of it.
+    /// Loading counts as a single edit.
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let input = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!";
+    /// let mut universe = Universe::new();
+    /// let mut read = 0;
+    /// let header = universe
+    ///     .read_rle(input.as_bytes(), (10, 10), |bytes| read = bytes)
+    ///     .unwrap();
+    /// assert_eq!(header.name.as_deref(), Some("Glider"));
+    /// assert_eq!(read, input.len() as u64);
+    /// assert_eq!(universe.population(), 5);
+    /// assert_eq!(universe.get_cell((11, 10)), Cell::Alive);
+    /// ```
+    pub fn read_rle(
+        &mut self,
+        mut reader: impl Read,
+        pos: impl Into<Position>,
+        mut progress: impl FnMut(u64),
+    ) -> Result<Rle, ParseError> {
+        let pos = pos.into();
+        self.record_edit();
+        self.with_history(|universe, _| {
+            let mut parser = Parser::new();
+            parser.states = Some(universe.rule().states());
+            let mut chunk = vec![0; CHUNK_LEN];
+            let mut cells = Vec::new();
+            let mut read = 0;
+            let flush =
+                |universe: &mut Universe, cells: &mut Vec<(Position, Cell)>| {
+                    universe.set_cells(cells.drain(..).map(|(cell, state)| {
+                        (Position::new(pos.x + cell.x, pos.y + cell.y), state)
+                    }))
+                };
+            while !parser.is_done() {
+                let len = match reader.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(len) => len,
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                read += len as u64;
+                parser.feed_batched(&chunk[..len], &mut cells, |cells| {
+                    flush(universe, cells);
+                    Ok::<_, ParseError>(())
+                })?;
+                progress(read);
+            }
+            flush(universe, &mut cells);
+            parser.finish()
+        })
+    }
+
+    /// the pattern is written relative to the bounding box of its live cells, with the letters
+    /// of Golly for the dying cells of Generations rules
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.load_rle_at("bo$2bo$3o!", (40, -2)).unwrap();
+    /// let copy = Universe::from_rle(&universe.to_rle()).unwrap();
+    /// assert_eq!(copy.bounding_box(), Some(((0, 0).into(), (2, 2).into())));
+    /// assert_eq!(copy.population(), 5);
+    /// ```
+    pub fn to_rle(&self) -> String {
+        let mut rle = Rle {
+            rule: Some(self.rule()),
+            ..Rle::default()
+        };
+        let mut cells = Vec::new();
+        if let Some((min, max)) = self.bounding_box() {
+            rle.width = (max.x - min.x) as u64 + 1;
+            rle.height = (max.y - min.y) as u64 + 1;
+            cells = self
+                .cells_in(Rect::new(min, max))
+                .map(|(pos, cell)| (Position::new(pos.x - min.x, pos.y - min.y), cell))
+                .collect();
+        }
+        write_cells(header(&rle), cells, self.rule().states() > 2)
+    }
+}
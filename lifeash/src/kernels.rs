@@ -0,0 +1,416 @@
+// the leaf level of the tree is evolved by brute force, a kernel gives the center 2x2 cells of a
+// 4x4 block one generation later
+// blocks are u16 with bit 15 for the north west cell and bit 0 for the south east one, row by row
+// results are u8 with bit 3 for the north west center cell, bit 2 north east, 1 south west and
+// 0 south east, a set bit means the cell is born or survives, the states of Generations rules
+// are applied afterwards
+
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::rule::{Rule, Transitions};
+
+// the center cells as (bit in the block, bit in the result)
+const CENTERS: [(u32, u32); 4] = [(10, 3), (9, 2), (6, 1), (5, 0)];
+// the neighbors of the center cells, in the order of `CENTERS`
+const NEIGHBORS: [u16; 4] = [
+    0b1110_1010_1110_0000,
+    0b0111_0101_0111_0000,
+    0b0000_1110_1010_1110,
+    0b0000_0111_0101_0111,
+];
+
+pub trait Kernel {
+    /// the kernel for `rule`, `None` if it cannot evolve the rule
+    fn new(rule: Rule) -> Option<Self>
+    where
+        Self: Sized;
+
+    fn name(&self) -> &'static str;
+
+    /// the center cells of `block` in the next generation
+    fn evolve(&self, block: u16) -> u8;
+}
+
+// looks up each center cell and its ring of neighbors, works for every rule
+#[derive(Debug, Clone)]
+pub struct LookupKernel(Transitions);
+
+impl Kernel for LookupKernel {
+    /// ```
+    /// use lifeash::{kernels::{Kernel, LookupKernel}, Rule};
+    ///
+    /// let kernel = LookupKernel::new(Rule::CONWAY).unwrap();
+    /// // a line of three in the second row, the middle survives and the cell below is born
+    /// assert_eq!(kernel.evolve(0b0000_1110_0000_0000), 0b1010);
+    /// ```
+    fn new(rule: Rule) -> Option<Self> {
+        Some(Self(Transitions::new(rule)))
+    }
+
+    fn name(&self) -> &'static str {
+        "lookup"
+    }
+
+    fn evolve(&self, block: u16) -> u8 {
+        CENTERS.iter().fold(0, |result, &(center, bit)| {
+            // the neighbors clockwise from north, as isotropic rules expect them
+            let ring = [center + 4, center + 3, center - 1, center - 5]
+                .iter()
+                .chain(&[center - 4, center - 3, center + 1, center + 5])
+                .enumerate()
+                .fold(0u8, |ring, (i, neighbor)| {
+                    ring | (((block >> neighbor) & 1) as u8) << i
+                });
+            let alive = self.0.alive((block >> center) & 1 == 1, ring);
+            result | u8::from(alive) << bit
+        })
+    }
+}
+
+// the results of all 65536 blocks, one lookup per block
+#[derive(Debug, Clone)]
+pub struct TableKernel(Box<[u8]>);
+
+impl Kernel for TableKernel {
+    /// ```
+    /// use lifeash::{kernels::{Kernel, TableKernel}, Rule};
+    ///
+    /// let kernel = TableKernel::new(Rule::CONWAY).unwrap();
+    /// assert_eq!(kernel.evolve(0b0000_1110_0000_0000), 0b1010);
+    /// ```
+    fn new(rule: Rule) -> Option<Self> {
+        let lookup = LookupKernel::new(rule)?;
+        Some(Self(
+            (0..=u16::MAX).map(|block| lookup.evolve(block)).collect(),
+        ))
+    }
+
+    fn name(&self) -> &'static str {
+        "table"
+    }
+
+    fn evolve(&self, block: u16) -> u8 {
+        self.0[usize::from(block)]
+    }
+}
+
+// counts the neighbors of all cells at once with bitwise adders, for totalistic rules
+#[derive(Debug, Clone)]
+pub struct BitboardKernel {
+    // bit n is set if cells with n neighbors are born or survive
+    birth: u16,
+    survival: u16,
+}
+
+impl Kernel for BitboardKernel {
+    /// ```
+    /// use lifeash::{kernels::{BitboardKernel, Kernel}, Rule};
+    ///
+    /// let kernel = BitboardKernel::new(Rule::CONWAY).unwrap();
+    /// assert_eq!(kernel.evolve(0b0000_1110_0000_0000), 0b1010);
+    /// assert!(BitboardKernel::new("B2-a/S12".parse().unwrap()).is_none());
+    /// ```
+    fn new(rule: Rule) -> Option<Self> {
+        if !rule.is_totalistic() {
+            return None;
+        }
+        let mask = |condition: fn(Rule, u32) -> bool| {
+            (0..9).fold(0, |mask, n| mask | u16::from(condition(rule, n)) << n)
+        };
+        Some(Self {
+            birth: mask(Rule::birth),
+            survival: mask(Rule::survival),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "bitboard"
+    }
+
+    fn evolve(&self, block: u16) -> u8 {
+        // the neighbors in each direction moved onto the cells, the borders of the block are
+        // garbage but only the centers are read
+        let neighbors = [
+            block >> 4,
+            block >> 3,
+            block << 1,
+            block << 5,
+            block << 4,
+            block << 3,
+            block >> 1,
+            block >> 5,
+        ];
+        // the four bits of the neighbor count of every cell
+        let mut count = [0u16; 4];
+        for &plane in &neighbors {
+            let mut carry = plane;
+            for bit in &mut count {
+                let sum = *bit ^ carry;
+                carry &= *bit;
+                *bit = sum;
+            }
+        }
+        let mut alive = 0u16;
+        for n in 0..9 {
+            let equal = count.iter().enumerate().fold(!0u16, |equal, (i, &bit)| {
+                equal & if n >> i & 1 == 1 { bit } else { !bit }
+            });
+            if self.birth >> n & 1 == 1 {
+                alive |= equal & !block;
+            }
+            if self.survival >> n & 1 == 1 {
+                alive |= equal & block;
+            }
+        }
+        CENTERS.iter().fold(0, |result, &(center, bit)| {
+            result | (((alive >> center) & 1) as u8) << bit
+        })
+    }
+}
+
+// counts the neighbors of each center with the popcount instruction, for totalistic rules on
+// cpus which have one
+#[derive(Debug, Clone)]
+pub struct PopcountKernel {
+    birth: u16,
+    survival: u16,
+}
+
+impl PopcountKernel {
+    #[inline(always)]
+    fn evolve_portable(&self, block: u16) -> u8 {
+        CENTERS
+            .iter()
+            .zip(&NEIGHBORS)
+            .fold(0, |result, (&(center, bit), &neighbors)| {
+                let count = (block & neighbors).count_ones();
+                let conditions = if (block >> center) & 1 == 1 {
+                    self.survival
+                } else {
+                    self.birth
+                };
+                result | (((conditions >> count) & 1) as u8) << bit
+            })
+    }
+
+    // compiled with the instruction enabled, `new` makes sure the cpu supports it
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "popcnt")]
+    unsafe fn evolve_popcnt(&self, block: u16) -> u8 {
+        self.evolve_portable(block)
+    }
+}
+
+impl Kernel for PopcountKernel {
+    /// ```
+    /// use lifeash::{kernels::{self, Kernel, PopcountKernel}, Rule};
+    ///
+    /// if let Some(kernel) = PopcountKernel::new(Rule::CONWAY) {
+    ///     assert_eq!(kernel.evolve(0b0000_1110_0000_0000), 0b1010);
+    /// } else {
+    ///     assert_eq!(kernels::cpu_feature(), None);
+    /// }
+    /// ```
+    fn new(rule: Rule) -> Option<Self> {
+        let bitboard = BitboardKernel::new(rule)?;
+        cpu_feature()?;
+        Some(Self {
+            birth: bitboard.birth,
+            survival: bitboard.survival,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "popcount"
+    }
+
+    fn evolve(&self, block: u16) -> u8 {
+        #[cfg(target_arch = "x86_64")]
+        // safe because the kernel only exists if the cpu has the instruction
+        unsafe {
+            self.evolve_popcnt(block)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        self.evolve_portable(block)
+    }
+}
+
+/// the cpu feature the popcount kernel uses, detected at runtime, `None` if it is missing
+///
+/// ```
+/// use lifeash::kernels;
+///
+/// if let Some(feature) = kernels::cpu_feature() {
+///     assert!(["popcnt", "neon"].contains(&feature));
+/// }
+/// ```
+pub fn cpu_feature() -> Option<&'static str> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("popcnt") {
+            return Some("popcnt");
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return Some("neon");
+        }
+    }
+    None
+}
+
+/// which kernel a universe evolves its leaves with
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum KernelKind {
+    // the best kernel for the rule and the cpu
+    #[default]
+    Auto,
+    Lookup,
+    Table,
+    Bitboard,
+    Popcount,
+}
+
+impl FromStr for KernelKind {
+    type Err = UnknownKernel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "lookup" => Ok(Self::Lookup),
+            "table" => Ok(Self::Table),
+            "bitboard" => Ok(Self::Bitboard),
+            "popcount" => Ok(Self::Popcount),
+            _ => Err(UnknownKernel(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for KernelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Lookup => "lookup",
+            Self::Table => "table",
+            Self::Bitboard => "bitboard",
+            Self::Popcount => "popcount",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKernel(pub String);
+
+impl fmt::Display for UnknownKernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown kernel '{}', expected auto, lookup, table, bitboard or popcount",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownKernel {}
+
+// the kernel a universe uses, dispatched without a virtual call in the innermost loop
+#[derive(Debug, Clone)]
+pub(crate) enum LeafKernel {
+    Lookup(LookupKernel),
+    Table(TableKernel),
+    Bitboard(BitboardKernel),
+    Popcount(PopcountKernel),
+}
+
+impl LeafKernel {
+    // falls back to the lookup kernel if the chosen one does not support the rule
+    pub(crate) fn new(kind: KernelKind, rule: Rule) -> Self {
+        let kernel = match kind {
+            // the bitboard is as fast as the table and does not build it for every universe
+            KernelKind::Auto => PopcountKernel::new(rule)
+                .map(Self::Popcount)
+                .or_else(|| BitboardKernel::new(rule).map(Self::Bitboard))
+                .or_else(|| TableKernel::new(rule).map(Self::Table)),
+            KernelKind::Table => TableKernel::new(rule).map(Self::Table),
+            KernelKind::Bitboard => BitboardKernel::new(rule).map(Self::Bitboard),
+            KernelKind::Popcount => PopcountKernel::new(rule).map(Self::Popcount),
+            KernelKind::Lookup => None,
+        };
+        kernel.unwrap_or_else(|| Self::Lookup(LookupKernel::new(rule).unwrap()))
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Lookup(kernel) => kernel.name(),
+            Self::Table(kernel) => kernel.name(),
+            Self::Bitboard(kernel) => kernel.name(),
+            Self::Popcount(kernel) => kernel.name(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn evolve(&self, block: u16) -> u8 {
+        match self {
+            Self::Lookup(kernel) => kernel.evolve(block),
+            Self::Table(kernel) => kernel.evolve(block),
+            Self::Bitboard(kernel) => kernel.evolve(block),
+            Self::Popcount(kernel) => kernel.evolve(block),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelMismatch {
+    pub kernel: &'static str,
+    pub block: u16,
+    pub expected: u8,
+    pub actual: u8,
+}
+
+impl fmt::Display for KernelMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} kernel evolves block {:#06x} to {:#06b} instead of {:#06b}",
+            self.kernel, self.block, self.actual, self.expected
+        )
+    }
+}
+
+impl Error for KernelMismatch {}
+
+/// compares every kernel which supports `rule` with the lookup kernel on all 4x4 blocks
+///
+/// ```
+/// use lifeash::{kernels, Rule};
+///
+/// assert_eq!(kernels::cross_check(Rule::CONWAY), Ok(()));
+/// ```
+pub fn cross_check(rule: Rule) -> Result<(), KernelMismatch> {
+    let reference = LookupKernel::new(rule).unwrap();
+    let mut kernels: Vec<Box<dyn Kernel>> = vec![Box::new(TableKernel::new(rule).unwrap())];
+    if let Some(bitboard) = BitboardKernel::new(rule) {
+        kernels.push(Box::new(bitboard));
+    }
+    if let Some(popcount) = PopcountKernel::new(rule) {
+        kernels.push(Box::new(popcount));
+    }
+    for block in 0..=u16::MAX {
+        let expected = reference.evolve(block);
+        for kernel in &kernels {
+            let actual = kernel.evolve(block);
+            if actual != expected {
+                return Err(KernelMismatch {
+                    kernel: kernel.name(),
+                    block,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,72 @@
+//! A hashlife implementation for life-like cellular automata.
+//!
+//! ```
+//! use lifeash::prelude::*;
+//!
+//! let mut universe = Universe::new();
+//! universe.set_cells([(0, 0), (1, 0), (2, 0)].map(|pos| (pos.into(), Cell::Alive)));
+//! universe.evolve();
+//! assert_eq!(universe.get_cell((1, -1)), Cell::Alive);
+//! ```
+//!
+//! # Stability
+//!
+//! Everything reachable from the crate root follows semantic versioning, checked with
+//! `cargo semver-checks` in CI. Errors, backends, kernels and the statistics are
+//! `#[non_exhaustive]`, so new variants and fields can come in minor releases. The optional
+//! features `serde`, `zip` and `image` are covered too, their dependencies are public.
+
+pub mod activity;
+pub mod analysis;
+pub mod boolean;
+pub mod cache;
+pub mod census;
 pub mod core;
+mod diff;
+mod envelope;
+pub mod format;
+mod freeze;
+pub mod grid;
+mod hashing;
+pub mod history;
+pub mod io;
+pub mod kernels;
+pub mod ltl;
+pub mod mask;
+pub mod mirror;
 pub mod node;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod pattern;
+pub mod patterns;
+#[cfg(feature = "serde")]
+mod portable;
+pub mod prelude;
+#[cfg(feature = "image")]
+mod render;
+pub mod rule;
+mod soup;
+pub mod store;
+mod table;
+pub mod timeline;
+pub mod transform;
 pub mod universe;
+mod wechsler;
 
 pub use crate::{
-    core::{Cell, Offset, Position},
-    universe::Universe,
+    activity::Activity,
+    boolean::SetOp,
+    cache::LevelStats,
+    core::{Cell, Offset, Position, Rect},
+    grid::BitGrid,
+    history::History,
+    kernels::KernelKind,
+    ltl::{Backend, LtlRule, Neighborhood},
+    mask::MaskOp,
+    mirror::Mirror,
+    pattern::{PasteMode, Pattern},
+    rule::Rule,
+    timeline::Timeline,
+    transform::Transform,
+    universe::{GcStats, Snapshot, Stats, Universe, UniverseBuilder},
 };
@@ -0,0 +1,342 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    core::{Cell, Level, Position},
+    node::Node,
+    rule::ParseRuleError,
+    universe::{Id, Universe},
+};
+
+// ranges above this make the leaves too large to evolve by brute force
+pub const MAX_RANGE: u8 = 16;
+
+// how the universe computes the next generation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Backend {
+    // the rule of the universe on the 3x3 neighborhood
+    #[default]
+    Life,
+    LargerThanLife(LtlRule),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    // the square of cells at most `range` away in both directions
+    Moore,
+    // the diamond of cells at most `range` steps away
+    VonNeumann,
+}
+
+// Larger than Life rule in Golly's notation, e.g. "R5,C0,M1,S34..58,B34..45,NM" for Bosco's rule
+// cells are born or survive if the alive cells in their neighborhood are within the ranges,
+// more than two states decay like Generations rules
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LtlRule {
+    range: u8,
+    states: u8,
+    // whether a cell counts itself
+    middle: bool,
+    survival: (u16, u16),
+    birth: (u16, u16),
+    neighborhood: Neighborhood,
+}
+
+impl LtlRule {
+    /// cells at most `range` away are neighbors
+    ///
+    /// ```
+    /// use lifeash::LtlRule;
+    ///
+    /// let bosco: LtlRule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+    /// assert_eq!(bosco.range(), 5);
+    /// ```
+    pub fn range(self) -> u8 {
+        self.range
+    }
+
+    /// the shape of the neighborhood
+    ///
+    /// ```
+    /// use lifeash::{LtlRule, Neighborhood};
+    ///
+    /// let rule: LtlRule = "R2,C0,M0,S2..4,B3..3,NN".parse().unwrap();
+    /// assert_eq!(rule.neighborhood(), Neighborhood::VonNeumann);
+    /// ```
+    pub fn neighborhood(self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// number of cell states, 2 unless cells decay through dying states
+    ///
+    /// ```
+    /// use lifeash::LtlRule;
+    ///
+    /// let rule: LtlRule = "R3,C4,M0,S5..9,B6..8,NM".parse().unwrap();
+    /// assert_eq!(rule.states(), 4);
+    /// ```
+    pub fn states(self) -> u8 {
+        self.states
+    }
+
+    /// the state of a cell in the next generation, `alive` counts the alive cells of the
+    /// neighborhood, including the cell itself
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, LtlRule};
+    ///
+    /// let conway: LtlRule = "R1,C0,M1,S3..4,B3..3,NM".parse().unwrap();
+    /// assert_eq!(conway.next_cell(Cell::Dead, 3), Cell::Alive);
+    /// assert_eq!(conway.next_cell(Cell::Alive, 4), Cell::Alive);
+    /// assert_eq!(conway.next_cell(Cell::Alive, 5), Cell::Dead);
+    /// ```
+    pub fn next_cell(self, center: Cell, alive: u16) -> Cell {
+        let count = if self.middle || center != Cell::Alive {
+            alive
+        } else {
+            alive - 1
+        };
+        let within = |(min, max): (u16, u16)| (min..=max).contains(&count);
+        match center {
+            Cell::Dead if within(self.birth) => Cell::Alive,
+            Cell::Dead => Cell::Dead,
+            Cell::Alive if within(self.survival) => Cell::Alive,
+            Cell::Alive | Cell::Dying(_) => {
+                let next = center.state() + 1;
+                if next < self.states {
+                    Cell::Dying(next)
+                } else {
+                    Cell::Dead
+                }
+            }
+        }
+    }
+
+    // cells of the neighborhood, including the middle one
+    fn size(range: u8, neighborhood: Neighborhood) -> u16 {
+        let range = u16::from(range);
+        match neighborhood {
+            Neighborhood::Moore => (2 * range + 1) * (2 * range + 1),
+            Neighborhood::VonNeumann => 2 * range * (range + 1) + 1,
+        }
+    }
+}
+
+// accepts the parts in any order, "C" and "M" default to 0 and "N" to the Moore neighborhood
+impl FromStr for LtlRule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseRuleError::Empty);
+        }
+        let invalid = || ParseRuleError::InvalidLargerThanLife(s.to_owned());
+        let number = |n: &str| n.parse::<u16>().map_err(|_| invalid());
+        let interval = |part: &str| -> Result<(u16, u16), ParseRuleError> {
+            let (min, max) = part.split_once("..").ok_or_else(invalid)?;
+            Ok((number(min)?, number(max)?))
+        };
+
+        let (mut range, mut states, mut middle) = (None, 0, false);
+        let (mut survival, mut birth) = (None, None);
+        let mut neighborhood = Neighborhood::Moore;
+        for part in s.split(',') {
+            let part = part.trim();
+            let mut chars = part.chars();
+            let key = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+            let value = chars.as_str();
+            match key {
+                'R' => range = Some(number(value)?),
+                'C' => states = number(value)?,
+                'M' => {
+                    middle = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid()),
+                    }
+                }
+                'S' => survival = Some(interval(value)?),
+                'B' => birth = Some(interval(value)?),
+                'N' => {
+                    neighborhood = match value {
+                        "M" | "m" => Neighborhood::Moore,
+                        "N" | "n" => Neighborhood::VonNeumann,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        let range = match range {
+            Some(range) if (1..=u16::from(MAX_RANGE)).contains(&range) => range as u8,
+            _ => return Err(invalid()),
+        };
+        let states = match states {
+            0..=2 => 2,
+            3..=255 => states as u8,
+            _ => return Err(ParseRuleError::InvalidStateCount(states.to_string())),
+        };
+        let (survival, birth) = match (survival, birth) {
+            (Some(survival), Some(birth)) => (survival, birth),
+            _ => return Err(invalid()),
+        };
+        let size = Self::size(range, neighborhood);
+        if [survival, birth]
+            .iter()
+            .any(|&(min, max)| min > max || max > size)
+        {
+            return Err(invalid());
+        }
+        if birth.0 == 0 {
+            return Err(ParseRuleError::BirthOnZero);
+        }
+        Ok(Self {
+            range,
+            states,
+            middle,
+            survival,
+            birth,
+            neighborhood,
+        })
+    }
+}
+
+impl fmt::Display for LtlRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "R{},C{},M{},S{}..{},B{}..{},N{}",
+            self.range,
+            if self.states > 2 { self.states } else { 0 },
+            u8::from(self.middle),
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1,
+            match self.neighborhood {
+                Neighborhood::Moore => 'M',
+                Neighborhood::VonNeumann => 'N',
+            }
+        )
+    }
+}
+
+// written in Golly's notation like `Rule`
+#[cfg(feature = "serde")]
+impl serde::Serialize for LtlRule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LtlRule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rulestring = String::deserialize(deserializer)?;
+        rulestring.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Universe {
+    // the level of the nodes which are evolved by brute force, one generation at a time
+    // their center is at least `range` cells away from the border
+    pub(crate) fn leaf_evolve_level(&self) -> u8 {
+        match self.backend() {
+            Backend::Life => 2,
+            Backend::LargerThanLife(rule) => {
+                let range = u32::from(rule.range());
+                2 + (32 - (range - 1).leading_zeros()) as u8
+            }
+        }
+    }
+
+    // evolves the center of a node of `leaf_evolve_level` by one generation
+    pub(crate) fn convolve(&mut self, rule: LtlRule, tree: Id) -> Id {
+        let level = tree.node(self).level();
+        if tree.node(self).population() == 0 {
+            return self.new_empty_tree(level - 1);
+        }
+        let side = level.side_len() as usize;
+        let mut cells = vec![Cell::Dead; side * side];
+        self.read_tree(tree, Position::new(0, 0), side, &mut cells);
+
+        // summed area table of the alive cells, with an extra row and column of zeros
+        let mut sums = vec![0u16; (side + 1) * (side + 1)];
+        for y in 0..side {
+            for x in 0..side {
+                sums[(y + 1) * (side + 1) + x + 1] = u16::from(cells[y * side + x] == Cell::Alive)
+                    + sums[y * (side + 1) + x + 1]
+                    + sums[(y + 1) * (side + 1) + x]
+                    - sums[y * (side + 1) + x];
+            }
+        }
+        // alive cells of the rectangle from (x0, y0) up to but excluding (x1, y1)
+        let area = |x0: usize, y0: usize, x1: usize, y1: usize| {
+            sums[y1 * (side + 1) + x1] + sums[y0 * (side + 1) + x0]
+                - sums[y0 * (side + 1) + x1]
+                - sums[y1 * (side + 1) + x0]
+        };
+
+        let range = usize::from(rule.range());
+        let half = side / 2;
+        let mut next = vec![Cell::Dead; half * half];
+        for y in 0..half {
+            for x in 0..half {
+                let (cx, cy) = (x + half / 2, y + half / 2);
+                let alive = match rule.neighborhood() {
+                    Neighborhood::Moore => {
+                        area(cx - range, cy - range, cx + range + 1, cy + range + 1)
+                    }
+                    // one row at a time, narrowing away from the center
+                    Neighborhood::VonNeumann => (cy - range..=cy + range)
+                        .map(|row| {
+                            let reach = range - (row as isize - cy as isize).unsigned_abs();
+                            area(cx - reach, row, cx + reach + 1, row + 1)
+                        })
+                        .sum(),
+                };
+                next[y * half + x] = rule.next_cell(cells[cy * side + cx], alive);
+            }
+        }
+        self.build_tree(&next, half, Position::new(0, 0), level - 1)
+    }
+
+    // copies the cells of `tree` into `cells`, a row major grid with rows of `side` cells
+    fn read_tree(&self, tree: Id, min: Position, side: usize, cells: &mut [Cell]) {
+        match tree.node(self) {
+            Node::Leaf(leaf) => cells[min.y as usize * side + min.x as usize] = leaf.0,
+            Node::Inode(inode) if inode.population > 0 => {
+                let half = inode.level.side_len() as i64 / 2;
+                for (quadrant, (dx, dy)) in [
+                    (inode.nw, (0, 0)),
+                    (inode.ne, (half, 0)),
+                    (inode.sw, (0, half)),
+                    (inode.se, (half, half)),
+                ] {
+                    self.read_tree(quadrant, Position::new(min.x + dx, min.y + dy), side, cells);
+                }
+            }
+            Node::Inode(_) => {}
+        }
+    }
+
+    // the tree of `level` with the cells of the grid starting at `min`
+    fn build_tree(&mut self, cells: &[Cell], side: usize, min: Position, level: Level) -> Id {
+        if level == 0 {
+            return self.new_leaf(cells[min.y as usize * side + min.x as usize]);
+        }
+        let half = level.side_len() as i64 / 2;
+        let nw = self.build_tree(cells, side, min, level - 1);
+        let ne = self.build_tree(cells, side, Position::new(min.x + half, min.y), level - 1);
+        let sw = self.build_tree(cells, side, Position::new(min.x, min.y + half), level - 1);
+        let se = self.build_tree(
+            cells,
+            side,
+            Position::new(min.x + half, min.y + half),
+            level - 1,
+        );
+        self.new_inode(nw, ne, sw, se)
+    }
+}
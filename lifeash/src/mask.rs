@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::Cell,
+    node::{Inode, Node},
+    universe::{Id, Universe},
+};
+
+// the live cells of the mask universe mark the region "inside" the mask
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MaskOp {
+    ClearOutside,
+    ClearInside,
+    FillInside,
+}
+
+impl Universe {
+    /// combines the live cells with the region marked by `mask`
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, MaskOp};
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.set_cell((0, 0), Cell::Alive);
+    /// universe.set_cell((5, 5), Cell::Alive);
+    /// let mut mask = Universe::new();
+    /// mask.map_region(Rect::new((-1, -1), (1, 1)), |_, _| Cell::Alive);
+    ///
+    /// universe.apply_mask(&mask, MaskOp::ClearOutside);
+    /// assert_eq!(universe.live_cells().collect::<Vec<_>>(), [Position::ORIGIN]);
+    /// ```
+    pub fn apply_mask(&mut self, mask: &Universe, op: MaskOp) {
+        self.record_edit();
+        let (root, mask) = self.align_with_root(mask);
+        let root = self.mask_tree(root, mask, op, &mut HashMap::new());
+        self.set_root(root);
+    }
+
+    // combines two trees of the same level, uniform subtrees are resolved without descending
+    fn mask_tree(
+        &mut self,
+        tree: Id,
+        mask: Id,
+        op: MaskOp,
+        memo: &mut HashMap<(Id, Id), Id>,
+    ) -> Id {
+        let (tree_node, mask_node) = (tree.node(self), mask.node(self));
+        let level = tree_node.level();
+        let (tree_empty, mask_empty, mask_full) = (
+            tree_node.population() == 0,
+            mask_node.population() == 0,
+            mask_node.is_full(),
+        );
+
+        match op {
+            MaskOp::ClearOutside if mask_empty => return self.new_empty_tree(level),
+            MaskOp::ClearOutside if tree_empty || mask_full || tree == mask => return tree,
+            MaskOp::ClearInside if mask_empty || tree_empty => return tree,
+            MaskOp::ClearInside if mask_full || tree == mask => return self.new_empty_tree(level),
+            MaskOp::FillInside if mask_empty || tree == mask => return tree,
+            MaskOp::FillInside if tree_empty => return mask,
+            _ => {}
+        }
+        if let Some(&id) = memo.get(&(tree, mask)) {
+            return id;
+        }
+
+        let id = match (tree_node, mask_node) {
+            // cells in any state but dead are kept as they are, filled cells take the state of
+            // the mask like the shortcuts above
+            (Node::Leaf(cell), Node::Leaf(fill)) => {
+                let (cell, fill) = (cell.0, fill.0);
+                let (alive, inside) = (cell != Cell::Dead, fill != Cell::Dead);
+                let cell = match op {
+                    MaskOp::ClearOutside if alive && inside => cell,
+                    MaskOp::ClearInside if alive && !inside => cell,
+                    MaskOp::FillInside if alive => cell,
+                    MaskOp::FillInside => fill,
+                    _ => Cell::Dead,
+                };
+                self.new_leaf(cell)
+            }
+            (Node::Inode(t), Node::Inode(m)) => {
+                let (Inode { nw, ne, sw, se, .. }, m) = (t.clone(), m.clone());
+                let nw = self.mask_tree(nw, m.nw, op, memo);
+                let ne = self.mask_tree(ne, m.ne, op, memo);
+                let sw = self.mask_tree(sw, m.sw, op, memo);
+                let se = self.mask_tree(se, m.se, op, memo);
+                self.new_inode(nw, ne, sw, se)
+            }
+            _ => unreachable!("trees must have the same level"),
+        };
+        memo.insert((tree, mask), id);
+        id
+    }
+}
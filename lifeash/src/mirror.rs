@@ -0,0 +1,86 @@
+use crate::{core::Rect, universe::Universe};
+
+// a copy of a region of another universe which follows it generation by generation,
+// e.g. for a close-up next to a zoomed out view
+// the copy is small and standalone, so it can be drawn or inspected without the source
+pub struct Mirror {
+    rect: Rect,
+    universe: Universe,
+}
+
+impl Mirror {
+    /// an empty mirror of `rect`, filled by the first `update`
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Mirror};
+    ///
+    /// let mirror = Mirror::new(Rect::new((0, 0), (9, 9)));
+    /// assert_eq!(mirror.universe().population(), 0);
+    /// ```
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            universe: Universe::new(),
+        }
+    }
+
+    /// the mirrored region of the source
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Mirror};
+    ///
+    /// let rect = Rect::new((-5, -5), (5, 5));
+    /// assert_eq!(Mirror::new(rect).rect(), rect);
+    /// ```
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// mirrors another region from the next `update` on
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Mirror};
+    ///
+    /// let source = Universe::from_rle("o7bo!").unwrap();
+    /// let mut mirror = Mirror::new(Rect::new((0, 0), (3, 3)));
+    /// mirror.update(&source);
+    /// mirror.set_rect(Rect::new((6, 0), (9, 3)));
+    /// mirror.update(&source);
+    /// assert_eq!(mirror.universe().live_cells().collect::<Vec<_>>(), [Position::new(2, 0)]);
+    /// ```
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    /// copies the current state of the region, with its north west corner at the origin
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Mirror};
+    ///
+    /// let mut source = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let mut mirror = Mirror::new(Rect::new((0, 0), (19, 19)));
+    /// for _ in 0..8 {
+    ///     source.evolve();
+    ///     mirror.update(&source);
+    /// }
+    /// assert_eq!(mirror.universe().generation(), 8);
+    /// assert_eq!(mirror.universe().bounding_box(), Some(((2, 2).into(), (4, 4).into())));
+    /// ```
+    pub fn update(&mut self, source: &Universe) {
+        self.universe = source.region_copy(self.rect);
+    }
+
+    /// the copy of the region as of the last update
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Mirror};
+    ///
+    /// let source = Universe::from_rle("2o$2o!").unwrap();
+    /// let mut mirror = Mirror::new(Rect::new((1, 0), (1, 5)));
+    /// mirror.update(&source);
+    /// assert_eq!(mirror.universe().population(), 2);
+    /// ```
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+}
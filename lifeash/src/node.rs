@@ -16,8 +16,14 @@ pub(crate) enum Node {
 #[derive(Debug, Clone)]
 pub(crate) struct Inode {
     pub(crate) level: Level,
-    pub(crate) population: u32,
+    // cells which are not dead, dying cells included
+    pub(crate) population: u128,
+    // center advanced by the full step of the level, 2^(level - leaf) generations where leaf is
+    // the level evolved by brute force
     pub(crate) result: Option<Id>,
+    // center advanced by 2^step generations as (step, result) for a smaller step, kept next to
+    // the full one so that changing the step size doesn't throw the cache away
+    pub(crate) slow_result: Option<(u8, Id)>,
     pub(crate) nw: Id,
     pub(crate) ne: Id,
     pub(crate) sw: Id,
@@ -43,6 +49,23 @@ impl Hash for Inode {
     }
 }
 
+impl Inode {
+    pub(crate) fn has_result(&self) -> bool {
+        self.result.is_some() || self.slow_result.is_some()
+    }
+
+    pub(crate) fn results(&self) -> impl Iterator<Item = Id> {
+        self.result
+            .into_iter()
+            .chain(self.slow_result.map(|(_, result)| result))
+    }
+
+    pub(crate) fn clear_results(&mut self) {
+        self.result = None;
+        self.slow_result = None;
+    }
+}
+
 impl From<Leaf> for Node {
     fn from(cell: Leaf) -> Self {
         Node::Leaf(cell)
@@ -62,22 +85,24 @@ impl Leaf {
 
     #[allow(dead_code)]
     fn alive(self) -> bool {
-        match self.0 {
-            Cell::Dead => false,
-            Cell::Alive => true,
-        }
+        matches!(self.0, Cell::Alive)
     }
 }
 
 impl Node {
     #[inline(always)]
-    pub(crate) fn population(&self) -> u32 {
+    pub(crate) fn population(&self) -> u128 {
         match *self {
             Node::Inode(ref i) => i.population,
-            Node::Leaf(c) => c.0 as u32,
+            Node::Leaf(c) => u128::from(c.0 != Cell::Dead),
         }
     }
 
+    pub(crate) fn is_full(&self) -> bool {
+        let side = u128::from(self.level().side_len());
+        self.population() == side * side
+    }
+
     #[inline(always)]
     pub(crate) fn level(&self) -> Level {
         match *self {
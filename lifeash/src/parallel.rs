@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::universe::{Id, Universe, UniverseBuilder, Worker};
+
+// trees of this level and above are evolved concurrently, below the work does not pay for
+// copying the trees between the universes
+const PARALLEL_LEVEL: u8 = 12;
+// the nine trees of the first stage, the four of the second reuse the first workers
+const WORKERS: usize = 9;
+
+impl Universe {
+    // the workers recompute the parts their trees overlap in, which only pays off with
+    // several threads
+    pub(crate) fn evolves_concurrently(&self, tree: Id) -> bool {
+        self.parallel
+            && rayon::current_num_threads() > 1
+            && tree.inode(self).level >= PARALLEL_LEVEL
+    }
+
+    // evolves every tree in a worker universe of its own and copies the results back
+    // the n-th tree always goes to the n-th worker, which covers the same part of the root
+    // from step to step and so finds its results in its own cache, and most of the nodes of
+    // the tree already copied
+    pub(crate) fn evolve_concurrently<const N: usize>(
+        &mut self,
+        trees: [Id; N],
+        step_log: u8,
+    ) -> [Id; N] {
+        let level = trees[0].inode(self).level;
+        let step_log = step_log.min(level.value() - self.leaf_evolve_level());
+        let mut workers = std::mem::take(&mut self.workers);
+        while workers.len() < N {
+            workers.push(self.worker());
+        }
+
+        let mut jobs = Vec::new();
+        let mut results = [None; N];
+        for ((tree, result), worker) in trees.iter().zip(&mut results).zip(&mut workers) {
+            match self.cached_result(*tree, step_log) {
+                Some(cached) => {
+                    self.count_lookup(level, true);
+                    *result = Some(cached);
+                }
+                None => {
+                    worker.forget_collected(self);
+                    let copy = worker
+                        .universe
+                        .import_tree(self, *tree, &mut worker.imported);
+                    jobs.push((worker, copy, *tree, result));
+                }
+            }
+        }
+
+        let evolved: Vec<_> = jobs
+            .par_iter_mut()
+            .map(|(worker, copy, _, _)| {
+                let universe = &mut worker.universe;
+                // the root keeps the tree and so its result alive through garbage collection
+                universe.set_root(*copy);
+                let result = universe.evolve_tree(*copy, step_log);
+                universe.collect_garbage_if_needed();
+                result
+            })
+            .collect();
+
+        for ((worker, _, tree, result), evolved) in jobs.into_iter().zip(evolved) {
+            worker.forget_collected(self);
+            let copy = self.import_tree(&worker.universe, evolved, &mut worker.exported);
+            // the result may be part of the next tree
+            worker.imported.insert(copy, evolved);
+            self.cache_result(tree, step_log, copy);
+            *result = Some(copy);
+            let stats = &mut worker.universe.stats;
+            self.stats.cache_hits += std::mem::take(&mut stats.cache_hits);
+            self.stats.cache_misses += std::mem::take(&mut stats.cache_misses);
+            for (cache, counted) in self.levels.iter_mut().zip(&mut worker.universe.levels) {
+                cache.hits += std::mem::take(&mut counted.hits);
+                cache.misses += std::mem::take(&mut counted.misses);
+            }
+        }
+        self.workers = workers;
+        results.map(Option::unwrap)
+    }
+
+    // the workers share the memory budget of the universe
+    fn worker(&self) -> Worker {
+        let mut universe = UniverseBuilder::new()
+            .rule(self.rule())
+            .backend(self.backend())
+            .parallel(false)
+            .kernel(self.kernel_kind)
+            .build();
+        universe.gc_threshold = self.gc_threshold.map(|threshold| threshold / WORKERS);
+        for (cache, limited) in universe.levels.iter_mut().zip(&self.levels) {
+            cache.limit = limited.limit;
+        }
+        Worker {
+            universe,
+            imported: HashMap::new(),
+            exported: HashMap::new(),
+            collections: (0, 0),
+        }
+    }
+}
+
+impl Worker {
+    // the copied nodes are only known until either universe collects garbage
+    fn forget_collected(&mut self, universe: &Universe) {
+        let collections = (
+            universe.gc_stats().collections,
+            self.universe.gc_stats().collections,
+        );
+        if collections != self.collections {
+            self.imported.clear();
+            self.exported.clear();
+            self.collections = collections;
+        }
+    }
+}
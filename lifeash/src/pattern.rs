@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use crate::{
+    core::{Cell, Position, Rect},
+    rule::Rule,
+    universe::Universe,
+};
+
+// a format independent set of live cells which can be stamped into a universe
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub comments: Vec<String>,
+    pub rule: Option<Rule>,
+    // relative to the origin of the pattern, may be negative
+    pub cells: Vec<Position>,
+}
+
+impl Pattern {
+    /// the smallest rectangle containing all cells, relative to the origin of the pattern
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let pattern = Pattern {
+    ///     cells: vec![Position::new(1, 0), Position::new(-2, 2)],
+    ///     ..Pattern::default()
+    /// };
+    /// assert_eq!(pattern.bounds(), Some(Rect::new((-2, 0), (1, 2))));
+    /// assert_eq!(Pattern::default().bounds(), None);
+    /// ```
+    pub fn bounds(&self) -> Option<Rect> {
+        match (
+            self.cells.iter().map(|pos| pos.x).min(),
+            self.cells.iter().map(|pos| pos.y).min(),
+            self.cells.iter().map(|pos| pos.x).max(),
+            self.cells.iter().map(|pos| pos.y).max(),
+        ) {
+            (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) => {
+                Some(Rect::new((min_x, min_y), (max_x, max_y)))
+            }
+            _ => None,
+        }
+    }
+}
+
+// how pasted cells are combined with the cells already in the universe
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PasteMode {
+    // the bounding box of the pattern replaces what was there
+    Overwrite,
+    Or,
+    Xor,
+}
+
+impl Universe {
+    /// places the origin of the pattern at `pos`, the rule of the universe is kept
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let pattern = Pattern {
+    ///     cells: vec![Position::new(0, 0), Position::new(1, 0)],
+    ///     ..Pattern::default()
+    /// };
+    /// let mut universe = Universe::new();
+    /// universe.stamp(&pattern, (10, -3));
+    /// assert_eq!(universe.get_cell((11, -3)), Cell::Alive);
+    /// assert_eq!(universe.population(), 2);
+    /// ```
+    pub fn stamp(&mut self, pattern: &Pattern, pos: impl Into<Position>) {
+        let pos = pos.into();
+        self.set_cells(
+            pattern
+                .cells
+                .iter()
+                .map(|cell| (Position::new(pos.x + cell.x, pos.y + cell.y), Cell::Alive)),
+        );
+    }
+
+    /// the live cells within `rect`, relative to its north west corner
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("3o$3o!").unwrap();
+    /// let pattern = universe.extract(Rect::new((1, 1), (5, 5)));
+    /// assert_eq!(pattern.cells, [(0, 0), (1, 0)].map(Position::from));
+    /// assert_eq!(pattern.rule, Some(Rule::CONWAY));
+    /// ```
+    pub fn extract(&self, rect: Rect) -> Pattern {
+        let mut cells: Vec<Position> = self
+            .live_cells_in(rect)
+            .map(|pos| Position::new(pos.x - rect.min.x, pos.y - rect.min.y))
+            .collect();
+        cells.sort_by_key(|pos| (pos.y, pos.x));
+        Pattern {
+            rule: Some(self.rule()),
+            cells,
+            ..Pattern::default()
+        }
+    }
+
+    /// places the origin of the pattern at `pos` and combines it with the existing cells
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, PasteMode};
+    ///
+    /// let domino = Universe::from_rle("2o!").unwrap().extract(Rect::new((0, 0), (1, 0)));
+    /// let mut universe = Universe::from_rle("o!").unwrap();
+    /// universe.paste(&domino, Position::ORIGIN, PasteMode::Xor);
+    /// assert_eq!(universe.live_cells().collect::<Vec<_>>(), [Position::new(1, 0)]);
+    /// ```
+    pub fn paste(&mut self, pattern: &Pattern, pos: impl Into<Position>, mode: PasteMode) {
+        let pos = pos.into();
+        let cells: HashSet<Position> = pattern
+            .cells
+            .iter()
+            .map(|cell| Position::new(pos.x + cell.x, pos.y + cell.y))
+            .collect();
+        let bounds = match pattern.bounds() {
+            Some(bounds) => Rect::new(
+                (bounds.min.x + pos.x, bounds.min.y + pos.y),
+                (bounds.max.x + pos.x, bounds.max.y + pos.y),
+            ),
+            None => return,
+        };
+        match mode {
+            PasteMode::Overwrite => self.map_region(bounds, |pos, _| {
+                if cells.contains(&pos) {
+                    Cell::Alive
+                } else {
+                    Cell::Dead
+                }
+            }),
+            PasteMode::Or => self.stamp(pattern, pos),
+            PasteMode::Xor => {
+                self.map_region(
+                    bounds,
+                    |pos, cell| {
+                        if cells.contains(&pos) {
+                            !cell
+                        } else {
+                            cell
+                        }
+                    },
+                )
+            }
+        }
+    }
+}
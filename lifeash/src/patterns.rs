@@ -0,0 +1,84 @@
+// a small catalog of classic patterns, embedded as RLE files from the patterns directory
+
+use crate::{io::rle, pattern::Pattern};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: &'static str,
+    pub rle: &'static str,
+}
+
+const CATALOG: &[Entry] = &[
+    Entry {
+        name: "glider",
+        rle: include_str!("../patterns/glider.rle"),
+    },
+    Entry {
+        name: "lwss",
+        rle: include_str!("../patterns/lwss.rle"),
+    },
+    Entry {
+        name: "gosper glider gun",
+        rle: include_str!("../patterns/gosper_glider_gun.rle"),
+    },
+    Entry {
+        name: "r-pentomino",
+        rle: include_str!("../patterns/r_pentomino.rle"),
+    },
+    Entry {
+        name: "acorn",
+        rle: include_str!("../patterns/acorn.rle"),
+    },
+    Entry {
+        name: "diehard",
+        rle: include_str!("../patterns/diehard.rle"),
+    },
+];
+
+/// all embedded patterns
+///
+/// ```
+/// use lifeash::{patterns, prelude::*};
+///
+/// let mut universe = Universe::new();
+/// for (i, entry) in patterns::catalog().iter().enumerate() {
+///     universe.stamp(&entry.pattern(), (100 * i as i64, 0));
+/// }
+/// assert!(universe.population() > 0);
+/// ```
+pub fn catalog() -> &'static [Entry] {
+    CATALOG
+}
+
+/// looks up an entry by its name
+///
+/// ```
+/// use lifeash::{patterns, prelude::*};
+///
+/// let gun = patterns::get("gosper glider gun").unwrap().pattern();
+/// let mut universe = Universe::new();
+/// universe.stamp(&gun, Position::ORIGIN);
+/// universe.advance(30);
+/// // the gun is back in its initial phase and one glider was emitted
+/// assert_eq!(universe.population(), gun.cells.len() as u128 + 5);
+/// ```
+pub fn get(name: &str) -> Option<&'static Entry> {
+    CATALOG.iter().find(|entry| entry.name == name)
+}
+
+impl Entry {
+    /// parses the embedded RLE, which is known to be valid
+    ///
+    /// ```
+    /// use lifeash::patterns;
+    ///
+    /// let glider = patterns::get("glider").unwrap().pattern();
+    /// assert_eq!(glider.name.as_deref(), Some("Glider"));
+    /// assert_eq!(glider.cells.len(), 5);
+    /// ```
+    pub fn pattern(&self) -> Pattern {
+        rle::parse(self.rle)
+            .map(Pattern::from)
+            .unwrap_or_else(|err| panic!("embedded pattern {} is invalid: {}", self.name, err))
+    }
+}
@@ -0,0 +1,117 @@
+// serde support for universes, which are written as their rule, generation, frozen regions and the
+// nodes of their tree, so that the sharing of the tree survives and huge patterns stay small
+// the root is centered on the origin, like in memory
+
+use std::collections::HashMap;
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    core::{Cell, Level, Rect},
+    ltl::{Backend, LtlRule},
+    node::Node,
+    rule::Rule,
+    universe::{Id, Universe},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Universe")]
+struct Portable {
+    rule: Rule,
+    // the rule the universe evolves by instead, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    larger_than_life: Option<LtlRule>,
+    generation: u128,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    frozen: Vec<Rect>,
+    // every node after its children, the root last
+    nodes: Vec<PortableNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Node")]
+enum PortableNode {
+    // the state of the cell
+    Leaf(u8),
+    // indices of the nw, ne, sw and se children into the nodes
+    Inode([u32; 4]),
+}
+
+impl Serialize for Universe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut indices = HashMap::new();
+        let mut order = Vec::new();
+        self.order_nodes(self.root(), false, &mut indices, &mut order);
+        let nodes = order
+            .iter()
+            .map(|&id| match id.node(self) {
+                Node::Leaf(leaf) => PortableNode::Leaf(leaf.0.state()),
+                Node::Inode(inode) => PortableNode::Inode(
+                    [inode.nw, inode.ne, inode.sw, inode.se].map(|child| indices[&child] as u32),
+                ),
+            })
+            .collect();
+        Portable {
+            rule: self.rule(),
+            larger_than_life: match self.backend() {
+                Backend::Life => None,
+                Backend::LargerThanLife(rule) => Some(rule),
+            },
+            generation: self.generation(),
+            frozen: self.frozen().to_vec(),
+            nodes,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Universe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let portable = Portable::deserialize(deserializer)?;
+        let (backend, states) = match portable.larger_than_life {
+            Some(rule) => (Backend::LargerThanLife(rule), rule.states()),
+            None => (Backend::Life, portable.rule.states()),
+        };
+        let mut universe = Universe::builder()
+            .rule(portable.rule)
+            .backend(backend)
+            .build();
+        universe.generation = portable.generation;
+        for rect in portable.frozen {
+            universe.freeze(rect);
+        }
+
+        let mut ids: Vec<Id> = Vec::with_capacity(portable.nodes.len());
+        for (index, node) in portable.nodes.into_iter().enumerate() {
+            let invalid = || D::Error::custom(format!("invalid node {}", index));
+            let id = match node {
+                PortableNode::Leaf(state) if state < states => {
+                    universe.new_leaf(Cell::from_state(state))
+                }
+                PortableNode::Leaf(_) => return Err(invalid()),
+                PortableNode::Inode(children) => {
+                    let [nw, ne, sw, se] =
+                        match children.map(|child| ids.get(child as usize).copied()) {
+                            [Some(nw), Some(ne), Some(sw), Some(se)] => [nw, ne, sw, se],
+                            _ => return Err(invalid()),
+                        };
+                    let level = nw.node(&universe).level();
+                    if level >= Level::MAX_LEVEL
+                        || [ne, sw, se]
+                            .iter()
+                            .any(|child| child.node(&universe).level() != level)
+                    {
+                        return Err(invalid());
+                    }
+                    universe.new_inode(nw, ne, sw, se)
+                }
+            };
+            ids.push(id);
+        }
+        match ids.last() {
+            Some(&root) if root.node(&universe).level() >= 3 => universe.set_root(root),
+            _ => return Err(D::Error::custom("the root is missing or below level 3")),
+        }
+        Ok(universe)
+    }
+}
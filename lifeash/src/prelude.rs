@@ -0,0 +1,16 @@
+//! The types needed for most uses of the library.
+//!
+//! ```
+//! use lifeash::prelude::*;
+//!
+//! let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+//! universe.advance(1 << 40);
+//! assert_eq!(universe.population(), 5);
+//! ```
+
+pub use crate::{
+    core::{Cell, Offset, Position, Rect},
+    pattern::Pattern,
+    rule::Rule,
+    universe::Universe,
+};
@@ -0,0 +1,124 @@
+// still images of the universe, drawn like the simulator does: dark cells on a light background,
+// dying cells fading out and blocks shaded by their density when cells are smaller than a pixel
+
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    core::{Cell, Position, Rect},
+    universe::Universe,
+};
+
+const BACKGROUND: [u8; 3] = [0xff, 0xff, 0xff];
+const LIVE: [u8; 3] = [0x10, 0x10, 0x10];
+
+impl Universe {
+    /// draws the cells within `rect`, `scale` is the side of a cell in pixels
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let image = universe.render_image(Rect::new((0, 0), (2, 2)), 4.0);
+    /// assert_eq!(image.dimensions(), (12, 12));
+    /// // the center of the top middle cell
+    /// assert_eq!(image.get_pixel(6, 2).0, [0x10, 0x10, 0x10, 0xff]);
+    /// assert_eq!(image.get_pixel(2, 2).0, [0xff; 4]);
+    /// ```
+    ///
+    /// below one pixel per cell, every pixel is shaded by the share of alive cells it covers
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.map_region(Rect::new((0, 0), (63, 63)), |pos, _| {
+    ///     if pos.x < 32 || (pos.x + pos.y) % 2 == 0 {
+    ///         Cell::Alive
+    ///     } else {
+    ///         Cell::Dead
+    ///     }
+    /// });
+    /// let image = universe.render_image(Rect::new((0, 0), (63, 63)), 1.0 / 32.0);
+    /// assert_eq!(image.dimensions(), (2, 2));
+    /// let (full, half) = (image.get_pixel(0, 0).0, image.get_pixel(1, 0).0);
+    /// assert_eq!(full, [0x10, 0x10, 0x10, 0xff]);
+    /// assert!(half[0] > full[0] && half[0] < 0xff);
+    /// ```
+    pub fn render_image(&self, rect: Rect, scale: f64) -> RgbaImage {
+        assert!(scale > 0.0, "the scale must be positive");
+        let width = pixels(rect.width(), scale);
+        let height = pixels(rect.height(), scale);
+        let mut canvas = Canvas {
+            rect,
+            scale,
+            width,
+            shades: vec![0.0; width as usize * height as usize],
+        };
+
+        if scale < 1.0 {
+            let level = (1.0 / scale).log2().ceil() as u8;
+            let side = 1i64 << level;
+            for (min, population) in self.node_population_in(rect, level) {
+                let density = population as f64 / (side * side) as f64;
+                canvas.fill(min, side, density.sqrt());
+            }
+        } else {
+            // dying cells of Generations rules fade out
+            let states = f64::from(self.rule().states());
+            for (pos, cell) in self.cells_in(rect) {
+                let shade = match cell {
+                    Cell::Dying(state) => (states - f64::from(state)) / (states - 1.0),
+                    _ => 1.0,
+                };
+                canvas.fill(pos, 1, shade);
+            }
+        }
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            let shade = canvas.shades[(y * width + x) as usize].min(1.0);
+            let mix = |i: usize| {
+                let (background, live) = (f64::from(BACKGROUND[i]), f64::from(LIVE[i]));
+                (background + (live - background) * shade).round() as u8
+            };
+            Rgba([mix(0), mix(1), mix(2), 0xff])
+        })
+    }
+}
+
+// pixels needed for `cells` cells, at least one
+fn pixels(cells: u64, scale: f64) -> u32 {
+    (cells as f64 * scale)
+        .ceil()
+        .clamp(1.0, f64::from(u32::MAX)) as u32
+}
+
+// how strongly every pixel is covered, from 0 to 1
+struct Canvas {
+    rect: Rect,
+    scale: f64,
+    width: u32,
+    shades: Vec<f64>,
+}
+
+impl Canvas {
+    // shades the pixels of the square of `side` cells at `min`, overlapping squares keep the
+    // darkest shade
+    fn fill(&mut self, min: Position, side: i64, shade: f64) {
+        let height = (self.shades.len() / self.width as usize) as i64;
+        let columns = self.span(min.x - self.rect.min.x, side, i64::from(self.width));
+        let rows = self.span(min.y - self.rect.min.y, side, height);
+        for y in rows {
+            for x in columns.clone() {
+                let pixel = &mut self.shades[(y * i64::from(self.width) + x) as usize];
+                *pixel = pixel.max(shade);
+            }
+        }
+    }
+
+    // the pixels covered by `len` cells starting `start` cells into the rect, never empty
+    fn span(&self, start: i64, len: i64, pixels: i64) -> std::ops::Range<i64> {
+        let first = (start as f64 * self.scale).floor() as i64;
+        let last = ((start + len) as f64 * self.scale).floor() as i64;
+        first.clamp(0, pixels - 1)..last.max(first + 1).clamp(1, pixels)
+    }
+}
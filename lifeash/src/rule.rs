@@ -0,0 +1,438 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::core::Cell;
+
+// isotropic rule in B/S notation, outer totalistic rules like B3/S23 are the special case
+// where every arrangement of n neighbors behaves the same
+// entry n of `birth`/`survival` has bit i set if a cell whose n alive neighbors are arranged
+// like letter i of `LETTERS[n]` in Hensel notation is born/survives
+// Generations rules have more than two states, cells which don't survive decay through the
+// dying states before they are dead, only alive cells count as neighbors
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: [u16; 9],
+    survival: [u16; 9],
+    states: u8,
+}
+
+// the arrangements of n neighbors up to rotation and reflection, in canonical order
+const LETTERS: [&str; 9] = [
+    "",
+    "ce",
+    "cekain",
+    "cekainyqjr",
+    "cekainyqjrtwz",
+    "cekainyqjr",
+    "cekain",
+    "ce",
+    "",
+];
+
+// all arrangements of n neighbors
+const FULL: [u16; 9] = [1, 0b11, 0x3f, 0x3ff, 0x1fff, 0x3ff, 0x3f, 0b11, 1];
+
+// one neighborhood per letter of up to 4 neighbors, bit 0 is the north neighbor and the
+// following bits go clockwise, arrangements of more neighbors are the complements of these
+const REPRESENTATIVES: [&[u8]; 5] = [
+    &[0],
+    &[0x02, 0x01],
+    &[0x0a, 0x05, 0x09, 0x03, 0x11, 0x22],
+    &[0x2a, 0x15, 0x25, 0x07, 0x83, 0x0b, 0x29, 0x23, 0x43, 0x13],
+    &[
+        0xaa, 0x55, 0x4b, 0x0f, 0x1b, 0x8b, 0x2b, 0x27, 0x53, 0x17, 0x93, 0x63, 0x33,
+    ],
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseRuleError {
+    Empty,
+    MissingSeparator,
+    InvalidNeighborCount(char),
+    // a letter which doesn't name an arrangement of the neighbor count before it
+    InvalidNeighborhood(String),
+    // a Larger than Life rule which is missing a part or has one out of range
+    InvalidLargerThanLife(String),
+    // Generations rules need between 2 and 255 states
+    InvalidStateCount(String),
+    // B0 rules make the infinite dead background flash, which hashlife can't represent
+    BirthOnZero,
+}
+
+impl Rule {
+    pub const CONWAY: Self = Self::from_masks(0b1000, 0b1100);
+
+    /// bit n of a mask is set if n alive neighbors lead to birth or survival, birth on 0 is
+    /// dropped like the parser rejects it, hashlife can't evolve such rules
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Rule::from_masks(1 << 3, 1 << 2 | 1 << 3), Rule::CONWAY);
+    /// assert_eq!(Rule::from_masks(1 | 1 << 3, 1 << 2 | 1 << 3), Rule::CONWAY);
+    /// ```
+    pub const fn from_masks(birth: u16, survival: u16) -> Self {
+        const fn expand(mask: u16) -> [u16; 9] {
+            let mut letters = [0; 9];
+            let mut n = 0;
+            while n <= 8 {
+                if mask & (1 << n) != 0 {
+                    letters[n] = FULL[n];
+                }
+                n += 1;
+            }
+            letters
+        }
+        Self {
+            birth: expand(birth & !1),
+            survival: expand(survival),
+            states: 2,
+        }
+    }
+
+    /// the same birth and survival conditions with `states` states, at least 2
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let star_wars = Rule::new(&[2], &[3, 4, 5]).with_states(4);
+    /// assert_eq!(star_wars, "345/2/4".parse().unwrap());
+    /// assert_eq!(star_wars.to_string(), "B2/S345/C4");
+    /// ```
+    pub const fn with_states(mut self, states: u8) -> Self {
+        self.states = if states < 2 { 2 } else { states };
+        self
+    }
+
+    /// the number of states, 2 for rules without dying states
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Rule::CONWAY.states(), 2);
+    /// assert_eq!("B2/S/C3".parse::<Rule>().unwrap().states(), 3);
+    /// ```
+    pub fn states(self) -> u8 {
+        self.states
+    }
+
+    /// neighbor counts above 8 are ignored, and so is birth on 0 like in `Rule::from_masks`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let highlife = Rule::new(&[3, 6], &[2, 3]);
+    /// assert_eq!(highlife, "B36/S23".parse().unwrap());
+    /// assert_eq!(highlife.to_string(), "B36/S23");
+    /// assert!(!Rule::new(&[0, 3], &[2, 3]).birth(0));
+    /// ```
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        let mask = |counts: &[u8]| {
+            counts
+                .iter()
+                .filter(|&&n| n <= 8)
+                .fold(0u16, |mask, &n| mask | 1 << n)
+        };
+        Self::from_masks(mask(birth), mask(survival))
+    }
+
+    /// whether a dead cell with `neighbors` alive neighbors comes alive, however they are arranged
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert!(Rule::CONWAY.birth(3));
+    /// assert!(!Rule::CONWAY.birth(2));
+    /// assert!(!"B3-a/S23".parse::<Rule>().unwrap().birth(3));
+    /// ```
+    pub fn birth(self, neighbors: u32) -> bool {
+        neighbors <= 8 && self.birth[neighbors as usize] == FULL[neighbors as usize]
+    }
+
+    /// whether an alive cell with `neighbors` alive neighbors stays alive, however they are
+    /// arranged
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert!(Rule::CONWAY.survival(2));
+    /// assert!(!Rule::CONWAY.survival(4));
+    /// ```
+    pub fn survival(self, neighbors: u32) -> bool {
+        neighbors <= 8 && self.survival[neighbors as usize] == FULL[neighbors as usize]
+    }
+
+    /// whether birth and survival only depend on the number of alive neighbors
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert!(Rule::CONWAY.is_totalistic());
+    /// assert!(!"B2-a/S12".parse::<Rule>().unwrap().is_totalistic());
+    /// ```
+    pub fn is_totalistic(self) -> bool {
+        (0..9).all(|n| {
+            [self.birth[n], self.survival[n]]
+                .iter()
+                .all(|&letters| letters == 0 || letters == FULL[n])
+        })
+    }
+
+    /// the state of a cell in the next generation, bit 0 of `neighborhood` is set if the north
+    /// neighbor is alive and the following bits go clockwise
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let brians_brain: Rule = "B2/S/C3".parse().unwrap();
+    /// assert_eq!(brians_brain.next_cell(Cell::Dead, 0b101), Cell::Alive);
+    /// assert_eq!(brians_brain.next_cell(Cell::Alive, 0b101), Cell::Dying(2));
+    /// assert_eq!(brians_brain.next_cell(Cell::Dying(2), 0b101), Cell::Dead);
+    ///
+    /// // north and north east are 2a, north and east are 2e
+    /// let rule: Rule = "B2-a/S12".parse().unwrap();
+    /// assert_eq!(rule.next_cell(Cell::Dead, 0b11), Cell::Dead);
+    /// assert_eq!(rule.next_cell(Cell::Dead, 0b101), Cell::Alive);
+    /// ```
+    pub fn next_cell(self, center: Cell, neighborhood: u8) -> Cell {
+        let (count, letter) = classify(neighborhood);
+        let alive = match center {
+            Cell::Dead => self.birth[count] & (1 << letter) != 0,
+            Cell::Alive => self.survival[count] & (1 << letter) != 0,
+            Cell::Dying(_) => false,
+        };
+        self.transition(center, alive)
+    }
+
+    // the next state of `center` given whether the rule lets it be born or survive
+    pub(crate) fn transition(self, center: Cell, alive: bool) -> Cell {
+        match center {
+            Cell::Dead if alive => Cell::Alive,
+            Cell::Dead => Cell::Dead,
+            Cell::Alive if alive => Cell::Alive,
+            Cell::Alive | Cell::Dying(_) => {
+                let next = center.state() + 1;
+                if next < self.states {
+                    Cell::Dying(next)
+                } else {
+                    Cell::Dead
+                }
+            }
+        }
+    }
+}
+
+// the neighbor count and the index of the letter of a neighborhood
+fn classify(neighborhood: u8) -> (usize, usize) {
+    let count = neighborhood.count_ones() as usize;
+    let (representatives, target) = if count <= 4 {
+        (REPRESENTATIVES[count], neighborhood)
+    } else {
+        (REPRESENTATIVES[8 - count], !neighborhood)
+    };
+    let letter = representatives
+        .iter()
+        .position(|&representative| symmetries(representative).any(|n| n == target))
+        .expect("every neighborhood has a letter");
+    (count, letter)
+}
+
+// the rotations and reflections of a neighborhood
+fn symmetries(neighborhood: u8) -> impl Iterator<Item = u8> {
+    // mirrors east and west, neighbor i goes to 8 - i
+    let reflect = |n: u8| (0..8).fold(0u8, |out, i| out | ((n >> i) & 1) << ((8 - i) % 8));
+    (0..4).flat_map(move |quarter| {
+        let rotated = neighborhood.rotate_left(2 * quarter);
+        vec![rotated, reflect(rotated)]
+    })
+}
+
+// whether a cell is born or survives for each of the 512 combinations of center and neighbors,
+// looked up by the evolution kernel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Transitions([u64; 8]);
+
+impl Transitions {
+    pub(crate) fn new(rule: Rule) -> Self {
+        let mut bits = [0u64; 8];
+        for neighborhood in 0..=255u8 {
+            let (count, letter) = classify(neighborhood);
+            for (center, letters) in [rule.birth, rule.survival].iter().enumerate() {
+                if letters[count] & (1 << letter) != 0 {
+                    let index = center << 8 | usize::from(neighborhood);
+                    bits[index / 64] |= 1 << (index % 64);
+                }
+            }
+        }
+        Self(bits)
+    }
+
+    pub(crate) fn alive(&self, center_alive: bool, neighborhood: u8) -> bool {
+        let index = usize::from(center_alive) << 8 | usize::from(neighborhood);
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+// accepts "B3/S23" style (case insensitive, either order) and the legacy "23/3" (S/B) style
+// neighbor counts may be restricted to arrangements in Hensel notation, as in "B2-a/S12"
+// Generations rules add the number of states, as in "B2/S345/C4" or "345/2/4"
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseRuleError::Empty);
+        }
+        if s.matches('/').count() == 2 {
+            let (rule, states) = s.split_at(s.rfind('/').unwrap());
+            let states = states[1..].trim();
+            let count = states
+                .strip_prefix(|c| matches!(c, 'C' | 'c' | 'G' | 'g'))
+                .unwrap_or(states);
+            return match count.parse::<u8>() {
+                Ok(count) if count >= 2 => Ok(rule.parse::<Rule>()?.with_states(count)),
+                _ => Err(ParseRuleError::InvalidStateCount(states.to_owned())),
+            };
+        }
+        let (first, second) = s.split_at(s.find('/').ok_or(ParseRuleError::MissingSeparator)?);
+        let second = &second[1..];
+
+        let strip = |part: &str, prefix: char| {
+            part.strip_prefix(prefix)
+                .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+                .map(str::to_owned)
+        };
+
+        let (birth, survival) = match (strip(first, 'B'), strip(second, 'S')) {
+            (Some(b), Some(s)) => (b, s),
+            _ => match (strip(first, 'S'), strip(second, 'B')) {
+                (Some(s), Some(b)) => (b, s),
+                _ => (second.to_owned(), first.to_owned()),
+            },
+        };
+
+        let rule = Self {
+            birth: parse_conditions(&birth)?,
+            survival: parse_conditions(&survival)?,
+            states: 2,
+        };
+        if rule.birth[0] != 0 {
+            return Err(ParseRuleError::BirthOnZero);
+        }
+        Ok(rule)
+    }
+}
+
+// neighbor counts, each optionally followed by the letters it is restricted to or by '-' and the
+// letters it excludes, e.g. "2-a3ce"
+fn parse_conditions(part: &str) -> Result<[u16; 9], ParseRuleError> {
+    let mut conditions = [0; 9];
+    let mut chars = part.chars().peekable();
+    while let Some(c) = chars.next() {
+        let n = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as usize,
+            _ => return Err(ParseRuleError::InvalidNeighborCount(c)),
+        };
+        let exclude = chars.next_if_eq(&'-').is_some();
+        let mut letters = 0;
+        while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+            match LETTERS[n].find(letter) {
+                Some(i) => letters |= 1 << i,
+                None => {
+                    return Err(ParseRuleError::InvalidNeighborhood(format!(
+                        "{}{}",
+                        n, letter
+                    )))
+                }
+            }
+        }
+        conditions[n] |= match (exclude, letters) {
+            (false, 0) => FULL[n],
+            (false, letters) => letters,
+            (true, letters) => FULL[n] & !letters,
+        };
+    }
+    Ok(conditions)
+}
+
+// the shorter of the included and the excluded letters, e.g. "2-a" instead of "2ceikn"
+fn write_conditions(f: &mut fmt::Formatter<'_>, conditions: [u16; 9]) -> fmt::Result {
+    for (n, &letters) in conditions.iter().enumerate() {
+        if letters == 0 {
+            continue;
+        }
+        write!(f, "{}", n)?;
+        if letters == FULL[n] {
+            continue;
+        }
+        let names = |letters: u16| -> String {
+            LETTERS[n]
+                .chars()
+                .enumerate()
+                .filter(|(i, _)| letters & (1 << i) != 0)
+                .map(|(_, c)| c)
+                .collect()
+        };
+        let excluded = FULL[n] & !letters;
+        if excluded.count_ones() < letters.count_ones() {
+            write!(f, "-{}", names(excluded))?;
+        } else {
+            write!(f, "{}", names(letters))?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        write_conditions(f, self.birth)?;
+        write!(f, "/S")?;
+        write_conditions(f, self.survival)?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+// written as the rulestring, which is readable in configs and independent of the bit layout
+#[cfg(feature = "serde")]
+impl serde::Serialize for Rule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Rule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rulestring = String::deserialize(deserializer)?;
+        rulestring.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty rulestring"),
+            Self::MissingSeparator => write!(f, "rulestring is missing the '/' separator"),
+            Self::InvalidNeighborCount(c) => write!(f, "invalid neighbor count '{}'", c),
+            Self::InvalidNeighborhood(letter) => write!(f, "invalid neighborhood \"{}\"", letter),
+            Self::InvalidLargerThanLife(rule) => {
+                write!(f, "invalid Larger than Life rule \"{}\"", rule)
+            }
+            Self::InvalidStateCount(states) => write!(f, "invalid number of states \"{}\"", states),
+            Self::BirthOnZero => write!(f, "B0 rules are not supported"),
+        }
+    }
+}
+
+impl Error for ParseRuleError {}
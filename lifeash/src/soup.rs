@@ -0,0 +1,51 @@
+// random soups which are the same for a seed on every platform and in every version, so that
+// searches and stress tests can be repeated
+
+use crate::{
+    core::{Cell, Rect},
+    universe::{mix, Universe},
+};
+
+// the increment of splitmix64, whose output function is `mix`
+const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+impl Universe {
+    /// overwrites `rect` with cells which are alive with the probability `density`, the same seed
+    /// always gives the same soup
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let rect = Rect::new((0, 0), (99, 99));
+    /// let mut universe = Universe::new();
+    /// universe.fill_random(rect, 0.3, 42);
+    /// let population = universe.population();
+    /// assert!((2700..3300).contains(&population));
+    ///
+    /// let mut again = Universe::new();
+    /// again.fill_random(rect, 0.3, 42);
+    /// assert_eq!(again.content_hash(), universe.content_hash());
+    /// again.fill_random(rect, 0.3, 43);
+    /// assert_ne!(again.content_hash(), universe.content_hash());
+    /// ```
+    pub fn fill_random(&mut self, rect: Rect, density: f64, seed: u64) {
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "density {} is not between 0 and 1",
+            density
+        );
+        let mut state = seed;
+        let cells = rect.positions().map(|pos| {
+            // the upper 53 bits as a float in [0, 1), exact on every platform
+            let sample = (mix(state) >> 11) as f64 / (1u64 << 53) as f64;
+            state = state.wrapping_add(GAMMA);
+            let cell = if sample < density {
+                Cell::Alive
+            } else {
+                Cell::Dead
+            };
+            (pos, cell)
+        });
+        self.set_cells(cells);
+    }
+}
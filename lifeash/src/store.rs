@@ -0,0 +1,197 @@
+// collections of pattern files, read from a directory or, with the zip feature, from a single
+// zip archive which can be shipped as one file
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "zip")]
+use std::{
+    fs::File,
+    io::{Read, Seek},
+};
+
+use crate::{
+    io::{rle, ParseError},
+    pattern::Pattern,
+};
+
+#[cfg(feature = "formats-extra")]
+use crate::io::{life, plaintext};
+
+/// a collection of pattern files, named by their path inside the collection with `/` separators
+pub trait PatternStore {
+    /// the names of all files with a known extension, sorted
+    fn names(&mut self) -> io::Result<Vec<String>>;
+
+    /// the contents of one file
+    fn read(&mut self, name: &str) -> io::Result<String>;
+
+    /// reads and parses one file, the format is picked by the extension
+    fn load(&mut self, name: &str) -> Result<Pattern, ParseError> {
+        let input = self.read(name)?;
+        parse(name, &input)
+    }
+}
+
+/// whether a pattern file with this name can be parsed
+///
+/// ```
+/// use lifeash::store;
+///
+/// assert!(store::is_pattern("guns/gosper.rle"));
+/// assert!(!store::is_pattern("README.md"));
+/// ```
+pub fn is_pattern(name: &str) -> bool {
+    let extension = extension(name);
+    let extra = matches!(extension.as_deref(), Some("cells" | "lif" | "life"));
+    extension.as_deref() == Some("rle") || (extra && cfg!(feature = "formats-extra"))
+}
+
+/// parses the contents of a pattern file in the format given by the extension of its name
+///
+/// ```
+/// use lifeash::store;
+///
+/// let pattern = store::parse("blinker.rle", "3o!").unwrap();
+/// assert_eq!(pattern.cells.len(), 3);
+/// assert!(store::parse("blinker.txt", "3o!").is_err());
+/// ```
+pub fn parse(name: &str, input: &str) -> Result<Pattern, ParseError> {
+    match extension(name).as_deref() {
+        Some("rle") => rle::parse(input).map(Pattern::from),
+        #[cfg(feature = "formats-extra")]
+        Some("cells") => plaintext::parse(input),
+        #[cfg(feature = "formats-extra")]
+        Some("lif") | Some("life") => life::parse(input),
+        _ => Err(ParseError::UnknownFormat(name.to_owned())),
+    }
+}
+
+fn extension(name: &str) -> Option<String> {
+    let (_, extension) = name.rsplit_once('.')?;
+    Some(extension.to_ascii_lowercase())
+}
+
+/// the pattern files below a directory, including its subdirectories
+///
+/// ```no_run
+/// use lifeash::store::{Directory, PatternStore};
+///
+/// let mut patterns = Directory::new("patterns");
+/// for name in patterns.names().unwrap() {
+///     println!("{}: {} cells", name, patterns.load(&name).unwrap().cells.len());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Directory {
+    root: PathBuf,
+}
+
+impl Directory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn collect(&self, dir: &Path, prefix: &str, names: &mut Vec<String>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            // names that aren't utf-8 can't be named in the store
+            let file_name = match entry.file_name().into_string() {
+                Ok(file_name) => file_name,
+                Err(_) => continue,
+            };
+            let name = format!("{}{}", prefix, file_name);
+            if entry.file_type()?.is_dir() {
+                self.collect(&entry.path(), &format!("{}/", name), names)?;
+            } else if is_pattern(&name) {
+                names.push(name);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PatternStore for Directory {
+    fn names(&mut self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        self.collect(&self.root, "", &mut names)?;
+        names.sort();
+        Ok(names)
+    }
+
+    fn read(&mut self, name: &str) -> io::Result<String> {
+        // names never leave the directory
+        if name.split('/').any(|part| part == ".." || part.is_empty()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid pattern name \"{}\"", name),
+            ));
+        }
+        fs::read_to_string(self.root.join(name))
+    }
+}
+
+/// the pattern files of a zip archive, from a file or any other seekable reader, e.g. the bytes
+/// of a download
+///
+/// ```
+/// use std::io::{Cursor, Write};
+/// use lifeash::store::{Archive, PatternStore};
+///
+/// let mut bytes = Cursor::new(Vec::new());
+/// let mut zip = zip::ZipWriter::new(&mut bytes);
+/// zip.start_file("oscillators/blinker.rle", zip::write::SimpleFileOptions::default())
+///     .unwrap();
+/// zip.write_all(b"3o!").unwrap();
+/// zip.finish().unwrap();
+///
+/// let mut archive = Archive::new(bytes).unwrap();
+/// assert_eq!(archive.names().unwrap(), ["oscillators/blinker.rle"]);
+/// assert_eq!(archive.load("oscillators/blinker.rle").unwrap().cells.len(), 3);
+/// ```
+#[cfg(feature = "zip")]
+pub struct Archive<R> {
+    zip: zip::ZipArchive<R>,
+}
+
+#[cfg(feature = "zip")]
+impl Archive<File> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: Read + Seek> Archive<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        Ok(Self {
+            zip: zip::ZipArchive::new(reader)?,
+        })
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: Read + Seek> PatternStore for Archive<R> {
+    fn names(&mut self) -> io::Result<Vec<String>> {
+        let mut names: Vec<_> = self
+            .zip
+            .file_names()
+            .filter(|name| is_pattern(name))
+            .map(str::to_owned)
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn read(&mut self, name: &str) -> io::Result<String> {
+        let mut input = String::new();
+        self.zip.by_name(name)?.read_to_string(&mut input)?;
+        Ok(input)
+    }
+}
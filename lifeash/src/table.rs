@@ -0,0 +1,110 @@
+// the ids of the canonical nodes, hashed and compared by the nodes they refer to in the arena, so
+// that every node is stored only once
+//
+// an open addressing table with linear probing, a map from nodes to ids would keep a second copy
+// of every node as its key
+
+use crate::{
+    node::Node,
+    universe::{mix, Id},
+};
+
+// the table is kept at most three quarters full
+const LOAD_NUMERATOR: usize = 3;
+const LOAD_DENOMINATOR: usize = 4;
+const MIN_SLOTS: usize = 16;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NodeTable {
+    // a power of two of them, every id sits in the slot its hash leads to or in a later one of
+    // the same run of occupied slots
+    slots: Vec<Id>,
+    len: usize,
+}
+
+impl NodeTable {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    // the id of the node in `nodes` which equals `node`
+    pub(crate) fn get(&self, nodes: &[Node], node: &Node) -> Option<Id> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut slot = hash(node) as usize & mask;
+        loop {
+            let id = self.slots[slot];
+            if id == Id::VACANT {
+                return None;
+            }
+            if nodes[id.index()] == *node {
+                return Some(id);
+            }
+            slot = (slot + 1) & mask;
+        }
+    }
+
+    // `id` must not be in the table yet, its node is read from `nodes`
+    pub(crate) fn insert(&mut self, nodes: &[Node], id: Id) {
+        self.reserve(nodes, 1);
+        self.place(nodes, id);
+        self.len += 1;
+    }
+
+    // makes room for `additional` more ids without growing
+    pub(crate) fn reserve(&mut self, nodes: &[Node], additional: usize) {
+        let needed = (self.len + additional) * LOAD_DENOMINATOR / LOAD_NUMERATOR + 1;
+        if needed > self.slots.len() {
+            self.rebuild(nodes, needed.next_power_of_two().max(MIN_SLOTS));
+        }
+    }
+
+    // keeps the ids for which `keep` returns true, the size of the table stays the same
+    pub(crate) fn retain(&mut self, nodes: &[Node], mut keep: impl FnMut(Id) -> bool) {
+        for slot in &mut self.slots {
+            if *slot != Id::VACANT && !keep(*slot) {
+                *slot = Id::VACANT;
+                self.len -= 1;
+            }
+        }
+        // removed ids break the runs which later ids were found through
+        self.rebuild(nodes, self.slots.len());
+    }
+
+    pub(crate) fn ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.slots.iter().copied().filter(|&id| id != Id::VACANT)
+    }
+
+    fn rebuild(&mut self, nodes: &[Node], len: usize) {
+        let old = std::mem::replace(&mut self.slots, vec![Id::VACANT; len]);
+        for id in old.into_iter().filter(|&id| id != Id::VACANT) {
+            self.place(nodes, id);
+        }
+    }
+
+    fn place(&mut self, nodes: &[Node], id: Id) {
+        let mask = self.slots.len() - 1;
+        let mut slot = hash(&nodes[id.index()]) as usize & mask;
+        while self.slots[slot] != Id::VACANT {
+            slot = (slot + 1) & mask;
+        }
+        self.slots[slot] = id;
+    }
+}
+
+// like the equality of nodes, the results of an inode are left out
+fn hash(node: &Node) -> u64 {
+    match node {
+        Node::Leaf(leaf) => mix(u64::from(leaf.0.state())),
+        Node::Inode(inode) => {
+            let pair = |a: Id, b: Id| a.index() as u64 | (b.index() as u64) << 32;
+            mix(mix(pair(inode.nw, inode.ne)) ^ pair(inode.sw, inode.se))
+        }
+    }
+}
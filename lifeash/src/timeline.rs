@@ -0,0 +1,166 @@
+use crate::universe::{Snapshot, Universe};
+
+// states of the universe taken while evolving, at most one per interval of generations
+// a superstep can skip several intervals, the snapshot is then taken before the step that
+// starts after the interval boundary
+// the snapshots keep their trees alive, so when there are more than `Timeline::CAPACITY` of
+// them every other one is released and the spacing doubles
+#[derive(Debug, Default)]
+pub struct Timeline {
+    interval: u128,
+    // generations between snapshots, the interval doubled each time the timeline was thinned
+    spacing: u128,
+    // ordered by generation
+    snapshots: Vec<Snapshot>,
+}
+
+impl Timeline {
+    /// the most snapshots kept at once
+    pub const CAPACITY: usize = 256;
+
+    pub(crate) fn new(interval: u128) -> Self {
+        Self {
+            interval,
+            spacing: interval,
+            ..Self::default()
+        }
+    }
+
+    /// generations between two snapshots, 0 disables the timeline
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Universe::new().timeline().interval(), 0);
+    /// let universe = Universe::builder().timeline_interval(64).build();
+    /// assert_eq!(universe.timeline().interval(), 64);
+    /// ```
+    pub fn interval(&self) -> u128 {
+        self.interval
+    }
+
+    /// generations between two snapshots now, a power of two times the interval
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Timeline};
+    ///
+    /// let mut universe = Universe::builder().timeline_interval(1).build();
+    /// for _ in 0..=Timeline::CAPACITY {
+    ///     universe.evolve();
+    /// }
+    /// assert_eq!(universe.timeline().spacing(), 2);
+    /// assert!(universe.timeline().generations().count() <= Timeline::CAPACITY);
+    /// ```
+    pub fn spacing(&self) -> u128 {
+        self.spacing
+    }
+
+    /// the generations which can be rewound to without evolving
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().timeline_interval(10).build();
+    /// for _ in 0..25 {
+    ///     universe.evolve();
+    /// }
+    /// assert_eq!(universe.timeline().generations().collect::<Vec<_>>(), [0, 10, 20]);
+    /// ```
+    pub fn generations(&self) -> impl Iterator<Item = u128> + '_ {
+        self.snapshots.iter().map(Snapshot::generation)
+    }
+}
+
+impl Universe {
+    // called before every superstep
+    pub(crate) fn record_timeline(&mut self) {
+        if self.timeline().interval == 0 {
+            return;
+        }
+        let generation = self.generation();
+        self.with_timeline(|universe, timeline| {
+            // restoring an earlier state leaves snapshots of a future which no longer happens
+            universe.release_after(timeline, generation);
+            let spacing = timeline.spacing;
+            let due = match timeline.snapshots.last() {
+                Some(last) => generation / spacing > last.generation() / spacing,
+                None => true,
+            };
+            if due {
+                timeline.snapshots.push(universe.snapshot());
+            }
+            while timeline.snapshots.len() > Timeline::CAPACITY {
+                universe.thin(timeline);
+            }
+        });
+    }
+
+    // doubles the spacing and keeps the first snapshot of every new interval
+    fn thin(&mut self, timeline: &mut Timeline) {
+        timeline.spacing *= 2;
+        let spacing = timeline.spacing;
+        let mut kept: Vec<Snapshot> = Vec::with_capacity(timeline.snapshots.len() / 2 + 1);
+        for snapshot in timeline.snapshots.drain(..) {
+            match kept.last() {
+                Some(last) if last.generation() / spacing == snapshot.generation() / spacing => {
+                    self.release(snapshot)
+                }
+                _ => kept.push(snapshot),
+            }
+        }
+        timeline.snapshots = kept;
+    }
+
+    fn release_after(&mut self, timeline: &mut Timeline, generation: u128) {
+        while let Some(last) = timeline.snapshots.last() {
+            if last.generation() <= generation {
+                break;
+            }
+            let last = timeline.snapshots.pop().unwrap();
+            self.release(last);
+        }
+    }
+
+    /// goes back to an earlier generation by restoring the closest snapshot of the timeline
+    /// before it and evolving the rest, returns false if the generation can't be reached
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().timeline_interval(16).build();
+    /// universe.load_rle_at("bo$2bo$3o!", (0, 0)).unwrap();
+    /// universe.advance(100);
+    /// assert!(universe.rewind_to(37));
+    /// assert_eq!(universe.generation(), 37);
+    ///
+    /// let mut expected = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// expected.advance(37);
+    /// assert_eq!(universe.content_hash(), expected.content_hash());
+    /// assert!(!universe.rewind_to(50));
+    /// ```
+    pub fn rewind_to(&mut self, generation: u128) -> bool {
+        if generation > self.generation() {
+            return false;
+        }
+        let rewound = self.with_timeline(|universe, timeline| {
+            match timeline
+                .snapshots
+                .iter()
+                .rev()
+                .find(|snapshot| snapshot.generation() <= generation)
+            {
+                Some(snapshot) => {
+                    universe.restore(snapshot);
+                    let generation = snapshot.generation();
+                    universe.release_after(timeline, generation);
+                    true
+                }
+                None => false,
+            }
+        });
+        if rewound {
+            self.advance_to(generation);
+        }
+        rewound
+    }
+}
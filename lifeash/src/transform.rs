@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::Position,
+    node::{Inode, Node},
+    pattern::Pattern,
+    universe::{Id, Universe},
+};
+
+// rotations and reflections about the center of the cell grid, which lies between the cells
+// (-1, -1) and (0, 0), so every quadrant is mapped onto a quadrant
+// clockwise as seen on the screen, where y grows downwards
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Transform {
+    RotateCw,
+    RotateCcw,
+    FlipX,
+    FlipY,
+}
+
+impl Transform {
+    /// where the cell at `pos` ends up
+    ///
+    /// ```
+    /// use lifeash::{Position, Transform};
+    ///
+    /// assert_eq!(Transform::RotateCw.apply(Position::new(1, 0)), Position::new(-1, 1));
+    /// assert_eq!(Transform::FlipX.apply(Position::new(1, 0)), Position::new(-2, 0));
+    /// ```
+    pub fn apply(self, pos: Position) -> Position {
+        let Position { x, y } = pos;
+        match self {
+            Self::RotateCw => Position::new(-1 - y, x),
+            Self::RotateCcw => Position::new(y, -1 - x),
+            Self::FlipX => Position::new(-1 - x, y),
+            Self::FlipY => Position::new(x, -1 - y),
+        }
+    }
+}
+
+impl Pattern {
+    /// stamping the result at the origin gives the same as stamping the pattern
+    /// and transforming the universe
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Transform};
+    ///
+    /// let glider = Pattern {
+    ///     cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+    ///         .into_iter()
+    ///         .map(Position::from)
+    ///         .collect(),
+    ///     ..Pattern::default()
+    /// };
+    /// let mut universe = Universe::new();
+    /// universe.stamp(&glider.transformed(Transform::RotateCw), Position::ORIGIN);
+    /// let mut expected = Universe::new();
+    /// expected.stamp(&glider, Position::ORIGIN);
+    /// expected.transform(Transform::RotateCw);
+    /// assert_eq!(universe.content_hash(), expected.content_hash());
+    /// ```
+    pub fn transformed(&self, transform: Transform) -> Pattern {
+        Pattern {
+            cells: self.cells.iter().map(|&pos| transform.apply(pos)).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// a quarter turn clockwise
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let domino = Pattern {
+    ///     cells: vec![Position::new(0, 0), Position::new(1, 0)],
+    ///     ..Pattern::default()
+    /// };
+    /// assert_eq!(domino.rotate_cw().cells, [(-1, 0), (-1, 1)].map(Position::from));
+    /// ```
+    pub fn rotate_cw(&self) -> Pattern {
+        self.transformed(Transform::RotateCw)
+    }
+
+    /// a quarter turn counterclockwise
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let domino = Pattern {
+    ///     cells: vec![Position::new(0, 0), Position::new(1, 0)],
+    ///     ..Pattern::default()
+    /// };
+    /// assert_eq!(domino.rotate_ccw().rotate_cw(), domino);
+    /// ```
+    pub fn rotate_ccw(&self) -> Pattern {
+        self.transformed(Transform::RotateCcw)
+    }
+
+    /// mirrors left and right
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let domino = Pattern {
+    ///     cells: vec![Position::new(0, 0), Position::new(1, 0)],
+    ///     ..Pattern::default()
+    /// };
+    /// assert_eq!(domino.flip_x().cells, [(-1, 0), (-2, 0)].map(Position::from));
+    /// ```
+    pub fn flip_x(&self) -> Pattern {
+        self.transformed(Transform::FlipX)
+    }
+
+    /// mirrors top and bottom
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let domino = Pattern {
+    ///     cells: vec![Position::new(0, 0), Position::new(1, 0)],
+    ///     ..Pattern::default()
+    /// };
+    /// assert_eq!(domino.flip_y().cells, [(0, -1), (1, -1)].map(Position::from));
+    /// ```
+    pub fn flip_y(&self) -> Pattern {
+        self.transformed(Transform::FlipY)
+    }
+}
+
+impl Universe {
+    /// transforms the quadtree by rearranging quadrants, shared subtrees are transformed once
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Transform};
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// universe.transform(Transform::RotateCw);
+    /// let mut cells: Vec<_> = universe.live_cells().collect();
+    /// cells.sort();
+    /// assert_eq!(cells, [(-1, 0), (-1, 1), (-1, 2)].map(Position::from));
+    /// ```
+    pub fn transform(&mut self, transform: Transform) {
+        self.record_edit();
+        let root = self.transform_tree(self.root(), transform, &mut HashMap::new());
+        self.set_root(root);
+    }
+
+    fn transform_tree(&mut self, tree: Id, transform: Transform, memo: &mut HashMap<Id, Id>) -> Id {
+        let Inode { nw, ne, sw, se, .. } = match tree.node(self) {
+            // empty trees are symmetric
+            node if node.population() == 0 => return tree,
+            Node::Leaf(_) => return tree,
+            Node::Inode(inode) => inode.clone(),
+        };
+        if let Some(&id) = memo.get(&tree) {
+            return id;
+        }
+
+        let nw = self.transform_tree(nw, transform, memo);
+        let ne = self.transform_tree(ne, transform, memo);
+        let sw = self.transform_tree(sw, transform, memo);
+        let se = self.transform_tree(se, transform, memo);
+        let id = match transform {
+            Transform::RotateCw => self.new_inode(sw, nw, se, ne),
+            Transform::RotateCcw => self.new_inode(ne, se, nw, sw),
+            Transform::FlipX => self.new_inode(ne, nw, se, sw),
+            Transform::FlipY => self.new_inode(sw, se, nw, ne),
+        };
+        memo.insert(tree, id);
+        id
+    }
+}
@@ -1,23 +1,133 @@
-use bimap::BiMap;
+use std::{collections::HashMap, convert::TryFrom, mem, time::Duration};
 
 use crate::{
-    core::{Cell, Level, Position, Quadrant::*},
+    cache::LevelCache,
+    core::{Cell, Level, Offset, Position, Quadrant::*, Rect},
+    hashing::CollisionCheck,
+    history::History,
+    kernels::{KernelKind, LeafKernel},
+    ltl::Backend,
     node::{Inode, Leaf, Node},
+    rule::Rule,
+    table::NodeTable,
+    timeline::Timeline,
 };
 
-#[derive(Default)]
 pub struct Universe {
-    table: BiMap<Id, Node>,
+    // the nodes are stored in an arena indexed by their id and looked up by content in the
+    // table, plain collections keep the universe Send
+    nodes: Vec<Node>,
+    table: NodeTable,
+    // slots of collected nodes, which are reused for new ones
+    free: Vec<Id>,
+    collisions: CollisionCheck,
     root: Option<Id>,
-    generation: usize,
+    pub(crate) generation: u128,
+    rule: Rule,
+    // evolves the leaf level for the rule
+    kernel: LeafKernel,
+    pub(crate) kernel_kind: KernelKind,
+    backend: Backend,
+
+    // estimated table size in bytes above which garbage is collected after a step
+    pub(crate) gc_threshold: Option<usize>,
+    // nodes which survive garbage collection even if unreachable from the root,
+    // counted because several snapshots can share a root
+    pinned: HashMap<Id, usize>,
+    gc_stats: GcStats,
+    pub(crate) stats: Stats,
+    // lookups and limits of the cached results, indexed by level
+    pub(crate) levels: Vec<LevelCache>,
+    history: History,
+    timeline: Timeline,
+    // regions whose cells keep their state while the rest evolves
+    frozen: Vec<Rect>,
+    // whether large subtrees are evolved on several threads, each with a universe of its own
+    // which keeps its cache from step to step
+    pub(crate) parallel: bool,
+    pub(crate) workers: Vec<Worker>,
+}
+
+// the universe of one thread of the parallel steps, with the nodes copied to and from it so
+// far, so that each step only copies the nodes which are new
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+pub(crate) struct Worker {
+    pub(crate) universe: Universe,
+    // ids of the universe to ids of the worker, and the results of the worker back
+    pub(crate) imported: HashMap<Id, Id>,
+    pub(crate) exported: HashMap<Id, Id>,
+    // the garbage collections of both universes when the maps were started, a collection frees
+    // ids which may come back as other nodes
+    pub(crate) collections: (u64, u64),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct Stats {
+    pub generation: u128,
+    // nodes currently in the table
+    pub nodes: usize,
+    // lookups of evolved results which were found in the cache or had to be computed
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    // total time spent in advancing the universe, zero on wasm32-unknown-unknown without a clock
+    pub evolve_time: Duration,
+    // nodes whose 64-bit hash was already taken, only counted with the verify-hashing feature
+    pub hash_collisions: u64,
+}
+
+pub struct UniverseBuilder {
+    rule: Rule,
+    backend: Backend,
+    gc_threshold: Option<usize>,
+    history_depth: usize,
+    timeline_interval: u128,
+    parallel: bool,
+    kernel: KernelKind,
+    level_cache_limits: Vec<(u8, usize)>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct GcStats {
+    pub collections: u64,
+    // nodes reclaimed by the last collection
+    pub last_reclaimed: usize,
+    // nodes reclaimed over the whole lifetime of the universe
+    pub total_reclaimed: u64,
+    // nodes in the table right now
+    pub live_nodes: usize,
 }
 
+// index into the arena, 32 bits keep the nodes small
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Id(usize);
+pub struct Id(u32);
+
+// the root has to be three levels above the step
+const MAX_STEP_LOG: u8 = Level::MAX_LEVEL.value() - 3;
+// rough memory footprint of a live node, a slot in the arena and on average two slots of the
+// table, which is between three eighths and three quarters full, the slots of collected nodes
+// are reused and not counted
+pub(crate) const NODE_BYTES: usize = mem::size_of::<Node>() + 2 * mem::size_of::<Id>();
+// an entry in the maps of the nodes copied between a worker and its universe
+const COPIED_BYTES: usize = 2 * mem::size_of::<Id>() + 1;
+
+impl Worker {
+    fn memory_usage(&self) -> usize {
+        self.universe.memory_usage() + (self.imported.len() + self.exported.len()) * COPIED_BYTES
+    }
+}
 
 impl Id {
-    fn node(self, univ: &Universe) -> &Node {
-        univ.table.get_by_left(&self).unwrap()
+    // marks an empty slot of the table, the arena never gets this far
+    pub(crate) const VACANT: Id = Id(u32::MAX);
+
+    pub(crate) fn node(self, univ: &Universe) -> &Node {
+        &univ.nodes[self.index()]
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
     }
 
     #[allow(dead_code)]
@@ -29,7 +139,7 @@ impl Id {
         }
     }
 
-    fn inode(self, univ: &Universe) -> &Inode {
+    pub(crate) fn inode(self, univ: &Universe) -> &Inode {
         if let Node::Inode(inode) = self.node(univ) {
             inode
         } else {
@@ -39,38 +149,341 @@ impl Id {
 }
 
 impl Universe {
+    /// the most generations `advance` takes at once while a region is frozen, each of them is a
+    /// step of its own then
+    pub const MAX_FROZEN_ADVANCE: u128 = 1 << 16;
+
+    /// an empty universe with the default rule
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::new();
+    /// assert_eq!(universe.population(), 0);
+    /// assert_eq!(universe.generation(), 0);
+    /// ```
+    pub fn new() -> Self {
+        UniverseBuilder::new().build()
+    }
+
+    /// see `UniverseBuilder`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::builder().rule("B36/S23".parse().unwrap()).build();
+    /// assert_eq!(universe.rule().to_string(), "B36/S23");
+    /// ```
+    pub fn builder() -> UniverseBuilder {
+        UniverseBuilder::new()
+    }
+
+    /// a copy holding only the nodes of the current tree, e.g. to hand the state to another thread
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(100);
+    /// let copy = universe.compacted();
+    /// assert_eq!(copy.generation(), 100);
+    /// assert_eq!(copy.content_hash(), universe.content_hash());
+    /// assert!(copy.stats().nodes <= universe.stats().nodes);
+    /// ```
+    pub fn compacted(&self) -> Universe {
+        let mut universe = Universe::builder()
+            .rule(self.rule)
+            .backend(self.backend)
+            .parallel(self.parallel)
+            .kernel(self.kernel_kind)
+            .build();
+        let root = universe.import_tree(self, self.root(), &mut HashMap::new());
+        universe.set_root(root);
+        universe.generation = self.generation;
+        universe
+    }
+
+    /// a standalone universe with the cells of `rect`, moved so that its north west corner is
+    /// the origin, with the same rule and generation
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o$5b2o$5b2o!").unwrap();
+    /// universe.evolve();
+    /// let copy = universe.region_copy(Rect::new((4, 1), (7, 3)));
+    /// assert_eq!(copy.generation(), 1);
+    /// assert_eq!(copy.population(), 4);
+    /// assert_eq!(copy.bounding_box(), Some(((1, 0).into(), (2, 1).into())));
+    /// ```
+    pub fn region_copy(&self, rect: Rect) -> Universe {
+        let mut universe = Universe::builder()
+            .rule(self.rule)
+            .backend(self.backend)
+            .parallel(self.parallel)
+            .kernel(self.kernel_kind)
+            .build();
+        let offset = Offset::new(-rect.min.x, -rect.min.y);
+        universe.set_cells(self.cells_in(rect).map(|(pos, cell)| (pos + offset, cell)));
+        universe.generation = self.generation;
+        universe
+    }
+}
+
+impl Stats {
+    /// fraction of evolved results served from the cache
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// assert_eq!(universe.stats().cache_hit_rate(), 0.0);
+    /// universe.advance(1000);
+    /// assert!(universe.stats().cache_hit_rate() > 0.0);
+    /// ```
+    pub fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl UniverseBuilder {
+    /// conway's rule without automatic garbage collection
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, UniverseBuilder};
+    ///
+    /// let universe = UniverseBuilder::new().build();
+    /// assert_eq!(universe.rule(), Rule::CONWAY);
+    /// ```
     pub fn new() -> Self {
         Self {
-            table: BiMap::new(),
+            rule: Rule::default(),
+            backend: Backend::Life,
+            gc_threshold: None,
+            history_depth: 0,
+            timeline_interval: 0,
+            parallel: false,
+            kernel: KernelKind::Auto,
+            level_cache_limits: Vec::new(),
+        }
+    }
+
+    /// the rule the universe evolves by
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::builder().rule(Rule::new(&[3, 6], &[2, 3])).build();
+    /// assert!(universe.rule().birth(6));
+    /// ```
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// collect garbage whenever the node table grows beyond roughly `bytes`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().gc_threshold(1 << 14).build();
+    /// universe.load_rle_at("bo$2bo$3o!", Position::ORIGIN).unwrap();
+    /// universe.advance(10_000);
+    /// assert!(universe.gc_stats().collections > 0);
+    /// ```
+    pub fn gc_threshold(mut self, bytes: usize) -> Self {
+        self.gc_threshold = Some(bytes);
+        self
+    }
+
+    /// how the universe evolves, see `Universe::set_backend`
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Backend};
+    ///
+    /// let rule = "R2,C0,M1,S3..5,B3..4,NM".parse().unwrap();
+    /// let universe = Universe::builder().backend(Backend::LargerThanLife(rule)).build();
+    /// assert_eq!(universe.backend(), Backend::LargerThanLife(rule));
+    /// ```
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// keep the states before the last `depth` edits for `Universe::undo`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().history_depth(1).build();
+    /// universe.set_cell((0, 0), Cell::Alive);
+    /// universe.set_cell((1, 0), Cell::Alive);
+    /// assert!(universe.undo());
+    /// assert!(!universe.undo());
+    /// assert_eq!(universe.population(), 1);
+    /// ```
+    pub fn history_depth(mut self, depth: usize) -> Self {
+        self.history_depth = depth;
+        self
+    }
+
+    /// snapshot the state every `interval` generations for `Universe::rewind_to`, the interval
+    /// doubles whenever more than `Timeline::CAPACITY` snapshots would be kept
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().timeline_interval(8).build();
+    /// universe.advance(20);
+    /// assert!(universe.rewind_to(3));
+    /// assert_eq!(universe.generation(), 3);
+    /// ```
+    pub fn timeline_interval(mut self, interval: u128) -> Self {
+        self.timeline_interval = interval;
+        self
+    }
+
+    /// evolve large universes on several threads, which needs the `parallel` feature
+    /// the threads recompute the parts where their subtrees overlap, so this pays off for large
+    /// patterns on many cores
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::builder().parallel(true).build();
+    /// assert!(universe.is_parallel());
+    /// ```
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// the kernel which evolves the leaves, kernels which do not support the rule fall back to
+    /// the lookup kernel
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, KernelKind};
+    ///
+    /// let universe = Universe::builder().kernel(KernelKind::Bitboard).build();
+    /// assert_eq!(universe.kernel(), "bitboard");
+    /// ```
+    pub fn kernel(mut self, kernel: KernelKind) -> Self {
+        self.kernel = kernel;
+        self
+    }
+
+    /// keep at most `limit` results of trees of `level`, see `Universe::set_level_cache_limit`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().level_cache_limit(3, 0).build();
+    /// universe.load_rle_at("bo$2bo$3o!", Position::ORIGIN).unwrap();
+    /// universe.advance(100);
+    /// let level = universe.level_stats()[3];
+    /// assert_eq!((level.cache_limit, level.cached_results), (Some(0), 0));
+    /// assert_eq!(level.cache_hits, 0);
+    /// ```
+    pub fn level_cache_limit(mut self, level: u8, limit: usize) -> Self {
+        assert!(
+            level <= Level::MAX_LEVEL.value(),
+            "there is no level {}",
+            level
+        );
+        self.level_cache_limits.push((level, limit));
+        self
+    }
+
+    /// an empty universe with the configured settings
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::builder().build();
+    /// assert_eq!(universe.population(), 0);
+    /// ```
+    pub fn build(self) -> Universe {
+        let mut universe = Universe {
+            nodes: Vec::new(),
+            table: NodeTable::new(),
+            free: Vec::new(),
+            collisions: CollisionCheck::new(),
             root: None,
             generation: 0,
+            rule: self.rule,
+            kernel: LeafKernel::new(self.kernel, self.rule),
+            kernel_kind: self.kernel,
+            backend: self.backend,
+            gc_threshold: self.gc_threshold,
+            pinned: HashMap::new(),
+            gc_stats: GcStats::default(),
+            stats: Stats::default(),
+            levels: vec![LevelCache::default(); usize::from(Level::MAX_LEVEL.value()) + 1],
+            history: History::new(self.history_depth),
+            timeline: Timeline::new(self.timeline_interval),
+            frozen: Vec::new(),
+            parallel: self.parallel,
+            workers: Vec::new(),
+        };
+        for (level, limit) in self.level_cache_limits {
+            universe.levels[usize::from(level)].limit = Some(limit);
         }
+        universe.root = Some(universe.new_empty_tree(Level::new(3)));
+        universe
+    }
+}
+
+impl Default for UniverseBuilder {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    // TODO: remove
-    pub fn initialize(&mut self) {
-        self.root = Some(self.new_empty_tree(Level::new(3)));
+impl Default for Universe {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Universe {
     fn get_id(&mut self, node: Node) -> Id {
-        if let Some(id) = self.table.get_by_right(&node)
-        {
-            *id
+        if let Some(id) = self.table.get(&self.nodes, &node) {
+            self.collisions.check_hit(&self.nodes, &node, id);
+            id
         } else {
-            let id = Id(self.table.len());
-            self.table.insert(id, node);
+            let id = match self.free.pop() {
+                Some(id) => {
+                    self.nodes[id.index()] = node;
+                    id
+                }
+                None => {
+                    let id = u32::try_from(self.nodes.len())
+                        .ok()
+                        .map(Id)
+                        .filter(|&id| id != Id::VACANT)
+                        .expect("node arena is full");
+                    self.nodes.push(node);
+                    id
+                }
+            };
+            let node = &self.nodes[id.index()];
+            self.stats.hash_collisions += self.collisions.insert(&self.nodes, node, id);
+            self.table.insert(&self.nodes, id);
             id
         }
     }
 
-    fn new_leaf(&mut self, cell: Cell) -> Id {
+    pub(crate) fn new_leaf(&mut self, cell: Cell) -> Id {
         let node = Node::Leaf(Leaf::new(cell));
         self.get_id(node)
     }
 
-    fn new_inode(&mut self, nwx: Id, nex: Id, swx: Id, sex: Id) -> Id {
+    pub(crate) fn new_inode(&mut self, nwx: Id, nex: Id, swx: Id, sex: Id) -> Id {
         let childs = (
             nwx.node(self),
             nex.node(self),
@@ -84,6 +497,7 @@ impl Universe {
                     level: nw.level + 1,
                     population: nw.population + ne.population + sw.population + se.population,
                     result: None,
+                    slow_result: None,
                     nw: nwx,
                     ne: nex,
                     sw: swx,
@@ -94,9 +508,10 @@ impl Universe {
                 level: Level::new(1),
                 population: [nw, ne, sw, se]
                     .iter()
-                    .filter(|c| matches!(c.0, Cell::Alive))
-                    .count() as u32,
+                    .filter(|c| c.0 != Cell::Dead)
+                    .count() as u128,
                 result: None,
+                slow_result: None,
                 nw: nwx,
                 ne: nex,
                 sw: swx,
@@ -108,7 +523,20 @@ impl Universe {
         self.get_id(Node::Inode(inode))
     }
 
-    fn new_empty_tree(&mut self, level: Level) -> Id {
+    // results don't take part in the hash of a node, so clearing them leaves the table alone
+    fn clear_results(&mut self) {
+        for node in &mut self.nodes {
+            if let Node::Inode(inode) = node {
+                inode.clear_results();
+            }
+        }
+        for cache in &mut self.levels {
+            cache.cached.clear();
+        }
+        self.workers.clear();
+    }
+
+    pub(crate) fn new_empty_tree(&mut self, level: Level) -> Id {
         if level == Level::LEAF_LEVEL {
             self.new_leaf(Cell::Dead)
         } else {
@@ -119,7 +547,7 @@ impl Universe {
 }
 
 impl Universe {
-    fn get_tree_cell(&self, tree: Id, pos: impl Into<Position>) -> Cell {
+    pub(crate) fn get_tree_cell(&self, tree: Id, pos: impl Into<Position>) -> Cell {
         let pos = pos.into();
         match *tree.node(self) {
             Node::Leaf(c) => c.0,
@@ -127,6 +555,7 @@ impl Universe {
                 level,
                 population: _,
                 result: _,
+                slow_result: _,
                 nw,
                 ne,
                 sw,
@@ -148,7 +577,7 @@ impl Universe {
         }
     }
 
-    fn set_tree_cell(&mut self, tree: Id, pos: impl Into<Position>, state: Cell) -> Id {
+    pub(crate) fn set_tree_cell(&mut self, tree: Id, pos: impl Into<Position>, state: Cell) -> Id {
         let pos = pos.into();
 
         match *tree.node(self) {
@@ -157,6 +586,7 @@ impl Universe {
                 level,
                 population: _,
                 result: _,
+                slow_result: _,
                 nw,
                 ne,
                 sw,
@@ -199,41 +629,412 @@ impl Universe {
     }
 }
 
+pub struct LiveCells<'a> {
+    blocks: NodePopulations<'a>,
+}
+
+impl Iterator for LiveCells<'_> {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // dying cells are skipped
+        loop {
+            let (leaf, pos) = self.blocks.next_node()?;
+            if self.blocks.universe.get_tree_cell(leaf, Position::ORIGIN) == Cell::Alive {
+                return Some(pos);
+            }
+        }
+    }
+}
+
+// cells which are not dead together with their state
+pub struct Cells<'a> {
+    blocks: NodePopulations<'a>,
+}
+
+impl Iterator for Cells<'_> {
+    type Item = (Position, Cell);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (leaf, pos) = self.blocks.next_node()?;
+        Some((
+            pos,
+            self.blocks.universe.get_tree_cell(leaf, Position::ORIGIN),
+        ))
+    }
+}
+
+// non empty subtrees of one level as their north west corner and population
+pub struct NodePopulations<'a> {
+    universe: &'a Universe,
+    rect: Option<Rect>,
+    level: Level,
+    // subtrees left to visit together with their north west corner
+    stack: Vec<(Id, Position)>,
+}
+
+impl<'a> NodePopulations<'a> {
+    fn new(universe: &'a Universe, rect: Option<Rect>, level: u8) -> Self {
+        let root = universe.root.unwrap();
+        let root_level = root.node(universe).level();
+        let min = root_level.min_pos();
+        Self {
+            universe,
+            rect,
+            level: Level::new(level.min(root_level.value())),
+            stack: vec![(root, min)],
+        }
+    }
+}
+
+impl NodePopulations<'_> {
+    // the next non empty subtree of the level and its north west corner
+    fn next_node(&mut self) -> Option<(Id, Position)> {
+        while let Some((tree, min)) = self.stack.pop() {
+            let node = tree.node(self.universe);
+            if node.population() == 0 {
+                continue;
+            }
+            let side = node.level().side_len() as i64;
+            if let Some(rect) = self.rect {
+                if !rect.intersects(Rect::new(min, min + Offset::new(side - 1, side - 1))) {
+                    continue;
+                }
+            }
+            if node.level() == self.level {
+                return Some((tree, min));
+            }
+            if let Node::Inode(Inode { nw, ne, sw, se, .. }) = *node {
+                let half = side / 2;
+                self.stack.push((se, min + Offset::new(half, half)));
+                self.stack.push((sw, min + Offset::new(0, half)));
+                self.stack.push((ne, min + Offset::new(half, 0)));
+                self.stack.push((nw, min));
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for NodePopulations<'_> {
+    type Item = (Position, u128);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node()
+            .map(|(tree, min)| (min, tree.node(self.universe).population()))
+    }
+}
+
+// the time from the call until the returned closure is called
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn stopwatch() -> impl FnOnce() -> Duration {
+    let start = std::time::Instant::now();
+    move || start.elapsed()
+}
+
+// `Instant::now` panics on wasm32-unknown-unknown, which has no clock
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn stopwatch() -> impl FnOnce() -> Duration {
+    || Duration::ZERO
+}
+
+// one round of splitmix64
+// seeds the mix of the upper half of `content_hash128`
+pub(crate) const HIGH_SEED: u64 = 0x6a09_e667_f3bc_c908;
+
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// `content_hash128` of cells moved by `-origin`, so that the same cells hash the same wherever
+// they are when `origin` moves with them
+pub(crate) fn cells_hash128(
+    cells: impl Iterator<Item = (Position, Cell)>,
+    origin: Position,
+) -> u128 {
+    let (low, high) = cells.fold((0u64, 0u64), |(low, high), (pos, cell)| {
+        let (x, y) = (pos.x - origin.x, pos.y - origin.y);
+        // alive cells hash like they did before there were dying states
+        let state = match cell {
+            Cell::Dying(state) => Some(mix(u64::from(state))),
+            _ => None,
+        };
+        let lane = |seed: u64| {
+            let y = mix(y as u64 ^ seed ^ state.unwrap_or(0));
+            mix(x as u64 ^ seed ^ y)
+        };
+        (
+            low.wrapping_add(lane(0)),
+            high.wrapping_add(lane(HIGH_SEED)),
+        )
+    });
+    u128::from(high) << 64 | u128::from(low)
+}
+
+// interleaves the bits of x and y (z-order), so that every subtree covers a contiguous key range
+// with the quadrants ordered nw, ne, sw, se
+fn morton_key(dx: u64, dy: u64) -> u128 {
+    fn spread(v: u64) -> u128 {
+        let mut v = u128::from(v);
+        v = (v | (v << 32)) & 0x0000_0000_ffff_ffff_0000_0000_ffff_ffff;
+        v = (v | (v << 16)) & 0x0000_ffff_0000_ffff_0000_ffff_0000_ffff;
+        v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff_00ff_00ff_00ff_00ff;
+        v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f;
+        v = (v | (v << 2)) & 0x3333_3333_3333_3333_3333_3333_3333_3333;
+        v = (v | (v << 1)) & 0x5555_5555_5555_5555_5555_5555_5555_5555;
+        v
+    }
+    spread(dx) | spread(dy) << 1
+}
+
+impl Universe {
+    // `cells` are sorted by their morton key relative to the north west corner of the root
+    fn set_tree_cells(&mut self, tree: Id, cells: &[(u128, Cell)]) -> Id {
+        if cells.is_empty() {
+            return tree;
+        }
+        match *tree.node(self) {
+            Node::Leaf(_) => self.new_leaf(cells[cells.len() - 1].1),
+            Node::Inode(Inode {
+                level,
+                nw,
+                ne,
+                sw,
+                se,
+                ..
+            }) => {
+                let shift = 2 * (level.value() - 1);
+                let quadrant = |key: u128| (key >> shift) & 0b11;
+                let ne_start = cells.partition_point(|&(key, _)| quadrant(key) < 1);
+                let sw_start = cells.partition_point(|&(key, _)| quadrant(key) < 2);
+                let se_start = cells.partition_point(|&(key, _)| quadrant(key) < 3);
+
+                let nw = self.set_tree_cells(nw, &cells[..ne_start]);
+                let ne = self.set_tree_cells(ne, &cells[ne_start..sw_start]);
+                let sw = self.set_tree_cells(sw, &cells[sw_start..se_start]);
+                let se = self.set_tree_cells(se, &cells[se_start..]);
+                self.new_inode(nw, ne, sw, se)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Edge {
+    West,
+    East,
+    North,
+    South,
+}
+
 impl Universe {
-    fn expand(&mut self) {
-        let level = self.root.unwrap().inode(self).level;
+    // outermost coordinate of a live cell in the direction of `edge`
+    // `min` is the north west corner of `tree`, empty subtrees are skipped
+    fn tree_bound(&self, tree: Id, min: Position, edge: Edge) -> Option<i64> {
+        let node = tree.node(self);
+        if node.population() == 0 {
+            return None;
+        }
+        match *node {
+            Node::Leaf(_) => Some(match edge {
+                Edge::West | Edge::East => min.x,
+                Edge::North | Edge::South => min.y,
+            }),
+            Node::Inode(Inode {
+                level,
+                nw,
+                ne,
+                sw,
+                se,
+                ..
+            }) => {
+                let half = (level.side_len() / 2) as i64;
+                let children = [
+                    (nw, min),
+                    (ne, min + Offset::new(half, 0)),
+                    (sw, min + Offset::new(0, half)),
+                    (se, min + Offset::new(half, half)),
+                ];
+                // the half closer to the edge is searched first
+                let (near, far) = match edge {
+                    Edge::West => ([0, 2], [1, 3]),
+                    Edge::East => ([1, 3], [0, 2]),
+                    Edge::North => ([0, 1], [2, 3]),
+                    Edge::South => ([2, 3], [0, 1]),
+                };
+                let search = |half: [usize; 2]| {
+                    let bounds = half
+                        .iter()
+                        .filter_map(|&i| self.tree_bound(children[i].0, children[i].1, edge));
+                    match edge {
+                        Edge::West | Edge::North => bounds.min(),
+                        Edge::East | Edge::South => bounds.max(),
+                    }
+                };
+                search(near).or_else(|| search(far))
+            }
+        }
+    }
+
+    // `min` is the north west corner of `tree`
+    fn map_tree(
+        &mut self,
+        tree: Id,
+        min: Position,
+        rect: Rect,
+        f: &impl Fn(Position, Cell) -> Cell,
+    ) -> Id {
+        let side = tree.node(self).level().side_len() as i64;
+        let area = Rect::new(min, min + Offset::new(side - 1, side - 1));
+        if !area.intersects(rect) {
+            return tree;
+        }
+
+        match *tree.node(self) {
+            Node::Leaf(leaf) => self.new_leaf(f(min, leaf.0)),
+            Node::Inode(Inode { nw, ne, sw, se, .. }) => {
+                let half = side / 2;
+                let nw = self.map_tree(nw, min, rect, f);
+                let ne = self.map_tree(ne, min + Offset::new(half, 0), rect, f);
+                let sw = self.map_tree(sw, min + Offset::new(0, half), rect, f);
+                let se = self.map_tree(se, min + Offset::new(half, half), rect, f);
+                self.new_inode(nw, ne, sw, se)
+            }
+        }
+    }
+}
+
+impl Universe {
+    fn expand_to(&mut self, pos: Position) {
+        while !pos.in_bounds(self.root.unwrap().node(self).level()) {
+            self.expand();
+        }
+    }
+
+    pub(crate) fn expand(&mut self) {
+        self.root = Some(self.expand_tree(self.root.unwrap()));
+    }
+
+    // surrounds the tree with an empty border, doubling its side length
+    pub(crate) fn expand_tree(&mut self, tree: Id) -> Id {
+        let level = tree.inode(self).level;
         let border = self.new_empty_tree(level - 1);
-        let (root_nw, root_ne, root_sw, root_se) = {
-            let root = self.root.unwrap().inode(self);
-            (root.nw, root.ne, root.sw, root.se)
+        let (tree_nw, tree_ne, tree_sw, tree_se) = {
+            let tree = tree.inode(self);
+            (tree.nw, tree.ne, tree.sw, tree.se)
         };
         let (nw, ne, sw, se) = (
-            self.new_inode(border, border, border, root_nw),
-            self.new_inode(border, border, root_ne, border),
-            self.new_inode(border, root_sw, border, border),
-            self.new_inode(root_se, border, border, border),
+            self.new_inode(border, border, border, tree_nw),
+            self.new_inode(border, border, tree_ne, border),
+            self.new_inode(border, tree_sw, border, border),
+            self.new_inode(tree_se, border, border, border),
         );
-        self.root = Some(self.new_inode(nw, ne, sw, se));
+        self.new_inode(nw, ne, sw, se)
     }
 
-    // since recursive make second function which always calls on root
-    fn evolve_tree(&mut self, tree: Id) -> Id {
-        {
-            let inode = tree.inode(self);
-            debug_assert!(inode.level >= Level::new(2), "must be level 2 or higher");
+    // copies a tree of another universe into this one, `imported` maps already copied nodes
+    pub(crate) fn import_tree(
+        &mut self,
+        other: &Universe,
+        tree: Id,
+        imported: &mut HashMap<Id, Id>,
+    ) -> Id {
+        if let Some(&id) = imported.get(&tree) {
+            return id;
         }
+        let id = match *tree.node(other) {
+            Node::Leaf(leaf) => self.new_leaf(leaf.0),
+            Node::Inode(Inode { nw, ne, sw, se, .. }) => {
+                let nw = self.import_tree(other, nw, imported);
+                let ne = self.import_tree(other, ne, imported);
+                let sw = self.import_tree(other, sw, imported);
+                let se = self.import_tree(other, se, imported);
+                self.new_inode(nw, ne, sw, se)
+            }
+        };
+        imported.insert(tree, id);
+        id
+    }
 
-        if let Some(result) = tree.inode(self).result {
+    // every node of the tree `id` after the ones it refers to, the results too with `cache`, which
+    // are a level below their node so there are no cycles
+    #[cfg(any(feature = "formats-extra", feature = "serde"))]
+    pub(crate) fn order_nodes(
+        &self,
+        id: Id,
+        cache: bool,
+        indices: &mut HashMap<Id, usize>,
+        order: &mut Vec<Id>,
+    ) {
+        if indices.contains_key(&id) {
+            return;
+        }
+        if let Node::Inode(inode) = id.node(self) {
+            for child in [inode.nw, inode.ne, inode.sw, inode.se] {
+                self.order_nodes(child, cache, indices, order);
+            }
+            for result in inode.results().filter(|_| cache) {
+                self.order_nodes(result, cache, indices, order);
+            }
+        }
+        indices.insert(id, order.len());
+        order.push(id);
+    }
+
+    // brings the root and a foreign tree (both centered on the origin) to the same level
+    pub(crate) fn align_with_root(&mut self, other: &Universe) -> (Id, Id) {
+        let mut tree = self.import_tree(other, other.root(), &mut HashMap::new());
+        while tree.node(self).level() > self.root.unwrap().node(self).level() {
+            self.expand();
+        }
+        while tree.node(self).level() < self.root.unwrap().node(self).level() {
+            tree = self.expand_tree(tree);
+        }
+        (self.root.unwrap(), tree)
+    }
+
+    // returns the center of `tree` (one level lower) advanced by 2^min(step_log, level - leaf)
+    // generations, where leaf is the level evolved by brute force, 2 for the 3x3 neighborhood
+    // the result is cached on the node, the full step and one smaller step side by side
+    pub(crate) fn evolve_tree(&mut self, tree: Id, step_log: u8) -> Id {
+        let level = tree.inode(self).level;
+        let leaf = self.leaf_evolve_level();
+        debug_assert!(level >= leaf, "must be level {} or higher", leaf);
+        let step_log = step_log.min(level.value() - leaf);
+
+        let cached = self.cached_result(tree, step_log);
+        self.count_lookup(level, cached.is_some());
+        if let Some(result) = cached {
             return result;
         }
 
-        if tree.inode(self).level == 2 {
-            self.manual_evolve(tree)
+        let (tree_nw, tree_ne, tree_sw, tree_se) = {
+            let inode = tree.inode(self);
+            (inode.nw, inode.ne, inode.sw, inode.se)
+        };
+        let result = if level == leaf {
+            match self.backend {
+                Backend::Life => self.manual_evolve(tree),
+                Backend::LargerThanLife(rule) => self.convolve(rule, tree),
+            }
+        } else if step_log == level.value() - leaf {
+            // full speed: both stages advance by 2^(level - 3)
+            let n01 = self.join_horizontal(tree_nw, tree_ne);
+            let n10 = self.join_vertical(tree_nw, tree_sw);
+            let n11 = self.centered_sub(tree);
+            let n12 = self.join_vertical(tree_ne, tree_se);
+            let n21 = self.join_horizontal(tree_sw, tree_se);
+
+            let n = self.evolve_all(
+                [tree_nw, n01, tree_ne, n10, n11, n12, tree_sw, n21, tree_se],
+                step_log,
+            );
+            self.evolve_quadrants(n, step_log)
         } else {
-            let (tree_nw, tree_ne, tree_sw, tree_se) = {
-                let inode = tree.inode(self);
-                (inode.nw, inode.ne, inode.sw, inode.se)
-            };
+            // only the second stage advances
             let n00 = self.centered_sub(tree_nw);
             let n01 = self.centered_horizontal(tree_nw, tree_ne);
             let n02 = self.centered_sub(tree_ne);
@@ -244,27 +1045,84 @@ impl Universe {
             let n21 = self.centered_horizontal(tree_sw, tree_se);
             let n22 = self.centered_sub(tree_se);
 
-            let (nw, ne, sw, se) = {
-                let nw = self.new_inode(n00, n01, n10, n11);
-                let ne = self.new_inode(n01, n02, n11, n12);
-                let sw = self.new_inode(n10, n11, n20, n21);
-                let se = self.new_inode(n11, n12, n21, n22);
-                (
-                    self.evolve_tree(nw),
-                    self.evolve_tree(ne),
-                    self.evolve_tree(sw),
-                    self.evolve_tree(se),
-                )
-            };
-            let result = self.new_inode(nw, ne, sw, se);
+            self.evolve_quadrants([n00, n01, n02, n10, n11, n12, n20, n21, n22], step_log)
+        };
+
+        self.cache_result(tree, step_log, result);
+        result
+    }
 
-            if let (id, Node::Inode(mut inode)) = self.table.remove_by_left(&tree).unwrap() {
+    // the result of `tree` if it was computed for the step already
+    pub(crate) fn cached_result(&self, tree: Id, step_log: u8) -> Option<Id> {
+        let inode = tree.inode(self);
+        if step_log == inode.level.value() - self.leaf_evolve_level() {
+            return inode.result;
+        }
+        match inode.slow_result {
+            Some((cached_step, result)) if cached_step == step_log => Some(result),
+            _ => None,
+        }
+    }
+
+    // a smaller step replaces the smaller step cached before, but leaves the full one alone
+    pub(crate) fn cache_result(&mut self, tree: Id, step_log: u8, result: Id) {
+        let leaf = self.leaf_evolve_level();
+        if let Node::Inode(inode) = &mut self.nodes[tree.index()] {
+            let cache = &mut self.levels[usize::from(inode.level.value())];
+            if cache.limit.is_some() && !inode.has_result() {
+                cache.cached.push_back(tree);
+            }
+            if step_log == inode.level.value() - leaf {
                 inode.result = Some(result);
-                self.table.insert(id, Node::Inode(inode));
+            } else {
+                inode.slow_result = Some((step_log, result));
             }
+            let level = inode.level.value();
+            self.trim_cache(level);
+        }
+    }
+
+    // drops the oldest results of the level until it is within its limit
+    pub(crate) fn trim_cache(&mut self, level: u8) {
+        let cache = &mut self.levels[usize::from(level)];
+        if let Some(limit) = cache.limit {
+            while cache.cached.len() > limit {
+                let tree = cache.cached.pop_front().unwrap();
+                if let Node::Inode(inode) = &mut self.nodes[tree.index()] {
+                    inode.clear_results();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn count_lookup(&mut self, level: Level, hit: bool) {
+        let cache = &mut self.levels[usize::from(level.value())];
+        if hit {
+            self.stats.cache_hits += 1;
+            cache.hits += 1;
+        } else {
+            self.stats.cache_misses += 1;
+            cache.misses += 1;
+        }
+    }
+
+    // combines the 3x3 grid of subnodes into four overlapping nodes and evolves them
+    fn evolve_quadrants(&mut self, n: [Id; 9], step_log: u8) -> Id {
+        let nw = self.new_inode(n[0], n[1], n[3], n[4]);
+        let ne = self.new_inode(n[1], n[2], n[4], n[5]);
+        let sw = self.new_inode(n[3], n[4], n[6], n[7]);
+        let se = self.new_inode(n[4], n[5], n[7], n[8]);
+        let [nw, ne, sw, se] = self.evolve_all([nw, ne, sw, se], step_log);
+        self.new_inode(nw, ne, sw, se)
+    }
 
-            result
+    // evolves independent trees of the same level, concurrently if they are large enough
+    fn evolve_all<const N: usize>(&mut self, trees: [Id; N], step_log: u8) -> [Id; N] {
+        #[cfg(feature = "parallel")]
+        if self.evolves_concurrently(trees[0]) {
+            return self.evolve_concurrently(trees, step_log);
         }
+        trees.map(|tree| self.evolve_tree(tree, step_log))
     }
 
     // Inode at level 2 contains 16 cells
@@ -284,48 +1142,74 @@ impl Universe {
             "manual evolution only at level 2 possible"
         );
 
+        // only alive cells count as neighbors, the states are needed for the centers
+        let mut cells = [Cell::Dead; 16];
         let mut all_bits: u16 = 0;
         for y in -2..2 {
             for x in -2..2 {
-                all_bits = (all_bits << 1) + self.get_tree_cell(node, (x, y)) as u16;
+                let cell = self.get_tree_cell(node, (x, y));
+                cells[((y + 2) * 4 + x + 2) as usize] = cell;
+                all_bits = (all_bits << 1) + u16::from(cell == Cell::Alive);
             }
         }
-        let (nw, ne, sw, se) = (
-            self.one_gen(all_bits >> 5),
-            self.one_gen(all_bits >> 4),
-            self.one_gen(all_bits >> 1),
-            self.one_gen(all_bits),
-        );
+        let alive = self.kernel.evolve(all_bits);
+        let [nw, ne, sw, se] =
+            [(cells[5], 3), (cells[6], 2), (cells[9], 1), (cells[10], 0)].map(|(center, bit)| {
+                // dying cells never come back
+                let alive = alive >> bit & 1 == 1 && !matches!(center, Cell::Dying(_));
+                let cell = self.rule.transition(center, alive);
+                self.new_leaf(cell)
+            });
 
         self.new_inode(nw, ne, sw, se)
     }
 
-    // to update a cell be have to look at 9 cells (itself and the 8 directly adjecent ones)
-    // so we still have to use a u16 bitmap.
-
-    // the bottom three bits a..=c are the south neighbors
-    // bits e..=g are the current row with 5 being the cell itself
-    // i..=k are the north neighbors
-
-    #[allow(clippy::inconsistent_digit_grouping)]
-    fn one_gen(&mut self, mut bitmask: u16) -> Id {
-        if bitmask == 0 {
-            return self.new_leaf(Cell::Dead);
-        }
+    /// the name of the kernel which evolves the leaves
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// // popcount if the cpu has the instruction
+    /// assert!(["popcount", "bitboard"].contains(&Universe::new().kernel()));
+    /// let rule = "B2-a/S12".parse().unwrap();
+    /// assert_eq!(Universe::builder().rule(rule).build().kernel(), "table");
+    /// ```
+    pub fn kernel(&self) -> &'static str {
+        self.kernel.name()
+    }
 
-        let center = (bitmask >> 5) & 1;
-        bitmask &= 0b00000__111_0101_0111; // mask out bits we don't care about
-        let neighbor_count = bitmask.count_ones();
-        if neighbor_count == 3 || (neighbor_count == 2 && center != 0) {
-            self.new_leaf(Cell::Alive)
-        } else {
-            self.new_leaf(Cell::Dead)
-        }
+    /// evolve the leaves with another kernel, which gives the same results
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, KernelKind};
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.set_kernel(KernelKind::Lookup);
+    /// universe.advance(4);
+    /// assert_eq!(universe.kernel(), "lookup");
+    /// assert_eq!(universe.population(), 5);
+    /// ```
+    pub fn set_kernel(&mut self, kind: KernelKind) {
+        self.kernel_kind = kind;
+        self.kernel = LeafKernel::new(kind, self.rule);
     }
 }
 
 // this can move into another class, when the refactoring of the leaves to Bool8x8 has been done.
 impl Universe {
+    // node of the same level straddling the border between two horizontally adjacent nodes
+    fn join_horizontal(&mut self, west: Id, east: Id) -> Id {
+        let (west, east) = (west.inode(self), east.inode(self));
+        let (nw, ne, sw, se) = (west.ne, east.nw, west.se, east.sw);
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    fn join_vertical(&mut self, north: Id, south: Id) -> Id {
+        let (north, south) = (north.inode(self), south.inode(self));
+        let (nw, ne, sw, se) = (north.sw, north.se, south.nw, south.ne);
+        self.new_inode(nw, ne, sw, se)
+    }
+
     fn centered_horizontal(&mut self, west: Id, east: Id) -> Id {
         let (west, east) = (west.inode(self), east.inode(self));
         debug_assert!(west.level == east.level, "levels must be the same");
@@ -381,20 +1265,73 @@ impl Universe {
 
 // Universe API
 impl Universe {
+    /// the universe grows as needed
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.set_cell((1_000_000, -7), Cell::Alive);
+    /// assert_eq!(universe.get_cell((1_000_000, -7)), Cell::Alive);
+    /// ```
     pub fn set_cell(&mut self, pos: impl Into<Position>, cell: Cell) {
+        self.record_edit();
         let pos = pos.into();
+        self.expand_to(pos);
+        self.root = Some(self.set_tree_cell(self.root.unwrap(), pos, cell));
+    }
 
-        loop {
-            let level = self.root.unwrap().node(self).level();
-            if pos.in_bounds(level) {
-                break;
-            }
-            self.expand();
+    /// builds all touched subtrees once instead of one path per cell
+    /// if a position occurs multiple times the last cell wins
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.set_cells(vec![
+    ///     (Position::new(0, 0), Cell::Alive),
+    ///     (Position::new(1, 0), Cell::Alive),
+    ///     (Position::new(0, 0), Cell::Dead),
+    /// ]);
+    /// assert_eq!(universe.live_cells().collect::<Vec<_>>(), [Position::new(1, 0)]);
+    /// ```
+    pub fn set_cells(&mut self, cells: impl IntoIterator<Item = (Position, Cell)>) {
+        self.record_edit();
+        let cells: Vec<(Position, Cell)> = cells.into_iter().collect();
+        if let (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) = (
+            cells.iter().map(|(pos, _)| pos.x).min(),
+            cells.iter().map(|(pos, _)| pos.y).min(),
+            cells.iter().map(|(pos, _)| pos.x).max(),
+            cells.iter().map(|(pos, _)| pos.y).max(),
+        ) {
+            self.expand_to(Position::new(min_x, min_y));
+            self.expand_to(Position::new(max_x, max_y));
         }
 
-        self.root = Some(self.set_tree_cell(self.root.unwrap(), pos, cell));
+        let root = self.root.unwrap();
+        let min = root.node(self).level().min_pos();
+        let mut keyed: Vec<(u128, Cell)> = cells
+            .into_iter()
+            .map(|(pos, cell)| {
+                let key = morton_key((pos.x - min.x) as u64, (pos.y - min.y) as u64);
+                (key, cell)
+            })
+            .collect();
+        // the sort is stable, so the last of equal keys is the last one given
+        keyed.sort_by_key(|&(key, _)| key);
+
+        self.root = Some(self.set_tree_cells(root, &keyed));
     }
 
+    /// cells outside of the tree are dead
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("o!").unwrap();
+    /// assert_eq!(universe.get_cell((0, 0)), Cell::Alive);
+    /// assert_eq!(universe.get_cell((i64::MAX, 0)), Cell::Dead);
+    /// ```
     pub fn get_cell(&self, pos: impl Into<Position>) -> Cell {
         let pos = pos.into();
         let root = self.root.unwrap();
@@ -406,65 +1343,837 @@ impl Universe {
         }
     }
 
+    pub(crate) fn root(&self) -> Id {
+        self.root.unwrap()
+    }
+
+    pub(crate) fn set_root(&mut self, root: Id) {
+        debug_assert!(
+            root.node(self).level() >= 3,
+            "root must be level 3 or higher"
+        );
+        self.root = Some(root);
+    }
+
+    /// lazily walks the quadtree, skipping empty subtrees
+    /// for drawing zoomed out views, a block of level `level` covers 2^level x 2^level cells
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// // a block fills exactly one 2x2 block
+    /// let universe = Universe::from_rle("2o$2o!").unwrap();
+    /// let blocks: Vec<_> = universe
+    ///     .node_population_in(Rect::new((-8, -8), (8, 8)), 1)
+    ///     .collect();
+    /// assert_eq!(blocks, [(Position::ORIGIN, 4)]);
+    /// ```
+    pub fn node_population_in(&self, rect: Rect, level: u8) -> NodePopulations<'_> {
+        NodePopulations::new(self, Some(rect), level)
+    }
+
+    /// a universe with the default rule in which exactly the given cells are alive,
+    /// duplicates are allowed
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_cells(&[(0, 0), (1, 0), (2, 0), (1, 0)]);
+    /// assert_eq!(universe.population(), 3);
+    /// ```
+    pub fn from_cells(cells: &[(i64, i64)]) -> Self {
+        let mut universe = Self::new();
+        universe.set_cells(cells.iter().map(|&pos| (pos.into(), Cell::Alive)));
+        universe
+    }
+
+    /// the live cells as (x, y) pairs, sorted by x and then y
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_cells(&[(2, 0), (0, 0), (1, 0)]);
+    /// assert_eq!(universe.to_cells(), [(0, 0), (1, 0), (2, 0)]);
+    /// universe.evolve();
+    /// assert_eq!(universe.to_cells(), [(1, -1), (1, 0), (1, 1)]);
+    /// ```
+    pub fn to_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells: Vec<(i64, i64)> = self.live_cells().map(|pos| (pos.x, pos.y)).collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// positions of all live cells, in no particular order
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("3o!").unwrap();
+    /// let mut cells: Vec<_> = universe.live_cells().collect();
+    /// cells.sort();
+    /// assert_eq!(cells, [(0, 0), (1, 0), (2, 0)].map(Position::from));
+    /// ```
+    pub fn live_cells(&self) -> LiveCells<'_> {
+        LiveCells {
+            blocks: NodePopulations::new(self, None, 0),
+        }
+    }
+
+    /// positions of the live cells within `rect`, in no particular order
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("3o!").unwrap();
+    /// let cells: Vec<_> = universe.live_cells_in(Rect::new((2, -1), (5, 1))).collect();
+    /// assert_eq!(cells, [Position::new(2, 0)]);
+    /// ```
+    pub fn live_cells_in(&self, rect: Rect) -> LiveCells<'_> {
+        LiveCells {
+            blocks: NodePopulations::new(self, Some(rect), 0),
+        }
+    }
+
+    /// the cells within `rect` which are not dead and their states, in no particular order
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::builder().rule("B2/S/C3".parse().unwrap()).build();
+    /// universe.set_cells([(0, 0), (1, 0)].map(|pos| (pos.into(), Cell::Alive)));
+    /// universe.evolve();
+    /// let mut cells: Vec<_> = universe.cells_in(Rect::new((0, -1), (1, 1))).collect();
+    /// cells.sort();
+    /// assert_eq!(
+    ///     cells,
+    ///     [
+    ///         (Position::new(0, -1), Cell::Alive),
+    ///         (Position::new(0, 0), Cell::Dying(2)),
+    ///         (Position::new(0, 1), Cell::Alive),
+    ///         (Position::new(1, -1), Cell::Alive),
+    ///         (Position::new(1, 0), Cell::Dying(2)),
+    ///         (Position::new(1, 1), Cell::Alive),
+    ///     ]
+    /// );
+    /// ```
+    pub fn cells_in(&self, rect: Rect) -> Cells<'_> {
+        Cells {
+            blocks: NodePopulations::new(self, Some(rect), 0),
+        }
+    }
+
+    /// only the subtrees intersecting `rect` are rebuilt
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("o!").unwrap();
+    /// universe.map_region(Rect::new((0, 0), (1, 1)), |_, cell| !cell);
+    /// assert_eq!(universe.get_cell((0, 0)), Cell::Dead);
+    /// assert_eq!(universe.population(), 3);
+    /// ```
+    pub fn map_region(&mut self, rect: Rect, f: impl Fn(Position, Cell) -> Cell) {
+        self.record_edit();
+        self.expand_to(rect.min);
+        self.expand_to(rect.max);
+        let root = self.root.unwrap();
+        let min = root.node(self).level().min_pos();
+        self.root = Some(self.map_tree(root, min, rect, &f));
+    }
+
+    /// advances by a single generation
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// universe.evolve();
+    /// assert_eq!(universe.get_cell((1, -1)), Cell::Alive);
+    /// assert_eq!(universe.get_cell((0, 0)), Cell::Dead);
+    /// ```
     pub fn evolve(&mut self) {
-        loop {
-            let iroot = self.root.unwrap().inode(self);
-            let (nw_pop, ne_pop, sw_pop, se_pop) = (
-                iroot.nw.node(self).population(),
-                iroot.ne.node(self).population(),
-                iroot.sw.node(self).population(),
-                iroot.se.node(self).population(),
-            );
+        self.advance(1);
+    }
 
-            let (nw_inner_pop, ne_inner_pop, sw_inner_pop, se_inner_pop) = (
-                iroot
-                    .nw
-                    .inode(self)
-                    .se
-                    .inode(self)
-                    .se
-                    .node(self)
-                    .population(),
-                iroot
-                    .ne
-                    .inode(self)
-                    .sw
-                    .inode(self)
-                    .sw
-                    .node(self)
-                    .population(),
-                iroot
-                    .sw
-                    .inode(self)
-                    .ne
-                    .inode(self)
-                    .ne
-                    .node(self)
-                    .population(),
-                iroot
-                    .se
-                    .inode(self)
-                    .nw
-                    .inode(self)
-                    .nw
-                    .node(self)
-                    .population(),
-            );
+    /// decomposes the generation count into powers of two and applies a superstep for each
+    /// panics above `Universe::MAX_FROZEN_ADVANCE` generations while a region is frozen, see
+    /// `freeze`
+    ///
+    /// The largest superstep is 2^60 generations, as the coordinates are 64 bits. Larger counts
+    /// repeat it until the universe comes back to an earlier state and skip the rest of the
+    /// cycles, so they finish for every pattern which stays within the coordinates, while
+    /// patterns which keep growing or moving panic once they leave them. Panics if the
+    /// generation count overflows.
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// universe.advance(1 << 80);
+    /// assert_eq!(universe.generation(), 1 << 80);
+    /// assert_eq!(universe.get_cell((1, 0)), Cell::Alive);
+    /// assert_eq!(universe.population(), 3);
+    /// ```
+    pub fn advance(&mut self, generations: u128) {
+        assert!(
+            self.frozen.is_empty() || generations <= Self::MAX_FROZEN_ADVANCE,
+            "cannot advance more than {} generations at once while a region is frozen",
+            Self::MAX_FROZEN_ADVANCE
+        );
+        assert!(
+            self.generation.checked_add(generations).is_some(),
+            "the generation count overflows"
+        );
+        let elapsed = stopwatch();
+        for step_log in 0..128 {
+            if generations & (1 << step_log) == 0 {
+                continue;
+            }
+            if step_log <= MAX_STEP_LOG {
+                self.superstep(step_log);
+            } else {
+                self.repeat_largest_step(1 << (step_log - MAX_STEP_LOG));
+            }
+        }
+        self.stats.evolve_time += elapsed();
+    }
 
-            if self.root.unwrap().node(self).level() >= 3
-                && nw_pop == nw_inner_pop
-                && ne_pop == ne_inner_pop
-                && sw_pop == sw_inner_pop
-                && se_pop == se_inner_pop
-            {
-                break;
+    // runs `count` supersteps of 2^60 generations, a universe which stays within the coordinates
+    // that long is periodic, so once the root comes back the whole cycles are skipped
+    fn repeat_largest_step(&mut self, count: u128) {
+        // the roots after every step so far, pinned so that their ids aren't reused
+        let mut seen: HashMap<Id, u128> = HashMap::new();
+        let mut done = 0;
+        while done < count {
+            let root = self.root();
+            if let Some(&start) = seen.get(&root) {
+                let period = done - start;
+                let skipped = (count - done) / period * period;
+                self.generation += skipped << MAX_STEP_LOG;
+                done += skipped;
+                // fewer steps than a cycle are left
+                for (&root, _) in seen.iter() {
+                    self.unpin(root);
+                }
+                seen.clear();
+                while done < count {
+                    self.superstep(MAX_STEP_LOG);
+                    done += 1;
+                }
+                return;
+            }
+            seen.insert(root, done);
+            self.pin(root);
+            self.superstep(MAX_STEP_LOG);
+            done += 1;
+        }
+        for (&root, _) in seen.iter() {
+            self.unpin(root);
+        }
+    }
+
+    /// hashlife superspeed for an arbitrary number of generations, see `advance`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.step(4);
+    /// assert_eq!(universe.bounding_box(), Some(((1, 1).into(), (3, 3).into())));
+    /// ```
+    pub fn step(&mut self, generations: u64) {
+        self.advance(u128::from(generations));
+    }
+
+    /// advances to an absolute generation, panics if it has already passed
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// universe.advance(3);
+    /// universe.advance_to(10);
+    /// assert_eq!(universe.generation(), 10);
+    /// ```
+    pub fn advance_to(&mut self, generation: u128) {
+        assert!(
+            generation >= self.generation,
+            "cannot advance to a past generation"
+        );
+        self.advance(generation - self.generation);
+    }
+
+    /// number of generations evolved since creation
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.advance(7);
+    /// assert_eq!(universe.generation(), 7);
+    /// ```
+    pub fn generation(&self) -> u128 {
+        self.generation
+    }
+
+    // advances by 2^step_log generations
+    fn superstep(&mut self, step_log: u8) {
+        // frozen cells depend on their absolute position, which the cached results of larger
+        // steps know nothing about, `advance` bounds the generations this splits into
+        if !self.frozen.is_empty() && step_log > 0 {
+            for _ in 0..1u128 << step_log {
+                self.superstep(0);
+            }
+            return;
+        }
+        self.record_timeline();
+        // the pattern has to stay within the center of the root, which is 2^(level - 3)
+        // cells away from the border of the result, neighborhoods of a larger range spread
+        // further each generation
+        let spread = self.leaf_evolve_level() - 2;
+        loop {
+            let iroot = self.root.unwrap().inode(self);
+            if iroot.level >= step_log + spread + 3 {
+                let inner = [
+                    (iroot.nw, iroot.nw.inode(self).se.inode(self).se),
+                    (iroot.ne, iroot.ne.inode(self).sw.inode(self).sw),
+                    (iroot.sw, iroot.sw.inode(self).ne.inode(self).ne),
+                    (iroot.se, iroot.se.inode(self).nw.inode(self).nw),
+                ];
+                if inner.iter().all(|(quadrant, inner)| {
+                    quadrant.node(self).population() == inner.node(self).population()
+                }) {
+                    break;
+                }
             }
             self.expand();
         }
+        self.expand_to_frozen();
 
         let root = self.root.unwrap();
+        self.root = Some(self.evolve_tree(root, step_log));
+        self.restore_frozen(root);
+        if self.root.unwrap().node(self).level() < 3 {
+            self.expand();
+        }
+        self.generation += 1 << step_log;
+        // ids held during the step are gone, so this is the first safe point to collect
+        self.collect_garbage_if_needed();
+    }
+
+    /// inclusive north west and south east corners of the smallest rectangle containing all live cells
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// assert_eq!(universe.bounding_box(), Some(((0, 0).into(), (2, 2).into())));
+    /// assert_eq!(Universe::new().bounding_box(), None);
+    /// ```
+    pub fn bounding_box(&self) -> Option<(Position, Position)> {
+        let root = self.root.unwrap();
+        let min = root.node(self).level().min_pos();
+        Some((
+            Position::new(
+                self.tree_bound(root, min, Edge::West)?,
+                self.tree_bound(root, min, Edge::North)?,
+            ),
+            Position::new(
+                self.tree_bound(root, min, Edge::East)?,
+                self.tree_bound(root, min, Edge::South)?,
+            ),
+        ))
+    }
+
+    /// cells which are not dead, dying cells included, memoized per node so this is O(1)
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// assert_eq!(universe.population(), 5);
+    /// ```
+    pub fn population(&self) -> u128 {
+        self.root.unwrap().node(self).population()
+    }
+
+    /// identifies the live cells independently of the process and of how far the root is expanded
+    /// cell hashes are summed, so the order of iteration doesn't matter
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut a = Universe::from_rle("3o!").unwrap();
+    /// let b = Universe::from_rle("3o!").unwrap();
+    /// a.advance(2);
+    /// assert_eq!(a.content_hash(), b.content_hash());
+    /// a.evolve();
+    /// assert_ne!(a.content_hash(), b.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash128() as u64
+    }
+
+    /// `content_hash` with another 64 bits from a differently seeded mix, for comparing the
+    /// states of long runs where a 64-bit collision is not unlikely enough
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let start = universe.content_hash128();
+    /// assert_eq!(start as u64, universe.content_hash());
+    /// universe.advance(4);
+    /// assert_ne!(universe.content_hash128(), start);
+    /// ```
+    pub fn content_hash128(&self) -> u128 {
+        let cells = Cells {
+            blocks: NodePopulations::new(self, None, 0),
+        };
+        cells_hash128(cells, Position::ORIGIN)
+    }
+
+    /// the rule the universe evolves by
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::from_rle("x = 1, y = 1, rule = B36/S23\no!").unwrap();
+    /// assert_eq!(universe.rule(), Rule::new(&[3, 6], &[2, 3]));
+    /// ```
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// cached results are only valid for the rule they were computed with
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// // without survival the blinker dies out
+    /// universe.set_rule("B3/S".parse().unwrap());
+    /// universe.evolve();
+    /// assert_eq!(universe.population(), 2);
+    /// ```
+    pub fn set_rule(&mut self, rule: Rule) {
+        if rule != self.rule {
+            self.rule = rule;
+            self.kernel = LeafKernel::new(self.kernel_kind, rule);
+            self.clear_results();
+        }
+    }
+
+    /// how the next generation is computed
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Backend};
+    ///
+    /// assert_eq!(Universe::new().backend(), Backend::Life);
+    /// ```
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// evolves by a Larger than Life rule instead of the rule of the universe, or by the rule
+    /// again for `Backend::Life`
+    ///
+    /// ```
+    /// use lifeash::{prelude::*, Backend};
+    ///
+    /// // Conway's life written as a Larger than Life rule
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.set_backend(Backend::LargerThanLife("R1,C0,M0,S2..3,B3..3,NM".parse().unwrap()));
+    /// universe.advance(64);
+    /// assert_eq!(universe.bounding_box(), Some(((16, 16).into(), (18, 18).into())));
+    ///
+    /// // every cell which sees the single cell comes alive, which fills its neighborhood
+    /// let mut diamond = Universe::from_rle("o!").unwrap();
+    /// diamond.set_backend(Backend::LargerThanLife("R2,C0,M1,S1..1,B1..1,NN".parse().unwrap()));
+    /// diamond.evolve();
+    /// assert_eq!(diamond.population(), 13);
+    /// ```
+    pub fn set_backend(&mut self, backend: Backend) {
+        if backend != self.backend {
+            self.backend = backend;
+            self.clear_results();
+        }
+    }
+
+    /// whether large universes are evolved on several threads
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert!(!Universe::new().is_parallel());
+    /// ```
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// evolve on several threads or on the calling one, the results are the same
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.set_parallel(true);
+    /// universe.advance(4);
+    /// assert_eq!(universe.population(), 5);
+    /// ```
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+        if !parallel {
+            self.workers.clear();
+        }
+    }
+
+    /// estimated table size in bytes above which garbage is collected after a step, `None` if
+    /// it is only collected by `collect_garbage`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// assert_eq!(Universe::new().gc_threshold(), None);
+    /// ```
+    pub fn gc_threshold(&self) -> Option<usize> {
+        self.gc_threshold
+    }
+
+    /// changes the threshold of `UniverseBuilder::gc_threshold` while running, zero collects
+    /// after every step
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.set_gc_threshold(Some(0));
+    /// universe.advance(100);
+    /// assert!(universe.gc_stats().collections > 0);
+    /// assert_eq!(universe.gc_threshold(), Some(0));
+    /// ```
+    pub fn set_gc_threshold(&mut self, bytes: Option<usize>) {
+        self.gc_threshold = bytes;
+        // the workers are built again with their share of the new threshold
+        self.workers.clear();
+    }
+}
+
+impl Universe {
+    /// marks every node reachable from the root or a pinned node, including cached results,
+    /// and removes the rest from the table
+    /// returns the number of reclaimed nodes
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// universe.advance(1000);
+    /// let reclaimed = universe.collect_garbage();
+    /// assert_eq!(universe.gc_stats().last_reclaimed, reclaimed);
+    /// // the pattern itself survives
+    /// assert_eq!(universe.population(), 5);
+    /// ```
+    pub fn collect_garbage(&mut self) -> usize {
+        let mut marked = vec![false; self.nodes.len()];
+        let mut stack: Vec<Id> = self
+            .root
+            .iter()
+            .chain(self.pinned.keys())
+            .copied()
+            .collect();
+        while let Some(id) = stack.pop() {
+            if mem::replace(&mut marked[id.index()], true) {
+                continue;
+            }
+            if let Node::Inode(inode) = id.node(self) {
+                stack.extend([inode.nw, inode.ne, inode.sw, inode.se]);
+                stack.extend(inode.results());
+            }
+        }
+
+        // the slots keep their stale nodes until they are reused
+        let before = self.free.len();
+        let free = &mut self.free;
+        self.table.retain(&self.nodes, |id| {
+            if !marked[id.index()] {
+                free.push(id);
+            }
+            marked[id.index()]
+        });
+        let reclaimed = self.free.len() - before;
+        for &id in &self.free[before..] {
+            self.collisions.remove(&self.nodes[id.index()], id);
+        }
+        for cache in &mut self.levels {
+            cache.cached.retain(|id| marked[id.index()]);
+        }
+
+        self.gc_stats.collections += 1;
+        self.gc_stats.last_reclaimed = reclaimed;
+        self.gc_stats.total_reclaimed += reclaimed as u64;
+        reclaimed
+    }
+
+    /// counters describing the work done so far
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// universe.advance(10);
+    /// let stats = universe.stats();
+    /// assert_eq!(stats.generation, 10);
+    /// assert!(stats.nodes > 0);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        Stats {
+            generation: self.generation,
+            nodes: self.table.len(),
+            ..self.stats
+        }
+    }
+
+    /// the undo and redo stacks
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::builder().history_depth(4).build();
+    /// assert!(!universe.history().can_undo());
+    /// ```
+    pub fn history(&self) -> &History {
+        &self.history
+    }
 
-        self.root = Some(self.evolve_tree(root));
-        self.generation += 1;
+    /// the snapshots taken while evolving
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let universe = Universe::builder().timeline_interval(100).build();
+    /// assert_eq!(universe.timeline().generations().count(), 0);
+    /// ```
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    // gives `f` the history and the universe without it at the same time
+    pub(crate) fn with_history<R>(&mut self, f: impl FnOnce(&mut Self, &mut History) -> R) -> R {
+        let mut history = mem::take(&mut self.history);
+        let result = f(self, &mut history);
+        self.history = history;
+        result
+    }
+
+    /// the regions which don't evolve, see `Universe::freeze`
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// assert!(universe.frozen().is_empty());
+    /// universe.freeze(Rect::new((0, 0), (3, 3)));
+    /// assert_eq!(universe.frozen(), [Rect::new((0, 0), (3, 3))]);
+    /// ```
+    pub fn frozen(&self) -> &[Rect] {
+        &self.frozen
+    }
+
+    pub(crate) fn frozen_mut(&mut self) -> &mut Vec<Rect> {
+        &mut self.frozen
+    }
+
+    // like `with_history` for the timeline
+    pub(crate) fn with_timeline<R>(&mut self, f: impl FnOnce(&mut Self, &mut Timeline) -> R) -> R {
+        let mut timeline = mem::take(&mut self.timeline);
+        let result = f(self, &mut timeline);
+        self.timeline = timeline;
+        result
+    }
+
+    /// counters of the garbage collector
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.collect_garbage();
+    /// assert_eq!(universe.gc_stats().collections, 1);
+    /// ```
+    pub fn gc_stats(&self) -> GcStats {
+        GcStats {
+            live_nodes: self.table.len(),
+            ..self.gc_stats
+        }
+    }
+
+    /// makes room for at least `additional` more nodes, so the table is not resized while they
+    /// are created
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.reserve(1 << 16);
+    /// universe.set_cell((1000, 1000), Cell::Alive);
+    /// assert_eq!(universe.population(), 1);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+        self.table.reserve(&self.nodes, additional);
+    }
+
+    /// estimated size of the node arena and the table of their ids in bytes
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// let empty = universe.memory_usage();
+    /// universe.set_cell((100, 100), Cell::Alive);
+    /// assert!(universe.memory_usage() > empty);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        self.table.len() * NODE_BYTES + self.workers.iter().map(Worker::memory_usage).sum::<usize>()
+    }
+
+    pub(crate) fn live_ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.table.ids()
+    }
+
+    pub(crate) fn collect_garbage_if_needed(&mut self) {
+        if let Some(threshold) = self.gc_threshold {
+            if self.memory_usage() > threshold {
+                self.collect_garbage();
+            }
+        }
+    }
+
+    // keeps the tree alive across garbage collections until it is unpinned as often as pinned
+    pub(crate) fn pin(&mut self, tree: Id) {
+        *self.pinned.entry(tree).or_insert(0) += 1;
+    }
+
+    pub(crate) fn unpin(&mut self, tree: Id) {
+        if let Some(count) = self.pinned.get_mut(&tree) {
+            *count -= 1;
+            if *count == 0 {
+                self.pinned.remove(&tree);
+            }
+        }
+    }
+}
+
+// a saved state of a universe, cheap because the tree is shared with the live state
+// only valid for the universe it was taken from
+#[derive(Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    root: Id,
+    generation: u128,
+    // the cached results of the tree only hold for the same rule, backend and frozen regions
+    rule: Rule,
+    backend: Backend,
+    frozen: Vec<Rect>,
+}
+
+impl Snapshot {
+    /// the generation the snapshot was taken at
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::new();
+    /// universe.advance(3);
+    /// let snapshot = universe.snapshot();
+    /// assert_eq!(snapshot.generation(), 3);
+    /// universe.release(snapshot);
+    /// ```
+    pub fn generation(&self) -> u128 {
+        self.generation
+    }
+
+    pub(crate) fn root(&self) -> Id {
+        self.root
+    }
+}
+
+impl Universe {
+    /// the snapshot keeps its tree alive until it is released
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    /// let snapshot = universe.snapshot();
+    /// universe.advance(100);
+    /// universe.collect_garbage();
+    /// universe.restore(&snapshot);
+    /// assert_eq!(universe.generation(), 0);
+    /// assert_eq!(universe.get_cell((1, 0)), Cell::Alive);
+    /// universe.release(snapshot);
+    /// ```
+    pub fn snapshot(&mut self) -> Snapshot {
+        let root = self.root();
+        self.pin(root);
+        Snapshot {
+            root,
+            generation: self.generation,
+            rule: self.rule,
+            backend: self.backend,
+            frozen: self.frozen.clone(),
+        }
+    }
+
+    /// goes back to the state of the snapshot, which stays valid, with its rule, backend and
+    /// frozen regions
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// let snapshot = universe.snapshot();
+    /// universe.evolve();
+    /// universe.restore(&snapshot);
+    /// universe.evolve();
+    /// universe.restore(&snapshot);
+    /// assert_eq!(universe.generation(), 0);
+    /// universe.release(snapshot);
+    /// ```
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.set_rule(snapshot.rule);
+        self.set_backend(snapshot.backend);
+        self.frozen.clone_from(&snapshot.frozen);
+        self.root = Some(snapshot.root);
+        self.generation = snapshot.generation;
+    }
+
+    /// lets garbage collection reclaim the tree of the snapshot
+    ///
+    /// ```
+    /// use lifeash::prelude::*;
+    ///
+    /// let mut universe = Universe::from_rle("3o!").unwrap();
+    /// let snapshot = universe.snapshot();
+    /// universe.advance(64);
+    /// universe.release(snapshot);
+    /// universe.collect_garbage();
+    /// assert_eq!(universe.population(), 3);
+    /// ```
+    pub fn release(&mut self, snapshot: Snapshot) {
+        self.unpin(snapshot.root);
+    }
+
+    // the tree of the snapshot and the root, the smaller one expanded to the level of the other
+    // both are centered on the origin, so they are the same id exactly if the cells are the same
+    pub(crate) fn aligned_with(&mut self, snapshot: &Snapshot) -> (Id, Id) {
+        let (mut before, mut after) = (snapshot.root(), self.root());
+        while before.node(self).level() < after.node(self).level() {
+            before = self.expand_tree(before);
+        }
+        while after.node(self).level() < before.node(self).level() {
+            after = self.expand_tree(after);
+        }
+        (before, after)
     }
 }
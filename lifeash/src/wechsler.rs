@@ -0,0 +1,104 @@
+// the extended Wechsler format behind apgcodes, shared by `Pattern::to_apgcode` and the census,
+// see https://www.conwaylife.com/wiki/Apgcode
+
+use crate::{census::Kind, core::Position};
+
+// rows per strip, each column of a strip is one character
+pub(crate) const STRIP_HEIGHT: i64 = 5;
+// one character per column of a strip, with the top row as the lowest bit
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+// empty columns covered by the longest `y` run, y0 stands for four of them up to yz for 39
+const MAX_RUN: usize = 39;
+
+// the prefix tells the kind with the population or the period, the rest is the shortest code
+// of all phases and orientations, the first in ascii order among equally short ones
+pub(crate) fn apgcode(kind: Kind, period: u64, phases: &[Vec<Position>]) -> String {
+    let prefix = match kind {
+        Kind::StillLife => format!("xs{}", phases[0].len()),
+        Kind::Oscillator => format!("xp{}", period),
+        Kind::Ship => format!("xq{}", period),
+    };
+    let code = phases
+        .iter()
+        .flat_map(|cells| orientations(cells))
+        .map(|cells| wechsler(&cells))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap();
+    format!("{}_{}", prefix, code)
+}
+
+// the cells moved to the origin and sorted, together with the corner they were moved from
+pub(crate) fn normalized(cells: Vec<Position>) -> Option<(Vec<Position>, Position)> {
+    let min_x = cells.iter().map(|pos| pos.x).min()?;
+    let min_y = cells.iter().map(|pos| pos.y).min()?;
+    let mut cells: Vec<Position> = cells
+        .into_iter()
+        .map(|pos| Position::new(pos.x - min_x, pos.y - min_y))
+        .collect();
+    cells.sort_unstable();
+    Some((cells, Position::new(min_x, min_y)))
+}
+
+// the eight rotations and reflections, each moved to the origin
+fn orientations(cells: &[Position]) -> Vec<Vec<Position>> {
+    let mut orientations = Vec::with_capacity(8);
+    for swap in [false, true] {
+        for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+            let transformed = cells
+                .iter()
+                .map(|pos| {
+                    let (x, y) = if swap { (pos.y, pos.x) } else { (pos.x, pos.y) };
+                    Position::new(sx * x, sy * y)
+                })
+                .collect();
+            orientations.push(normalized(transformed).unwrap().0);
+        }
+    }
+    orientations
+}
+
+// strips of five rows separated by z, each column a character, with runs of empty columns
+// shortened and left out at the end of a strip
+fn wechsler(cells: &[Position]) -> String {
+    let width = cells.iter().map(|pos| pos.x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|pos| pos.y + 1).max().unwrap_or(0);
+    let strips = (height + STRIP_HEIGHT - 1) / STRIP_HEIGHT;
+    let mut columns = vec![vec![0; width as usize]; strips as usize];
+    for pos in cells {
+        columns[(pos.y / STRIP_HEIGHT) as usize][pos.x as usize] |= 1 << (pos.y % STRIP_HEIGHT);
+    }
+
+    let mut code = String::new();
+    for (i, strip) in columns.iter().enumerate() {
+        if i > 0 {
+            code.push('z');
+        }
+        let mut zeros = 0;
+        for &column in strip {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+            push_zeros(&mut code, zeros);
+            zeros = 0;
+            code.push(char::from(DIGITS[column]));
+        }
+    }
+    code
+}
+
+fn push_zeros(code: &mut String, mut zeros: usize) {
+    while zeros > 0 {
+        let run = zeros.min(MAX_RUN);
+        match run {
+            1 => code.push('0'),
+            2 => code.push('w'),
+            3 => code.push('x'),
+            _ => {
+                code.push('y');
+                code.push(char::from(DIGITS[run - 4]));
+            }
+        }
+        zeros -= run;
+    }
+}
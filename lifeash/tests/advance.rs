@@ -0,0 +1,154 @@
+use lifeash::{Position, Rect, Universe};
+
+const PULSAR: &str = "x = 13, y = 13, rule = B3/S23
+2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$o4bobo4bo$
+o4bobo4bo$o4bobo4bo2$2b3o3b3o!";
+
+const PENTADECATHLON: &str = "x = 10, y = 3, rule = B3/S23
+2bo4bo2b$2ob4ob2o$2bo4bo!";
+
+const GLIDER: &str = "x = 3, y = 3, rule = B3/S23
+bo$2bo$3o!";
+
+fn cells(universe: &Universe) -> Vec<Position> {
+    let mut cells: Vec<_> = universe.live_cells().collect();
+    cells.sort_by_key(|pos| (pos.y, pos.x));
+    cells
+}
+
+fn assert_period(pattern: &str, period: u128) {
+    let mut universe = Universe::from_rle(pattern).unwrap();
+    let start = cells(&universe);
+    for phase in 1..period {
+        let mut shifted = Universe::from_rle(pattern).unwrap();
+        shifted.advance(phase);
+        assert_ne!(cells(&shifted), start, "period divides {}", phase);
+    }
+    for _ in 0..4 {
+        universe.advance(period);
+        assert_eq!(cells(&universe), start);
+    }
+    assert_eq!(universe.generation(), 4 * period);
+}
+
+#[test]
+fn pulsar_has_period_3() {
+    assert_period(PULSAR, 3);
+}
+
+#[test]
+fn pentadecathlon_has_period_15() {
+    assert_period(PENTADECATHLON, 15);
+}
+
+#[test]
+fn advance_matches_single_steps() {
+    for generations in [1, 3, 5, 7, 13, 27] {
+        let mut stepped = Universe::from_rle(GLIDER).unwrap();
+        for _ in 0..generations {
+            stepped.evolve();
+        }
+        let mut advanced = Universe::from_rle(GLIDER).unwrap();
+        advanced.advance(generations);
+        assert_eq!(
+            cells(&advanced),
+            cells(&stepped),
+            "{} generations",
+            generations
+        );
+        assert_eq!(advanced.generation(), stepped.generation());
+    }
+}
+
+#[test]
+fn advance_to_targets_absolute_generations() {
+    let mut universe = Universe::from_rle(PENTADECATHLON).unwrap();
+    let start = cells(&universe);
+    universe.advance(7);
+    universe.advance_to(30);
+    assert_eq!(universe.generation(), 30);
+    assert_eq!(cells(&universe), start);
+    universe.advance_to(30);
+    assert_eq!(universe.generation(), 30);
+}
+
+#[test]
+#[should_panic]
+fn advance_to_rejects_past_generations() {
+    let mut universe = Universe::from_rle(GLIDER).unwrap();
+    universe.advance(5);
+    universe.advance_to(4);
+}
+
+// frozen regions split every step into single generations, which would never end
+#[test]
+#[should_panic(expected = "while a region is frozen")]
+fn frozen_universes_reject_large_steps() {
+    let mut universe = Universe::from_rle(GLIDER).unwrap();
+    universe.freeze(Rect::new((0, 0), (0, 0)));
+    universe.advance(16);
+    universe.advance(1 << 40);
+}
+
+#[test]
+fn advance_beyond_u64() {
+    let generation = 1 << 70;
+    let mut universe = Universe::from_rle(PENTADECATHLON).unwrap();
+    universe.advance_to(generation);
+    assert_eq!(universe.generation(), generation);
+
+    // 2^70 = 4 mod 15
+    let mut expected = Universe::from_rle(PENTADECATHLON).unwrap();
+    expected.advance(4);
+    assert_eq!(cells(&universe), cells(&expected));
+}
+
+// the largest supersteps are skipped once the universe repeats, so any count finishes
+#[test]
+fn advance_far_beyond_u64() {
+    let generations = (1 << 100) + (1 << 64) + 7;
+    let mut universe = Universe::from_rle(PULSAR).unwrap();
+    universe.advance(generations);
+    assert_eq!(universe.generation(), generations);
+    let mut expected = Universe::from_rle(PULSAR).unwrap();
+    expected.advance(generations % 3);
+    assert_eq!(cells(&universe), cells(&expected));
+
+    let mut universe = Universe::from_rle(PENTADECATHLON).unwrap();
+    universe.advance(u128::MAX);
+    assert_eq!(universe.generation(), u128::MAX);
+    let mut expected = Universe::from_rle(PENTADECATHLON).unwrap();
+    expected.advance(u128::MAX % 15);
+    assert_eq!(cells(&universe), cells(&expected));
+}
+
+#[test]
+#[should_panic(expected = "overflows")]
+fn advance_rejects_generation_overflow() {
+    let mut universe = Universe::from_rle(PULSAR).unwrap();
+    universe.advance(1);
+    universe.advance(u128::MAX);
+}
+
+// a glider leaves the 64 bit coordinates long before 2^70 generations
+#[test]
+#[should_panic(expected = "maximal level")]
+fn moving_patterns_leave_the_coordinates() {
+    let mut universe = Universe::from_rle(GLIDER).unwrap();
+    universe.advance(1 << 70);
+}
+
+#[test]
+fn step_tracks_generation() {
+    let mut universe = Universe::from_rle(GLIDER).unwrap();
+    universe.step(1000);
+    universe.step(24);
+    assert_eq!(universe.generation(), 1024);
+
+    // a glider moves one cell diagonally every four generations
+    let moved: Vec<_> = cells(&universe)
+        .into_iter()
+        .map(|pos| Position::new(pos.x - 256, pos.y - 256))
+        .collect();
+    assert_eq!(moved, cells(&Universe::from_rle(GLIDER).unwrap()));
+}
@@ -0,0 +1,93 @@
+use lifeash::{
+    analysis::{self, Periodicity},
+    census::Kind,
+    prelude::*,
+};
+
+fn periodicity(rle: &str, max_period: u64) -> Option<Periodicity> {
+    analysis::detect_period(&Universe::from_rle(rle).unwrap(), max_period)
+}
+
+#[test]
+fn periods_of_oscillators() {
+    let oscillators = [
+        ("2o$2o!", 1),
+        ("3o!", 2),
+        ("b3o$3o!", 2),
+        ("2o$o$3bo$2b2o!", 2),
+        (
+            "2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$o4bobo4bo$\
+             o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+            3,
+        ),
+    ];
+    for &(rle, period) in &oscillators {
+        let periodicity = periodicity(rle, 10).unwrap();
+        assert_eq!(periodicity.period, period, "{}", rle);
+        assert_eq!(periodicity.displacement, Offset::new(0, 0), "{}", rle);
+    }
+}
+
+#[test]
+fn ships_report_their_displacement() {
+    let glider = periodicity("bo$2bo$3o!", 10).unwrap();
+    assert_eq!((glider.period, glider.displacement), (4, Offset::new(1, 1)));
+    assert_eq!(glider.kind(), Kind::Ship);
+
+    let lwss = periodicity("bo2bo$o4b$o3bo$4o!", 10).unwrap();
+    assert_eq!(lwss.period, 4);
+    assert_eq!(lwss.displacement.dy, 0);
+    assert_eq!(lwss.displacement.dx.abs(), 2);
+}
+
+#[test]
+fn chaotic_patterns_have_no_period() {
+    assert_eq!(periodicity("b2o$2o$bo!", 50), None);
+    // the blinker needs two generations
+    assert_eq!(periodicity("3o!", 1), None);
+}
+
+#[test]
+fn the_universe_is_left_alone() {
+    let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    let before = universe.content_hash128();
+    analysis::detect_period(&universe, 10).unwrap();
+    assert_eq!(universe.generation(), 0);
+    assert_eq!(universe.content_hash128(), before);
+}
+
+#[test]
+fn empty_universes_are_still() {
+    let periodicity = analysis::detect_period(&Universe::new(), 10).unwrap();
+    assert_eq!(periodicity.period, 1);
+    assert_eq!(periodicity.kind(), Kind::StillLife);
+}
+
+#[test]
+fn ships_are_stable_from_the_start() {
+    let mut glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    let stable = glider.run_until_stable(100).unwrap();
+    assert_eq!(stable.generation, 0);
+    assert_eq!(stable.periodicity.period, 4);
+    assert_eq!(stable.periodicity.displacement, Offset::new(1, 1));
+    assert_eq!(glider.generation(), 4);
+}
+
+#[test]
+fn stabilization_is_found_where_it_began() {
+    // dies out at generation 2 and stays empty
+    let mut universe = Universe::from_rle("o$bo$2bo!").unwrap();
+    universe.evolve();
+    let stable = universe.run_until_stable(10).unwrap();
+    assert_eq!(universe.population(), 0);
+    assert_eq!(stable.generation, 2);
+    assert_eq!(stable.periodicity.period, 1);
+    assert_eq!(stable.periodicity.kind(), Kind::StillLife);
+    assert_eq!(universe.generation(), 3);
+
+    // the budget counts from the current generation
+    let mut r_pentomino = Universe::from_rle("b2o$2o$bo!").unwrap();
+    r_pentomino.advance(50);
+    assert_eq!(r_pentomino.run_until_stable(20), None);
+    assert_eq!(r_pentomino.generation(), 70);
+}
@@ -0,0 +1,82 @@
+// the core of the stable api, spelled out with its exact signatures, so that changing one of
+// them fails here before cargo semver-checks has to catch it
+
+use std::time::Duration;
+
+use lifeash::{
+    io::ParseError, Backend, GcStats, KernelKind, PasteMode, Pattern, Position, Rect, Rule, Stats,
+    Universe, UniverseBuilder,
+};
+
+#[test]
+fn universe_signatures() {
+    let _: fn() -> Universe = Universe::new;
+    let _: fn() -> UniverseBuilder = Universe::builder;
+    let _: fn(UniverseBuilder, Rule) -> UniverseBuilder = UniverseBuilder::rule;
+    let _: fn(UniverseBuilder, KernelKind) -> UniverseBuilder = UniverseBuilder::kernel;
+    let _: fn(UniverseBuilder) -> Universe = UniverseBuilder::build;
+
+    let _: fn(&mut Universe, u128) = Universe::advance;
+    let _: fn(&mut Universe, u64) = Universe::step;
+    let _: fn(&Universe) -> u128 = Universe::generation;
+    let _: fn(&Universe) -> u128 = Universe::population;
+    let _: fn(&Universe) -> Option<(Position, Position)> = Universe::bounding_box;
+    let _: fn(&Universe) -> Rule = Universe::rule;
+    let _: fn(&mut Universe, Rule) = Universe::set_rule;
+    let _: fn(&Universe) -> Backend = Universe::backend;
+    let _: fn(&Universe, Rect) -> Pattern = Universe::extract;
+    // `impl Trait` arguments can't be named, calling them checks what they accept
+    let _: fn(&mut Universe, &Pattern, Position) = |universe, pattern, pos| {
+        universe.stamp(pattern, pos);
+        universe.stamp(pattern, (pos.x, pos.y));
+    };
+    let _: fn(&mut Universe, &Pattern, Position, PasteMode) = |universe, pattern, pos, mode| {
+        universe.paste(pattern, pos, mode);
+    };
+}
+
+#[test]
+fn format_signatures() {
+    let _: fn(&str) -> Result<Universe, ParseError> = Universe::from_rle;
+    let _: fn(&Universe) -> String = Universe::to_rle;
+    #[cfg(feature = "formats-extra")]
+    {
+        let _: fn(&str) -> Result<Universe, ParseError> = Universe::from_macrocell;
+        let _: fn(&Universe) -> String = Universe::to_macrocell;
+        let _: fn(&[u8]) -> Result<Universe, ParseError> = |bytes| Universe::deserialize(bytes);
+        let _: fn(&Universe, &mut Vec<u8>) -> std::io::Result<()> =
+            |universe, bytes| universe.serialize(bytes);
+    }
+}
+
+#[test]
+fn stats_fields() {
+    let universe = Universe::new();
+    let Stats {
+        generation,
+        nodes,
+        cache_hits,
+        cache_misses,
+        evolve_time,
+        hash_collisions,
+        ..
+    } = universe.stats();
+    let _: (u128, usize, u64, u64, Duration, u64) = (
+        generation,
+        nodes,
+        cache_hits,
+        cache_misses,
+        evolve_time,
+        hash_collisions,
+    );
+    let _: f64 = universe.stats().cache_hit_rate();
+
+    let GcStats {
+        collections,
+        last_reclaimed,
+        total_reclaimed,
+        live_nodes,
+        ..
+    } = universe.gc_stats();
+    let _: (u64, usize, u64, usize) = (collections, last_reclaimed, total_reclaimed, live_nodes);
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use lifeash::{prelude::*, SetOp};
+
+fn cells(universe: &Universe) -> HashSet<Position> {
+    universe.live_cells().collect()
+}
+
+#[test]
+fn operations_match_sets() {
+    let mut a = Universe::new();
+    a.fill_random(Rect::new((0, 0), (63, 63)), 0.5, 1);
+    let mut b = Universe::new();
+    b.fill_random(Rect::new((32, -16), (95, 47)), 0.5, 2);
+    let (set_a, set_b) = (cells(&a), cells(&b));
+
+    assert_eq!(cells(&a.union(&b)), &set_a | &set_b);
+    assert_eq!(cells(&a.intersection(&b)), &set_a & &set_b);
+    assert_eq!(cells(&a.difference(&b)), &set_a - &set_b);
+    assert_eq!(cells(&a.xor(&b)), &set_a ^ &set_b);
+
+    let mut combined = a.compacted();
+    combined.combine(&b, SetOp::Xor);
+    assert_eq!(combined.content_hash128(), a.xor(&b).content_hash128());
+}
+
+#[test]
+fn universes_of_different_sizes() {
+    let small = Universe::from_rle("3o!").unwrap();
+    let mut large = small.compacted();
+    large.set_cell((-(1 << 30), 1 << 30), Cell::Alive);
+
+    assert_eq!(small.union(&large).population(), 4);
+    assert_eq!(small.intersection(&large).population(), 3);
+    assert_eq!(small.difference(&large).population(), 0);
+    assert_eq!(
+        cells(&large.difference(&small)),
+        HashSet::from([Position::new(-(1 << 30), 1 << 30)])
+    );
+}
+
+#[test]
+fn regions_are_cleared_and_filled() {
+    let mut universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    universe.set_cell((10, 10), Cell::Alive);
+    let rect = Rect::new((0, 0), (3, 3));
+    let mut region = Universe::new();
+    region.map_region(rect, |_, _| Cell::Alive);
+
+    universe.combine(&region, SetOp::Union);
+    assert_eq!(universe.population(), 17);
+    universe.combine(&region, SetOp::Difference);
+    assert_eq!(cells(&universe), HashSet::from([Position::new(10, 10)]));
+}
+
+#[test]
+fn patterns_keep_their_metadata() {
+    let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    let mut glider = glider.extract(Rect::new((0, 0), (2, 2)));
+    glider.name = Some("glider".to_string());
+    let block = Pattern {
+        cells: [(1, 1), (2, 1), (1, 2), (2, 2)]
+            .map(Position::from)
+            .to_vec(),
+        ..Pattern::default()
+    };
+
+    let union = glider.union(&block);
+    assert_eq!(union.name.as_deref(), Some("glider"));
+    assert_eq!(union.rule, Some(Rule::CONWAY));
+    assert_eq!(union.cells.len(), 6);
+    assert_eq!(glider.intersection(&block).cells.len(), 3);
+    assert_eq!(
+        glider.difference(&block).cells,
+        [(1, 0), (0, 2)].map(Position::from)
+    );
+    assert_eq!(glider.xor(&block).cells.len(), 3);
+}
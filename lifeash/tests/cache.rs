@@ -0,0 +1,108 @@
+use lifeash::{prelude::*, LevelStats};
+
+fn soup() -> Universe {
+    let mut universe = Universe::new();
+    universe.fill_random(Rect::new((0, 0), (63, 63)), 0.4, 7);
+    universe
+}
+
+fn total(levels: &[LevelStats], field: fn(&LevelStats) -> usize) -> usize {
+    levels.iter().map(field).sum()
+}
+
+// limits only cost time, the cells are the same with any of them
+#[test]
+fn limits_keep_the_results_right() {
+    let mut unlimited = soup();
+    unlimited.advance(1000);
+
+    // without any results at a level everything above it is computed again and again, so only
+    // a few levels go without
+    for (levels, limit) in [(3..=3, 0), (3..=6, 16), (8..=20, 64)] {
+        let mut limited = soup();
+        for level in levels.clone() {
+            limited.set_level_cache_limit(level, Some(limit));
+        }
+        limited.advance(1000);
+        assert_eq!(
+            limited.content_hash(),
+            unlimited.content_hash(),
+            "{}",
+            limit
+        );
+        for level in &limited.level_stats()[*levels.start() as usize..=*levels.end() as usize] {
+            assert!(level.cached_results <= limit, "{:?}", level);
+        }
+    }
+}
+
+#[test]
+fn limits_survive_garbage_collection() {
+    let mut universe = Universe::builder()
+        .gc_threshold(1 << 16)
+        .level_cache_limit(5, 4)
+        .build();
+    universe.fill_random(Rect::new((0, 0), (63, 63)), 0.4, 7);
+    universe.advance(2000);
+    assert!(universe.gc_stats().collections > 0);
+    assert!(universe.level_stats()[5].cached_results <= 4);
+
+    // the results dropped while limited are not brought back
+    universe.set_level_cache_limit(5, None);
+    assert_eq!(universe.level_stats()[5].cache_limit, None);
+    assert!(universe.level_stats()[5].cached_results <= 4);
+}
+
+#[test]
+fn levels_add_up_to_the_totals() {
+    let mut universe = soup();
+    universe.advance(500);
+    let levels = universe.level_stats();
+    let stats = universe.stats();
+    assert_eq!(total(&levels, |level| level.nodes), stats.nodes);
+    assert_eq!(
+        total(&levels, |level| level.memory),
+        universe.memory_usage()
+    );
+    let hits: u64 = levels.iter().map(|level| level.cache_hits).sum();
+    assert_eq!(hits, stats.cache_hits);
+    for (i, level) in levels.iter().enumerate() {
+        assert_eq!(usize::from(level.level), i);
+        assert!(level.cached_results <= level.nodes);
+    }
+    // nothing is cached at the leaves
+    assert_eq!(levels[0].cached_results, 0);
+}
+
+#[test]
+fn a_lower_limit_drops_results_at_once() {
+    let mut universe = soup();
+    universe.advance(500);
+    let before = universe.level_stats();
+    let level = (0..before.len())
+        .max_by_key(|&level| before[level].cached_results)
+        .unwrap();
+    assert!(before[level].cached_results > 2);
+
+    universe.set_level_cache_limit(level as u8, Some(2));
+    let after = universe.level_stats();
+    assert_eq!(after[level].cached_results, 2);
+    assert_eq!(after[level].cache_limit, Some(2));
+}
+
+// blinkers and a block come back every second generation, so after the first round the
+// results of both step sizes are all there, only the growing root above them is new
+#[test]
+fn changing_the_step_keeps_the_results_of_both() {
+    let mut universe = Universe::from_rle("3o10b3o$20b3o$$$$5b2o$5b2o20b3o!").unwrap();
+    let mut misses = Vec::new();
+    for _ in 0..3 {
+        let before = universe.stats().cache_misses;
+        universe.advance(1 << 4);
+        universe.evolve();
+        universe.evolve();
+        misses.push(universe.stats().cache_misses - before);
+    }
+    assert!(misses[1] * 10 < misses[0], "{:?}", misses);
+    assert_eq!(misses[1], misses[2]);
+}
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use lifeash::{
+    census::{self, Census, Kind},
+    prelude::*,
+};
+
+// small soups settle fast enough for debug builds
+fn census() -> Census {
+    Census::new(Rule::CONWAY).soup_size(8, 8)
+}
+
+fn object(rle: &str) -> Option<census::Object> {
+    let universe = Universe::from_rle(rle).unwrap();
+    let (min, max) = universe.bounding_box().unwrap();
+    census::identify(&universe.extract(Rect::new(min, max)), Rule::CONWAY, 60)
+}
+
+// the codes catagolue lists these objects under
+#[test]
+fn apgcodes_match_catagolue() {
+    let objects = [
+        ("2o$2o!", "xs4_33"),
+        ("b2o$o2bo$b2o!", "xs6_696"),
+        ("b2o$o2bo$bobo$2bo!", "xs7_2596"),
+        ("2o$obo$bo!", "xs5_253"),
+        ("2o$obo$b2o!", "xs6_356"),
+        ("2o$obo$bobo$2bo!", "xs7_25ac"),
+        ("3o!", "xp2_7"),
+        ("b3o$3o!", "xp2_7e"),
+        ("2o$2o$2b2o$2b2o!", "xp2_318c"),
+        ("2bo4bo2b$2ob4ob2o$2bo4bo!", "xp15_4r4z4r4"),
+        ("bo$2bo$3o!", "xq4_153"),
+        ("bo2bo$o$o3bo$4o!", "xq4_6frc"),
+        ("3bo$bo3bo$o$o4bo$5o!", "xq4_27dee6"),
+    ];
+    for &(rle, apgcode) in &objects {
+        assert_eq!(object(rle).unwrap().apgcode, apgcode, "{}", rle);
+        // patterns are encoded the same way
+        #[cfg(feature = "formats-extra")]
+        {
+            let universe = Universe::from_rle(rle).unwrap();
+            let (min, max) = universe.bounding_box().unwrap();
+            let pattern = universe.extract(Rect::new(min, max));
+            assert_eq!(pattern.to_apgcode().as_deref(), Some(apgcode), "{}", rle);
+        }
+    }
+}
+
+#[test]
+fn the_phase_and_orientation_do_not_matter() {
+    let glider = object("bo$2bo$3o!").unwrap();
+    for rle in &["obo$b2o$bo!", "2bo$obo$b2o!", "3o$o$bo!"] {
+        let other = object(rle).unwrap();
+        assert_eq!(other.apgcode, glider.apgcode);
+        assert_eq!(other.kind, Kind::Ship);
+        assert_eq!(other.period, 4);
+    }
+}
+
+// two blocks ten columns apart as one still life, the gap is written as a run
+#[test]
+fn empty_columns_are_shortened() {
+    assert_eq!(object("2o10b2o$2o10b2o!").unwrap().apgcode, "xs8_33y633");
+    assert_eq!(object("2o3b2o$2o3b2o!").unwrap().apgcode, "xs8_33x33");
+}
+
+#[test]
+fn ash_is_made_of_its_objects() {
+    let census = census();
+    for seed in 0..8 {
+        let ash = census.run_soup(seed).unwrap();
+        let mut universe = Universe::new();
+        universe.fill_random(Rect::new((0, 0), (7, 7)), 0.5, seed);
+        universe.advance(ash.generation);
+
+        let cells: HashSet<_> = universe.live_cells().collect();
+        let mut covered = HashSet::new();
+        for object in &ash.objects {
+            for &pos in &object.cells {
+                assert!(covered.insert(pos), "{:?} is in two objects", pos);
+            }
+            let pattern = Pattern {
+                cells: object.cells.clone(),
+                ..Pattern::default()
+            };
+            let alone = census::identify(&pattern, Rule::CONWAY, 60).unwrap();
+            assert_eq!(alone.apgcode, object.apgcode);
+        }
+        assert_eq!(covered, cells, "seed {}", seed);
+    }
+}
+
+#[test]
+fn censuses_are_repeatable() {
+    let mut first = census();
+    first.run(0..8);
+    let mut second = census();
+    for seed in 0..8 {
+        let ash = second.run_soup(seed);
+        second.add(ash.as_ref());
+    }
+    assert_eq!(first.tally(), second.tally());
+    assert_eq!(first.soups(), 8);
+}
+
+#[test]
+fn common_objects_have_names() {
+    let objects = [
+        ("2o$2o!", "block"),
+        ("bo$obo$bo!", "tub"),
+        ("2o$obo$2bo$2b2o!", "eater 1"),
+        ("2bo$obo$bobo$bo!", "clock"),
+        ("3b2o$bo4bo$o$o5bo$6o!", "heavyweight spaceship"),
+    ];
+    for &(rle, name) in &objects {
+        let apgcode = object(rle).unwrap().apgcode;
+        assert_eq!(census::common_name(&apgcode), Some(name), "{}", rle);
+    }
+}
+
+#[test]
+fn objects_are_picked_among_others() {
+    let pulsar = "2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$\
+                  o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!";
+    let mut universe = Universe::from_rle(pulsar).unwrap();
+    let glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    universe.stamp(
+        &glider.extract(Rect::new((0, 0), (2, 2))),
+        Position::new(20, 0),
+    );
+
+    let picked = census::object_at(&universe, Position::new(0, 2), 10).unwrap();
+    assert_eq!(census::common_name(&picked.apgcode), Some("pulsar"));
+    assert_eq!(picked.cells.len(), 48);
+    // next to a cell of the glider
+    let picked = census::object_at(&universe, Position::new(23, 2), 10).unwrap();
+    assert_eq!(census::common_name(&picked.apgcode), Some("glider"));
+    assert_eq!(
+        census::object_at(&universe, Position::new(30, 30), 10),
+        None
+    );
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "formats-extra")]
+
+use lifeash::{prelude::*, Backend};
+
+const HALFMAX: &str = include_str!("../benches/halfmax.rle");
+
+// runs `universe` for `before` generations, then once straight on and once through a checkpoint
+fn assert_resumes(mut universe: Universe, before: u128, after: u128, cache: bool) {
+    universe.advance(before);
+    let mut checkpoint = Vec::new();
+    if cache {
+        universe.serialize_with_cache(&mut checkpoint).unwrap();
+    } else {
+        universe.serialize(&mut checkpoint).unwrap();
+    }
+    let mut resumed = Universe::deserialize(&checkpoint[..]).unwrap();
+    assert_eq!(resumed.content_hash128(), universe.content_hash128());
+
+    universe.advance(after);
+    resumed.advance(after);
+    assert_eq!(resumed.generation(), universe.generation());
+    assert_eq!(resumed.population(), universe.population());
+    assert_eq!(resumed.content_hash128(), universe.content_hash128());
+}
+
+// loads a checkpoint of an earlier version and checks that it evolves like `expected`
+fn assert_migrates(checkpoint: &[u8], mut expected: Universe) {
+    let mut universe = Universe::deserialize(checkpoint).unwrap();
+    assert_eq!(universe.generation(), expected.generation());
+    assert_eq!(universe.rule(), expected.rule());
+    assert_eq!(universe.backend(), expected.backend());
+    assert_eq!(universe.frozen(), expected.frozen());
+    assert_eq!(universe.content_hash128(), expected.content_hash128());
+
+    universe.advance(50);
+    expected.advance(50);
+    assert_eq!(universe.content_hash128(), expected.content_hash128());
+}
+
+#[test]
+fn breeder_resumes_exactly() {
+    for &cache in &[false, true] {
+        assert_resumes(Universe::from_rle(HALFMAX).unwrap(), 1000, 3000, cache);
+    }
+}
+
+#[test]
+fn dying_cells_resume_exactly() {
+    let mut universe = Universe::builder()
+        .rule("B2/S345/C4".parse().unwrap())
+        .build();
+    universe.load_rle_at("2o$obo$b3o$2bo!", (0, 0)).unwrap();
+    for &cache in &[false, true] {
+        assert_resumes(universe.compacted(), 50, 150, cache);
+    }
+}
+
+#[test]
+fn larger_than_life_resumes_exactly() {
+    let mut universe = Universe::from_rle("3o$o2bo$2o2bo$b4o!").unwrap();
+    universe.set_backend(Backend::LargerThanLife(
+        "R2,C0,M1,S5..8,B5..6,NM".parse().unwrap(),
+    ));
+    for &cache in &[false, true] {
+        assert_resumes(universe.compacted(), 20, 40, cache);
+    }
+}
+
+#[test]
+fn truncated_checkpoints_are_rejected() {
+    let mut universe = Universe::from_rle(HALFMAX).unwrap();
+    universe.advance(100);
+    let mut checkpoint = Vec::new();
+    universe.serialize_with_cache(&mut checkpoint).unwrap();
+    for len in (0..checkpoint.len()).step_by(97) {
+        assert!(Universe::deserialize(&checkpoint[..len]).is_err());
+    }
+}
+
+#[test]
+fn corrupted_checkpoints_are_rejected() {
+    let mut universe = Universe::from_rle(HALFMAX).unwrap();
+    universe.advance(100);
+    let mut checkpoint = Vec::new();
+    universe.serialize(&mut checkpoint).unwrap();
+    for i in (0..checkpoint.len()).step_by(13) {
+        let mut corrupted = checkpoint.clone();
+        corrupted[i] ^= 0x10;
+        assert!(Universe::deserialize(&corrupted[..]).is_err(), "byte {}", i);
+    }
+}
+
+// the fixtures were written by version 1 and must never be regenerated
+#[test]
+fn version_1_checkpoints_load() {
+    let mut glider = Universe::from_rle("bo$2bo$3o!").unwrap();
+    glider.advance(100);
+    assert_migrates(include_bytes!("fixtures/glider-v1.lash"), glider);
+
+    let mut generations = Universe::builder()
+        .rule("B2/S345/C4".parse().unwrap())
+        .build();
+    generations.load_rle_at("2o$obo$b3o$2bo!", (0, 0)).unwrap();
+    generations.advance(50);
+    assert_migrates(
+        include_bytes!("fixtures/generations-cached-v1.lash"),
+        generations,
+    );
+
+    let mut larger_than_life = Universe::from_rle("3o$o2bo$2o2bo$b4o!").unwrap();
+    larger_than_life.set_backend(Backend::LargerThanLife(
+        "R2,C0,M1,S5..8,B5..6,NM".parse().unwrap(),
+    ));
+    larger_than_life.freeze(Rect::new((0, 0), (1, 1)));
+    larger_than_life.advance(20);
+    assert_migrates(
+        include_bytes!("fixtures/larger-than-life-v1.lash"),
+        larger_than_life,
+    );
+}
@@ -0,0 +1,135 @@
+// memoization bugs tend to show up only under concurrency, so every pattern of the corpus is run
+// serially and on several thread counts, and the results are compared with each other and with
+// hashes recorded once, which are the same on every platform
+
+use lifeash::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+const HALFMAX: &str = include_str!("../benches/halfmax.rle");
+
+const SOUP: i64 = 32;
+// distance of the second soup from the first, so that the parallel levels are reached without
+// the work of a large soup
+const SPREAD: i64 = 1 << 12;
+const THREAD_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+struct Case {
+    name: &'static str,
+    universe: fn() -> Universe,
+    generations: u128,
+    // `content_hash128` of the result
+    expected: u128,
+}
+
+fn soup(rule: &str, density: f64, seed: u64) -> Universe {
+    let mut universe = Universe::builder().rule(rule.parse().unwrap()).build();
+    for (corner, seed) in [(0, seed), (SPREAD, !seed)] {
+        let rect = Rect::new((corner, corner), (corner + SOUP - 1, corner + SOUP - 1));
+        universe.fill_random(rect, density, seed);
+    }
+    universe
+}
+
+const CORPUS: [Case; 5] = [
+    Case {
+        name: "halfmax",
+        universe: || Universe::from_rle(HALFMAX).unwrap(),
+        generations: 1 << 12,
+        expected: 0xd636_33b4_1522_79dd_ec54_fc15_b121_f99b,
+    },
+    Case {
+        name: "conway soup",
+        universe: || soup("B3/S23", 0.5, 1),
+        generations: 1 << 9,
+        expected: 0xaac3_3976_ff45_b8cb_b06d_c78b_2512_ea24,
+    },
+    Case {
+        name: "highlife soup",
+        universe: || soup("B36/S23", 0.4, 2),
+        generations: 1 << 10,
+        expected: 0x290f_9cf8_a863_13a0_d9ae_425a_cf5c_2874,
+    },
+    Case {
+        name: "day and night soup",
+        universe: || soup("B3678/S34678", 0.5, 3),
+        generations: 1 << 10,
+        expected: 0x37e8_1d5e_e9a7_f85a_4bb3_9323_8ad8_3982,
+    },
+    Case {
+        name: "brian's brain soup",
+        universe: || soup("B2/S/C3", 0.2, 4),
+        generations: 1 << 7,
+        expected: 0x3cde_2ffa_97d6_3b75_965e_7796_6644_49e9,
+    },
+];
+
+fn run(case: &Case, threads: Option<usize>) -> Universe {
+    let mut universe = (case.universe)();
+    universe.set_parallel(threads.is_some());
+    match threads {
+        Some(threads) => ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| universe.advance(case.generations)),
+        None => universe.advance(case.generations),
+    }
+    universe
+}
+
+#[test]
+fn parallel_and_serial_evolution_agree() {
+    for case in &CORPUS {
+        let serial = run(case, None);
+        for threads in THREAD_COUNTS {
+            let parallel = run(case, Some(threads));
+            assert_eq!(parallel.generation(), serial.generation());
+            assert_eq!(
+                parallel.population(),
+                serial.population(),
+                "{} on {} threads",
+                case.name,
+                threads
+            );
+            assert_eq!(
+                parallel.content_hash128(),
+                serial.content_hash128(),
+                "{} on {} threads",
+                case.name,
+                threads
+            );
+        }
+    }
+}
+
+// the hash only depends on the positions and states of the cells, not on the byte order or the
+// word size
+#[test]
+fn results_match_the_recorded_hashes() {
+    for case in &CORPUS {
+        assert_eq!(
+            run(case, None).content_hash128(),
+            case.expected,
+            "{}",
+            case.name
+        );
+    }
+}
+
+// the workers keep the nodes they copied from step to step, until either side collects garbage
+#[test]
+fn garbage_collection_between_parallel_steps() {
+    let case = &CORPUS[1];
+    let serial = run(case, None);
+    let mut universe = (case.universe)();
+    universe.set_parallel(true);
+    universe.set_gc_threshold(Some(1 << 18));
+    let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    pool.install(|| {
+        for _ in 0..case.generations / 16 {
+            universe.step(16);
+        }
+    });
+    assert!(universe.gc_stats().collections > 0);
+    assert_eq!(universe.content_hash128(), serial.content_hash128());
+}
@@ -0,0 +1,69 @@
+use lifeash::{prelude::*, Backend, KernelKind};
+
+const R_PENTOMINO: &str = "b2o$2o$bo!";
+
+#[test]
+fn backends_agree() {
+    let mut life = Universe::from_rle(R_PENTOMINO).unwrap();
+    let mut ltl = life.compacted();
+    // conway's rule in the notation of larger than life, without the center
+    ltl.set_backend(Backend::LargerThanLife(
+        "R1,C0,M0,S2..3,B3..3,NM".parse().unwrap(),
+    ));
+    let mut lookup = life.compacted();
+    lookup.set_kernel(KernelKind::Lookup);
+    life.advance(300);
+    ltl.advance(300);
+    lookup.advance(300);
+    assert_eq!(life.xor(&ltl).population(), 0);
+    assert_eq!(life.xor(&lookup).population(), 0);
+}
+
+#[test]
+fn xor_is_symmetric() {
+    let mut highlife = Universe::builder().rule("B36/S23".parse().unwrap()).build();
+    highlife.fill_random(Rect::new((0, 0), (31, 31)), 0.4, 7);
+    let mut life = highlife.compacted();
+    life.set_rule(Rule::CONWAY);
+    highlife.advance(100);
+    life.advance(100);
+
+    let (a, b) = (highlife.xor(&life), life.xor(&highlife));
+    assert!(a.population() > 0);
+    assert_eq!(a.content_hash128(), b.content_hash128());
+    // every cell of the difference is alive in exactly one of them
+    for pos in a.live_cells() {
+        assert_ne!(highlife.get_cell(pos), life.get_cell(pos));
+    }
+}
+
+#[test]
+fn universes_of_different_sizes() {
+    let small = Universe::from_rle("3o!").unwrap();
+    let mut large = Universe::new();
+    large.set_cell((1 << 20, -(1 << 20)), Cell::Alive);
+    let xor = small.xor(&large);
+    assert_eq!(xor.population(), 4);
+    assert_eq!(
+        large.diff_cells(
+            &small,
+            Rect::new((1 << 20, -(1 << 20)), (1 << 20, -(1 << 20)))
+        ),
+        [Position::new(1 << 20, -(1 << 20))]
+    );
+}
+
+#[test]
+fn dying_cells_differ() {
+    let mut universe = Universe::builder().rule("B2/S/C3".parse().unwrap()).build();
+    universe.set_cells([(0, 0), (1, 0)].map(|pos| (pos.into(), Cell::Alive)));
+    universe.evolve();
+    let empty = Universe::builder().rule(universe.rule()).build();
+    let rect = Rect::new((-2, -2), (3, 2));
+
+    let states = universe.cells_in(rect).count();
+    assert!(universe.cells_in(rect).any(|(_, cell)| cell != Cell::Alive));
+    assert_eq!(universe.diff_cells(&empty, rect).len(), states);
+    assert_eq!(empty.diff_cells(&universe, rect).len(), states);
+    assert_eq!(universe.xor(&empty).population(), states as u128);
+}
@@ -0,0 +1,60 @@
+use lifeash::prelude::*;
+
+const HALFMAX: &str = include_str!("../benches/halfmax.rle");
+
+// the dense copies agree with looking up every cell on its own
+fn assert_matches_cells(universe: &Universe, rect: Rect) {
+    let grid = universe.to_grid(rect);
+    let bits = universe.to_bit_grid(rect);
+    assert_eq!(grid.len() as u64, rect.height());
+    for (pos, (x, y)) in rect
+        .positions()
+        .zip((0..rect.height()).flat_map(|y| (0..rect.width()).map(move |x| (x, y))))
+    {
+        let cell = universe.get_cell(pos);
+        assert_eq!(grid[y as usize][x as usize], cell, "{:?}", pos);
+        assert_eq!(bits.get(x, y), cell == Cell::Alive, "{:?}", pos);
+    }
+}
+
+#[test]
+fn grids_match_the_universe() {
+    let mut universe = Universe::from_rle(HALFMAX).unwrap();
+    universe.advance(500);
+    let (min, max) = universe.bounding_box().unwrap();
+    assert_matches_cells(&universe, Rect::new(min, max));
+    // partly outside of the tree and not aligned to words or leaves
+    assert_matches_cells(
+        &universe,
+        Rect::new((min.x - 70, min.y + 3), (min.x + 61, max.y + 9)),
+    );
+}
+
+#[test]
+fn grids_keep_dying_states() {
+    let mut universe = Universe::builder()
+        .rule("B2/S345/C4".parse().unwrap())
+        .build();
+    universe.load_rle_at("2o$obo$b3o$2bo!", (0, 0)).unwrap();
+    universe.advance(20);
+    let (min, max) = universe.bounding_box().unwrap();
+    let rect = Rect::new(min, max);
+    assert!(universe
+        .to_grid(rect)
+        .iter()
+        .flatten()
+        .any(|cell| matches!(cell, Cell::Dying(_))));
+    assert_matches_cells(&universe, rect);
+}
+
+#[test]
+fn unused_bits_stay_cleared() {
+    let universe = Universe::from_rle(HALFMAX).unwrap();
+    let (min, max) = universe.bounding_box().unwrap();
+    // cuts through the pattern, so cells right of the window must not leak into the padding
+    let rect = Rect::new(min, (min.x + 9, max.y));
+    let grid = universe.to_bit_grid(rect);
+    for y in 0..grid.height() {
+        assert_eq!(grid.row(y)[0] >> 10, 0);
+    }
+}
@@ -0,0 +1,45 @@
+use lifeash::{kernels, KernelKind, Rule, Universe};
+
+const RULES: [&str; 6] = [
+    "B3/S23",
+    "B36/S23",
+    "B2/S",
+    "B1357/S1357",
+    "B2-a/S12",
+    "B2/S345/C4",
+];
+
+#[test]
+fn kernels_agree_on_every_block() {
+    for rule in RULES.iter() {
+        let rule: Rule = rule.parse().unwrap();
+        if let Err(mismatch) = kernels::cross_check(rule) {
+            panic!("{}: {}", rule, mismatch);
+        }
+    }
+}
+
+#[test]
+fn kernels_evolve_universes_alike() {
+    let soup = "2obo2bo$ob3o2b$3obobo$bo2b2ob$obob2obo$2b3obo!";
+    for rule in RULES.iter() {
+        let hashes: Vec<_> = [
+            KernelKind::Lookup,
+            KernelKind::Table,
+            KernelKind::Bitboard,
+            KernelKind::Popcount,
+        ]
+        .iter()
+        .map(|&kind| {
+            let mut universe = Universe::builder()
+                .rule(rule.parse().unwrap())
+                .kernel(kind)
+                .build();
+            universe.load_rle_at(soup, (0, 0)).unwrap();
+            universe.advance(100);
+            universe.content_hash()
+        })
+        .collect();
+        assert!(hashes.windows(2).all(|pair| pair[0] == pair[1]), "{}", rule);
+    }
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "formats-extra")]
+
+use lifeash::prelude::*;
+
+// the format has no dying cells, leaves holding nothing else are left out like empty ones
+#[test]
+fn dying_cells_are_written_as_dead() {
+    let mut universe = Universe::builder().rule("B2/S/C3".parse().unwrap()).build();
+    universe.set_cell((20, 20), Cell::Dying(2));
+    let copy = Universe::from_macrocell(&universe.to_macrocell()).unwrap();
+    assert_eq!(copy.population(), 0);
+
+    universe.set_cell((-20, 3), Cell::Alive);
+    let copy = Universe::from_macrocell(&universe.to_macrocell()).unwrap();
+    assert_eq!(copy.population(), 1);
+    assert_eq!(copy.get_cell((-20, 3)), Cell::Alive);
+    assert_eq!(copy.get_cell((20, 20)), Cell::Dead);
+}
+
+#[test]
+fn macrocell_round_trip() {
+    let mut universe = Universe::from_rle("b2o$2o$bo!").unwrap();
+    universe.advance(100);
+    let copy = Universe::from_macrocell(&universe.to_macrocell()).unwrap();
+    assert_eq!(copy.content_hash(), universe.content_hash());
+}
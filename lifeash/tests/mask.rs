@@ -0,0 +1,43 @@
+use lifeash::{prelude::*, MaskOp};
+
+// a domino in B2/S/C3 leaves dying cells behind after one generation
+fn generations() -> Universe {
+    let mut universe = Universe::builder().rule("B2/S/C3".parse().unwrap()).build();
+    universe.set_cells([(0, 0), (1, 0)].map(|pos| (pos.into(), Cell::Alive)));
+    universe.evolve();
+    universe
+}
+
+fn region(rect: Rect) -> Universe {
+    let mut region = Universe::new();
+    region.map_region(rect, |_, _| Cell::Alive);
+    region
+}
+
+#[test]
+fn dying_cells_are_kept() {
+    let universe = generations();
+    let cells: Vec<_> = universe.cells_in(Rect::new((-8, -8), (8, 8))).collect();
+    assert!(cells.iter().any(|&(_, cell)| cell != Cell::Alive));
+
+    // a large mask is resolved by the shortcuts, a tight one down to the leaves
+    for rect in [Rect::new((-64, -64), (63, 63)), Rect::new((-1, -1), (2, 1))] {
+        let mut masked = universe.compacted();
+        masked.apply_mask(&region(rect), MaskOp::ClearOutside);
+        assert_eq!(masked.content_hash128(), universe.content_hash128());
+
+        let mut filled = universe.compacted();
+        filled.apply_mask(&region(rect), MaskOp::FillInside);
+        for &(pos, cell) in &cells {
+            assert_eq!(filled.get_cell(pos), cell);
+        }
+    }
+}
+
+#[test]
+fn dying_cells_are_cleared() {
+    let universe = generations();
+    let mut cleared = universe.compacted();
+    cleared.apply_mask(&region(Rect::new((-1, -1), (2, 1))), MaskOp::ClearInside);
+    assert_eq!(cleared.population(), 0);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "image")]
+
+use lifeash::prelude::*;
+
+const HALFMAX: &str = include_str!("../benches/halfmax.rle");
+
+#[test]
+fn one_pixel_per_cell_matches_the_grid() {
+    let mut universe = Universe::from_rle(HALFMAX).unwrap();
+    universe.advance(300);
+    let (min, max) = universe.bounding_box().unwrap();
+    let rect = Rect::new(min, max);
+    let image = universe.render_image(rect, 1.0);
+    let grid = universe.to_bit_grid(rect);
+    assert_eq!(
+        u64::from(image.width()) * u64::from(image.height()),
+        rect.width() * rect.height()
+    );
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let dark = pixel.0[0] < 0x80;
+        assert_eq!(dark, grid.get(u64::from(x), u64::from(y)), "({}, {})", x, y);
+    }
+}
+
+#[test]
+fn dying_cells_fade_out() {
+    let mut universe = Universe::builder()
+        .rule("B2/S345/C4".parse().unwrap())
+        .build();
+    universe.load_rle_at("2o$obo$b3o$2bo!", (0, 0)).unwrap();
+    universe.advance(20);
+    let (min, max) = universe.bounding_box().unwrap();
+    let rect = Rect::new(min, max);
+    let image = universe.render_image(rect, 1.0);
+    for (pos, cell) in universe.cells_in(rect) {
+        let shade = image
+            .get_pixel((pos.x - min.x) as u32, (pos.y - min.y) as u32)
+            .0[0];
+        match cell {
+            Cell::Alive => assert_eq!(shade, 0x10),
+            Cell::Dying(_) => assert!(shade > 0x10 && shade < 0xff),
+            Cell::Dead => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn zoomed_out_images_keep_the_density() {
+    let universe = Universe::from_rle(HALFMAX).unwrap();
+    let (min, max) = universe.bounding_box().unwrap();
+    let rect = Rect::new(min, max);
+    for &scale in &[0.5, 0.3, 0.1] {
+        let image = universe.render_image(rect, scale);
+        assert_eq!(image.width(), (rect.width() as f64 * scale).ceil() as u32);
+        assert!(image.pixels().any(|pixel| pixel.0[0] < 0xff));
+    }
+    // far away there is nothing to draw
+    let empty = universe.render_image(Rect::new((1000, 1000), (1999, 1999)), 0.1);
+    assert!(empty.pixels().all(|pixel| pixel.0 == [0xff; 4]));
+}
@@ -0,0 +1,112 @@
+use lifeash::{
+    io::{rle, ParseError},
+    prelude::*,
+};
+
+fn brians_brain() -> Universe {
+    Universe::builder().rule("B2/S/C3".parse().unwrap()).build()
+}
+
+#[test]
+fn dying_cells_round_trip() {
+    let mut universe = brians_brain();
+    universe.set_cells([(0, 0), (1, 0), (1, 1)].map(|pos| (pos.into(), Cell::Alive)));
+    universe.advance(3);
+    assert!(universe
+        .cells_in(Rect::new((-9, -9), (9, 9)))
+        .any(|(_, cell)| cell == Cell::Dying(2)));
+
+    // the copy starts at the origin
+    let copy = Universe::from_rle(&universe.to_rle()).unwrap();
+    let (min, max) = universe.bounding_box().unwrap();
+    let corner = Offset::new(min.x, min.y);
+    let mut expected: Vec<_> = universe
+        .cells_in(Rect::new(min, max))
+        .map(|(pos, cell)| (pos - corner, cell))
+        .collect();
+    let mut cells: Vec<_> = copy
+        .cells_in(Rect::new(min - corner, max - corner))
+        .collect();
+    expected.sort();
+    cells.sort();
+    assert_eq!(cells, expected);
+    assert_eq!(copy.rule(), universe.rule());
+}
+
+// states above 24 take two letters
+#[test]
+fn golly_state_letters() {
+    let input = "x = 5, y = 2, rule = B2/S/C40\n.A2B$pAXpO!";
+    let universe = Universe::from_rle(input).unwrap();
+    assert_eq!(universe.get_cell((0, 0)), Cell::Dead);
+    assert_eq!(universe.get_cell((1, 0)), Cell::Alive);
+    assert_eq!(universe.get_cell((3, 0)), Cell::Dying(2));
+    assert_eq!(universe.get_cell((0, 1)), Cell::Dying(25));
+    assert_eq!(universe.get_cell((1, 1)), Cell::Dying(24));
+    assert_eq!(universe.get_cell((2, 1)), Cell::Dying(39));
+    assert_eq!(
+        Universe::from_rle(&universe.to_rle())
+            .unwrap()
+            .content_hash(),
+        universe.content_hash()
+    );
+}
+
+#[test]
+fn states_the_rule_lacks_are_errors() {
+    assert!(matches!(
+        Universe::from_rle("x = 1, y = 1, rule = B2/S/C3\nC!"),
+        Err(ParseError::UnexpectedState { line: 2, state: 3 })
+    ));
+    // the rule of the universe counts, not the one of the header
+    let mut universe = Universe::new();
+    assert!(universe
+        .load_rle_at("x = 1, y = 1, rule = B2/S/C3\nB!", (0, 0))
+        .is_err());
+    let mut universe = brians_brain();
+    universe.load_rle_at("AB!", (0, 0)).unwrap();
+    assert_eq!(universe.get_cell((1, 0)), Cell::Dying(2));
+}
+
+// patterns only hold alive cells, so their dying cells can't be read
+#[test]
+fn patterns_reject_dying_cells() {
+    let input = "x = 2, y = 1, rule = B2/S/C3\nAB!";
+    assert!(matches!(
+        rle::parse(input),
+        Err(ParseError::UnexpectedState { state: 2, .. })
+    ));
+    assert_eq!(
+        rle::parse("x = 1, y = 1, rule = B2/S/C3\nA!")
+            .unwrap()
+            .cells
+            .len(),
+        1
+    );
+}
+
+// a run is handed out in batches as it is decoded, so its length doesn't decide the memory
+#[test]
+fn huge_runs_are_batched() {
+    let mut parser = rle::Parser::new();
+    let mut cells = Vec::new();
+    let mut batches = 0;
+    let result = parser.feed_batched(b"99999999999o!", &mut cells, |batch| {
+        assert_eq!(batch.len(), rle::BATCH_LEN);
+        batch.clear();
+        batches += 1;
+        // stops the parser, the rest of the run would take too long to decode
+        if batches == 4 {
+            Err(ParseError::Io("enough".to_owned()))
+        } else {
+            Ok(())
+        }
+    });
+    assert_eq!(result, Err(ParseError::Io("enough".to_owned())));
+    assert_eq!(batches, 4);
+    assert!(cells.capacity() <= rle::BATCH_LEN);
+
+    let mut universe = Universe::new();
+    universe.load_rle_at("200000o!", (0, 0)).unwrap();
+    assert_eq!(universe.population(), 200_000);
+}
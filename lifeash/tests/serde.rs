@@ -0,0 +1,77 @@
+#![cfg(feature = "serde")]
+
+use lifeash::{prelude::*, Backend, LtlRule};
+
+#[test]
+fn rules_are_rulestrings() {
+    let rule: Rule = "B2-a/S12/C3".parse().unwrap();
+    let json = serde_json::to_string(&rule).unwrap();
+    assert_eq!(json, format!("\"{}\"", rule));
+    assert_eq!(serde_json::from_str::<Rule>(&json).unwrap(), rule);
+    assert!(serde_json::from_str::<Rule>("\"B0/S\"").is_err());
+
+    let bosco: LtlRule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+    let json = serde_json::to_string(&bosco).unwrap();
+    assert_eq!(serde_json::from_str::<LtlRule>(&json).unwrap(), bosco);
+}
+
+#[test]
+fn patterns_round_trip() {
+    let pattern = Pattern {
+        name: Some("glider".to_owned()),
+        comments: vec!["the smallest spaceship".to_owned()],
+        rule: Some(Rule::CONWAY),
+        cells: vec![
+            Position::new(1, 0),
+            Position::new(2, 1),
+            Position::new(0, 2),
+            Position::new(1, 2),
+            Position::new(2, 2),
+        ],
+    };
+    let json = serde_json::to_string(&pattern).unwrap();
+    assert!(json.contains("\"rule\":\"B3/S23\""));
+    assert_eq!(serde_json::from_str::<Pattern>(&json).unwrap(), pattern);
+}
+
+#[test]
+fn universes_round_trip() {
+    let mut universe = Universe::builder()
+        .rule("B2/S345/C4".parse().unwrap())
+        .build();
+    universe.load_rle_at("2o$obo$b3o$2bo!", (-40, 17)).unwrap();
+    universe.freeze(Rect::new((-40, 17), (-39, 18)));
+    universe.advance(30);
+
+    let json = serde_json::to_string(&universe).unwrap();
+    let mut copy: Universe = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy.generation(), 30);
+    assert_eq!(copy.rule(), universe.rule());
+    assert_eq!(copy.frozen(), universe.frozen());
+    assert_eq!(copy.content_hash128(), universe.content_hash128());
+
+    universe.advance(30);
+    copy.advance(30);
+    assert_eq!(copy.content_hash128(), universe.content_hash128());
+}
+
+#[test]
+fn larger_than_life_universes_round_trip() {
+    let mut universe = Universe::from_rle("3o$o2bo$2o2bo$b4o!").unwrap();
+    let rule = "R2,C0,M1,S5..8,B5..6,NM".parse().unwrap();
+    universe.set_backend(Backend::LargerThanLife(rule));
+    let copy: Universe = serde_json::from_str(&serde_json::to_string(&universe).unwrap()).unwrap();
+    assert_eq!(copy.backend(), Backend::LargerThanLife(rule));
+}
+
+#[test]
+fn invalid_trees_are_rejected() {
+    let universe = Universe::from_rle("bo$2bo$3o!").unwrap();
+    let json = serde_json::to_string(&universe).unwrap();
+    // a child which comes after its parent
+    let forward = json.replacen("[0,0,0,0]", "[0,0,0,99]", 1);
+    assert!(serde_json::from_str::<Universe>(&forward).is_err());
+    // a dying cell, which Conway's rule doesn't have
+    let dying = json.replacen("{\"Leaf\":0}", "{\"Leaf\":2}", 1);
+    assert!(serde_json::from_str::<Universe>(&dying).is_err());
+}
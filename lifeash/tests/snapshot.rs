@@ -0,0 +1,37 @@
+use lifeash::{prelude::*, Backend};
+
+const R_PENTOMINO: &str = "b2o$2o$bo!";
+
+// the tree of the snapshot is evolved by the backend it was taken with, not the one of the
+// universe at the time it is restored
+#[test]
+fn snapshots_restore_the_backend() {
+    let mut universe = Universe::from_rle(R_PENTOMINO).unwrap();
+    let snapshot = universe.snapshot();
+    universe.set_backend(Backend::LargerThanLife(
+        "R2,C0,M1,S5..9,B5..7,NM".parse().unwrap(),
+    ));
+    universe.advance(64);
+
+    universe.restore(&snapshot);
+    assert_eq!(universe.backend(), Backend::Life);
+    universe.advance(64);
+    let mut expected = Universe::from_rle(R_PENTOMINO).unwrap();
+    expected.advance(64);
+    assert_eq!(universe.content_hash128(), expected.content_hash128());
+    universe.release(snapshot);
+}
+
+#[test]
+fn snapshots_restore_the_frozen_regions() {
+    let mut universe = Universe::from_rle(R_PENTOMINO).unwrap();
+    let rect = Rect::new((0, 0), (1, 1));
+    universe.freeze(rect);
+    let snapshot = universe.snapshot();
+    universe.thaw(rect);
+    universe.freeze(Rect::new((5, 5), (6, 6)));
+
+    universe.restore(&snapshot);
+    assert_eq!(universe.frozen(), [rect]);
+    universe.release(snapshot);
+}
@@ -0,0 +1,38 @@
+use lifeash::prelude::*;
+
+// soups must never change for a seed, searches publish their seeds
+#[test]
+fn soups_are_pinned_to_their_seed() {
+    let mut universe = Universe::new();
+    universe.fill_random(Rect::new((0, 0), (15, 15)), 0.5, 1);
+    assert_eq!(
+        universe.to_rle(),
+        "x = 16, y = 16, rule = B3/S23\n\
+         3b2o3bobobob2o$4b6o2bo$3o7bo3b2o$2b3ob2obo3bo$b3ob2o4bo3bo$b2o4b2o2b2o\n\
+         2bo$2b2o3b2o2bo2b2o$2b2ob2o4b5o$3o2b3ob3o$bo3b2obob2ob2o$2o2b2ob6ob2o$\n\
+         2o2b4obo2b3o$b2ob7ob2obo$3o2bo2bobob2o$b4o2bob6o$o3b2o2b2o2b2obo!\n"
+    );
+}
+
+#[test]
+fn soups_overwrite_their_rect() {
+    let rect = Rect::new((-20, -20), (19, 19));
+    let mut universe = Universe::from_rle("o!").unwrap();
+    universe.set_cell((100, 100), Cell::Alive);
+    universe.fill_random(rect, 1.0, 0);
+    assert_eq!(universe.population(), 40 * 40 + 1);
+    universe.fill_random(rect, 0.0, 0);
+    assert_eq!(universe.population(), 1);
+    assert_eq!(universe.get_cell((100, 100)), Cell::Alive);
+}
+
+#[test]
+fn soups_follow_the_density() {
+    let rect = Rect::new((0, 0), (199, 199));
+    for &density in &[0.1, 0.375, 0.9] {
+        let mut universe = Universe::new();
+        universe.fill_random(rect, density, 2026);
+        let share = universe.population() as f64 / (200.0 * 200.0);
+        assert!((share - density).abs() < 0.01, "{} for {}", share, density);
+    }
+}
@@ -0,0 +1,66 @@
+use lifeash::{
+    io::ParseError,
+    patterns,
+    store::{Directory, PatternStore},
+};
+
+fn embedded() -> Directory {
+    Directory::new(concat!(env!("CARGO_MANIFEST_DIR"), "/patterns"))
+}
+
+#[test]
+fn directories_hold_the_embedded_patterns() {
+    let mut store = embedded();
+    let names = store.names().unwrap();
+    assert_eq!(names.len(), patterns::catalog().len());
+    let mut loaded: Vec<_> = names.iter().map(|name| store.load(name).unwrap()).collect();
+    let mut expected: Vec<_> = patterns::catalog().iter().map(|e| e.pattern()).collect();
+    loaded.sort_by(|a, b| a.name.cmp(&b.name));
+    expected.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(loaded, expected);
+}
+
+#[test]
+fn names_stay_inside_the_directory() {
+    let mut store = embedded();
+    assert!(store.read("../Cargo.toml").is_err());
+    assert!(store.read("/etc/hostname").is_err());
+    assert!(matches!(
+        store.load("../Cargo.toml"),
+        Err(ParseError::Io(_))
+    ));
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn archives_read_like_directories() {
+    use std::io::{Cursor, Write};
+
+    use lifeash::store::Archive;
+
+    let mut directory = embedded();
+    let mut bytes = Cursor::new(Vec::new());
+    let mut zip = zip::ZipWriter::new(&mut bytes);
+    let options = zip::write::SimpleFileOptions::default();
+    for name in directory.names().unwrap() {
+        zip.start_file(format!("classics/{}", name), options)
+            .unwrap();
+        zip.write_all(directory.read(&name).unwrap().as_bytes())
+            .unwrap();
+    }
+    zip.start_file("classics/README.md", options).unwrap();
+    zip.finish().unwrap();
+
+    let mut archive = Archive::new(bytes).unwrap();
+    let names = archive.names().unwrap();
+    assert_eq!(names.len(), patterns::catalog().len());
+    for name in names {
+        let stripped = name.strip_prefix("classics/").unwrap();
+        assert_eq!(
+            archive.load(&name).unwrap(),
+            directory.load(stripped).unwrap()
+        );
+    }
+    assert!(archive.load("classics/README.md").is_err());
+    assert!(archive.read("missing.rle").is_err());
+}
@@ -0,0 +1,30 @@
+use lifeash::{prelude::*, Timeline};
+
+const GLIDER: &str = "bo$2bo$3o!";
+
+// a long run keeps a bounded number of snapshots, spread evenly over all of it
+#[test]
+fn long_runs_are_thinned() {
+    let mut universe = Universe::builder().timeline_interval(4).build();
+    universe.load_rle_at(GLIDER, (0, 0)).unwrap();
+    for _ in 0..5000 {
+        universe.evolve();
+    }
+    let timeline = universe.timeline();
+    let generations: Vec<_> = timeline.generations().collect();
+    assert!(generations.len() <= Timeline::CAPACITY);
+    assert!(generations.len() > Timeline::CAPACITY / 2);
+    assert_eq!(timeline.interval(), 4);
+    assert_eq!(timeline.spacing(), 32);
+    assert!(generations
+        .iter()
+        .enumerate()
+        .all(|(i, &generation)| generation == i as u128 * timeline.spacing()));
+
+    // released snapshots don't keep their trees alive
+    universe.collect_garbage();
+    assert!(universe.rewind_to(1234));
+    let mut expected = Universe::from_rle(GLIDER).unwrap();
+    expected.advance(1234);
+    assert_eq!(universe.content_hash(), expected.content_hash());
+}